@@ -0,0 +1,102 @@
+//! NAZARE: admin CLI for the ggoldca vaults
+mod command;
+mod parse;
+
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use command::Command;
+use ggoldca_client::transaction::{
+    build_transaction, send_transaction, serialize_transaction, simulate_transaction,
+    writable_accounts,
+};
+use ggoldca_client::ClientConfig;
+use solana_account_decoder::UiAccountData;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::Keypair;
+
+#[derive(Parser)]
+#[clap(name = "ggoldca-admin", version, about)]
+struct Opts {
+    /// RPC url or moniker (localhost, devnet, mainnet-beta). Defaults to the Solana CLI config
+    #[clap(long, short = 'u', global = true)]
+    url: Option<String>,
+    /// Admin keypair file. Defaults to the Solana CLI config
+    #[clap(long, short = 'k', global = true)]
+    keypair: Option<String>,
+    /// Print the serialized transaction and the simulated accounts instead of sending it
+    #[clap(long, global = true)]
+    dry_run: bool,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+/// Connection and signer shared by the subcommands
+pub struct Context {
+    pub rpc: RpcClient,
+    pub admin: Keypair,
+    pub dry_run: bool,
+}
+
+impl Context {
+    fn new(opts: &Opts) -> Result<Self> {
+        let config = ClientConfig::load(opts.url.as_deref(), opts.keypair.as_deref())?;
+
+        Ok(Self {
+            rpc: config.rpc_client(),
+            admin: config.keypair,
+            dry_run: opts.dry_run,
+        })
+    }
+
+    /// Send the instructions in a single transaction, or simulate it in dry-run mode,
+    /// failing when the simulation does
+    pub fn execute(&self, instructions: &[Instruction], extra_signers: &[&Keypair]) -> Result<()> {
+        let transaction = build_transaction(&self.rpc, instructions, &self.admin, extra_signers)?;
+
+        if !self.dry_run {
+            let signature = send_transaction(&self.rpc, &transaction)?;
+            println!("Signature: {}", signature);
+            return Ok(());
+        }
+
+        println!("Transaction: {}", serialize_transaction(&transaction)?);
+
+        let simulation =
+            simulate_transaction(&self.rpc, &transaction, &writable_accounts(instructions))?;
+
+        for log in simulation.logs {
+            println!("  {}", log);
+        }
+        if let Some(err) = simulation.err {
+            return Err(anyhow!("Simulation failed: {}", err));
+        }
+        println!("Simulation succeeded");
+
+        println!("Accounts:");
+        for (address, account) in simulation.accounts {
+            match account {
+                Some(account) => {
+                    let data = match account.data {
+                        UiAccountData::Binary(data, _) => data,
+                        data => format!("{:?}", data),
+                    };
+                    println!(
+                        "  {} owner: {} lamports: {} data: {}",
+                        address, account.owner, account.lamports, data
+                    );
+                }
+                None => println!("  {} closed", address),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn main() -> Result<()> {
+    let opts = Opts::parse();
+    let ctx = Context::new(&opts)?;
+
+    opts.command.run(&ctx)
+}
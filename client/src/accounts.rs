@@ -0,0 +1,240 @@
+use crate::error::{ClientError, Result};
+use crate::pda::{PositionKeys, VaultKeys, WhirlpoolKeys};
+use crate::tick_array::{swap_tick_arrays, SWAP_TICK_ARRAYS};
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::TokenAccount;
+use ggoldca::math::liquidity::token_amounts_from_liquidity;
+use ggoldca::math::quote::{reinvest_quote, swap_params_for_ratio, WithdrawQuote};
+use ggoldca::state::VaultAccount;
+use ggoldca::{BPS_SCALE, VAULT_VERSION};
+use solana_client::rpc_client::RpcClient;
+use whirlpool::state::position::Position;
+use whirlpool::state::whirlpool::Whirlpool;
+
+/// Fetch and deserialize an account of the ggoldca program
+pub fn fetch_account<T: AccountDeserialize>(rpc: &RpcClient, address: &Pubkey) -> Result<T> {
+    let data = rpc.get_account_data(address)?;
+    T::try_deserialize(&mut data.as_slice()).map_err(|_| ClientError::AccountDeserialize(*address))
+}
+
+/// Deserialize the data of a vault account, rejecting vaults not migrated to the current version
+pub fn deserialize_vault_account(address: &Pubkey, data: &[u8]) -> Result<VaultAccount> {
+    match data.get(8) {
+        Some(&VAULT_VERSION) => VaultAccount::try_deserialize(&mut &data[..])
+            .map_err(|_| ClientError::AccountDeserialize(*address)),
+        Some(&version) => Err(ClientError::UnsupportedVaultVersion(*address, version)),
+        None => Err(ClientError::AccountDeserialize(*address)),
+    }
+}
+
+pub fn fetch_vault_account(rpc: &RpcClient, address: &Pubkey) -> Result<VaultAccount> {
+    let data = rpc.get_account_data(address)?;
+    deserialize_vault_account(address, &data)
+}
+
+pub fn fetch_whirlpool(rpc: &RpcClient, address: &Pubkey) -> Result<Whirlpool> {
+    use anchor_lang_for_whirlpool::AccountDeserialize;
+
+    let data = rpc.get_account_data(address)?;
+    Whirlpool::try_deserialize(&mut data.as_slice())
+        .map_err(|_| ClientError::AccountDeserialize(*address))
+}
+
+pub fn fetch_position(rpc: &RpcClient, address: &Pubkey) -> Result<Position> {
+    use anchor_lang_for_whirlpool::AccountDeserialize;
+
+    let data = rpc.get_account_data(address)?;
+    Position::try_deserialize(&mut data.as_slice())
+        .map_err(|_| ClientError::AccountDeserialize(*address))
+}
+
+/// Current state of a vault with the addresses needed to build its instructions
+pub struct VaultState {
+    pub keys: VaultKeys,
+    pub vault: VaultAccount,
+    pub pool: Whirlpool,
+    pub pool_keys: WhirlpoolKeys,
+}
+
+impl VaultState {
+    pub fn fetch(rpc: &RpcClient, vault_account: &Pubkey) -> Result<Self> {
+        let vault = fetch_vault_account(rpc, vault_account)?;
+        let pool = fetch_whirlpool(rpc, &vault.whirlpool_id)?;
+
+        Ok(Self {
+            keys: VaultKeys::from_vault_account(&vault),
+            pool_keys: WhirlpoolKeys::from_whirlpool(vault.whirlpool_id, &pool),
+            vault,
+            pool,
+        })
+    }
+
+    /// Keys of one of the vault positions
+    pub fn position_keys(&self, rpc: &RpcClient, position: &Pubkey) -> Result<PositionKeys> {
+        if !self.vault.position_address_exists(*position) {
+            return Err(ClientError::PositionNotFound(*position));
+        }
+
+        let position_data = fetch_position(rpc, position)?;
+        Ok(PositionKeys::from_position(
+            &self.keys.vault_account,
+            &self.pool_keys,
+            &position_data,
+        ))
+    }
+
+    /// Keys of the position currently holding the vault liquidity
+    pub fn active_position_keys(&self, rpc: &RpcClient) -> Result<PositionKeys> {
+        if self.vault.positions.is_empty() {
+            return Err(ClientError::NoActivePosition(self.keys.vault_account));
+        }
+
+        self.position_keys(rpc, &self.vault.active_position_key())
+    }
+
+    /// Balances of the vault input token accounts
+    pub fn vault_balances(&self, rpc: &RpcClient) -> Result<(u64, u64)> {
+        let account_a: TokenAccount = fetch_account(rpc, &self.keys.vault_input_token_a_account)?;
+        let account_b: TokenAccount = fetch_account(rpc, &self.keys.vault_input_token_b_account)?;
+        Ok((account_a.amount, account_b.amount))
+    }
+
+    /// Minimum amounts withdrawn by rebalance from current_position, those at the current
+    /// price less max_slippage_bps
+    pub fn rebalance_min_amounts(
+        &self,
+        rpc: &RpcClient,
+        current_position: &Pubkey,
+        max_slippage_bps: u16,
+    ) -> Result<(u64, u64)> {
+        let current = fetch_position(rpc, current_position)?;
+
+        let (amount_a, amount_b) = WithdrawQuote {
+            liquidity: current.liquidity,
+            vault_amount_a: 0,
+            vault_amount_b: 0,
+        }
+        .total_amounts(
+            self.pool.sqrt_price,
+            self.pool.tick_current_index,
+            current.tick_lower_index,
+            current.tick_upper_index,
+        )
+        .map_err(ClientError::Math)?;
+
+        Ok((
+            min_amount_with_slippage(amount_a, max_slippage_bps),
+            min_amount_with_slippage(amount_b, max_slippage_bps),
+        ))
+    }
+
+    /// Tick arrays of the swap made by rebalance to match the ratio of new_position,
+    /// following the amounts the program will hold after removing the current liquidity
+    pub fn rebalance_swap_tick_arrays(
+        &self,
+        rpc: &RpcClient,
+        current_position: &Pubkey,
+        new_position: &Pubkey,
+    ) -> Result<[Pubkey; SWAP_TICK_ARRAYS]> {
+        let current = fetch_position(rpc, current_position)?;
+        let new = fetch_position(rpc, new_position)?;
+        let (vault_amount_a, vault_amount_b) = self.vault_balances(rpc)?;
+
+        let sqrt_price = self.pool.sqrt_price;
+        let tick = self.pool.tick_current_index;
+
+        let (withdrawn_a, withdrawn_b) = token_amounts_from_liquidity(
+            sqrt_price,
+            tick,
+            current.tick_lower_index,
+            current.tick_upper_index,
+            current.liquidity,
+            false,
+        )
+        .map_err(ClientError::Math)?;
+
+        let (position_amount_a, position_amount_b) = token_amounts_from_liquidity(
+            sqrt_price,
+            tick,
+            new.tick_lower_index,
+            new.tick_upper_index,
+            current.liquidity,
+            false,
+        )
+        .map_err(ClientError::Math)?;
+
+        let swap_params = swap_params_for_ratio(
+            vault_amount_a.saturating_add(withdrawn_a),
+            vault_amount_b.saturating_add(withdrawn_b),
+            position_amount_a,
+            position_amount_b,
+            sqrt_price,
+        )
+        .map_err(ClientError::Math)?;
+
+        Ok(swap_tick_arrays(
+            &self.pool_keys.whirlpool,
+            tick,
+            self.pool_keys.tick_spacing,
+            swap_params.a_to_b,
+        ))
+    }
+
+    /// Tick arrays of the swap made by reinvest to match the ratio of the active position,
+    /// following the current vault balances
+    pub fn reinvest_swap_tick_arrays(&self, rpc: &RpcClient) -> Result<[Pubkey; SWAP_TICK_ARRAYS]> {
+        let position = fetch_position(rpc, &self.vault.active_position_key())?;
+        let (vault_amount_a, vault_amount_b) = self.vault_balances(rpc)?;
+
+        let sqrt_price = self.pool.sqrt_price;
+        let tick = self.pool.tick_current_index;
+
+        let (position_amount_a, position_amount_b) = token_amounts_from_liquidity(
+            sqrt_price,
+            tick,
+            position.tick_lower_index,
+            position.tick_upper_index,
+            position.liquidity,
+            false,
+        )
+        .map_err(ClientError::Math)?;
+
+        let quote = reinvest_quote(
+            vault_amount_a.saturating_add(position.fee_owed_a),
+            vault_amount_b.saturating_add(position.fee_owed_b),
+            position_amount_a,
+            position_amount_b,
+            sqrt_price,
+            self.vault.max_swap_slippage_bps,
+        )
+        .map_err(ClientError::Math)?;
+
+        Ok(swap_tick_arrays(
+            &self.pool_keys.whirlpool,
+            tick,
+            self.pool_keys.tick_spacing,
+            quote.swap.a_to_b,
+        ))
+    }
+}
+
+/// amount less max_slippage_bps, rounded down
+fn min_amount_with_slippage(amount: u64, max_slippage_bps: u16) -> u64 {
+    let remaining_bps = BPS_SCALE.saturating_sub(u64::from(max_slippage_bps));
+    (u128::from(amount) * u128::from(remaining_bps) / u128::from(BPS_SCALE)) as u64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_min_amount_with_slippage() {
+        assert_eq!(min_amount_with_slippage(10_000, 0), 10_000);
+        assert_eq!(min_amount_with_slippage(10_000, 100), 9_900);
+        assert_eq!(min_amount_with_slippage(999, 100), 989);
+        assert_eq!(min_amount_with_slippage(u64::MAX, 10_000), 0);
+        assert_eq!(min_amount_with_slippage(10_000, u16::MAX), 0);
+    }
+}
@@ -0,0 +1,55 @@
+//! Connection and signer settings, defaulting to the Solana CLI config
+use crate::error::{ClientError, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::signature::{read_keypair_file, Keypair};
+
+/// RPC url of a moniker (localhost, devnet, mainnet-beta or their initial), other urls are kept
+pub fn normalize_url(url: &str) -> String {
+    match url {
+        "l" | "localhost" => "http://localhost:8899",
+        "d" | "devnet" => "https://api.devnet.solana.com",
+        "m" | "mainnet-beta" => "https://api.mainnet-beta.solana.com",
+        url => url,
+    }
+    .to_string()
+}
+
+pub struct ClientConfig {
+    pub url: String,
+    pub keypair: Keypair,
+}
+
+impl ClientConfig {
+    /// Load the given url and keypair file, the ones of the Solana CLI config otherwise
+    pub fn load(url: Option<&str>, keypair_path: Option<&str>) -> Result<Self> {
+        let config = match solana_cli_config::CONFIG_FILE.as_ref() {
+            Some(config_file) => solana_cli_config::Config::load(config_file).unwrap_or_default(),
+            None => solana_cli_config::Config::default(),
+        };
+
+        let url = normalize_url(url.unwrap_or(&config.json_rpc_url));
+        let keypair_path = keypair_path.unwrap_or(&config.keypair_path);
+        let keypair = read_keypair_file(keypair_path)
+            .map_err(|err| ClientError::Keypair(keypair_path.to_string(), err.to_string()))?;
+
+        Ok(Self { url, keypair })
+    }
+
+    pub fn rpc_client(&self) -> RpcClient {
+        RpcClient::new_with_commitment(self.url.clone(), CommitmentConfig::confirmed())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normalize_url() {
+        assert_eq!(normalize_url("l"), "http://localhost:8899");
+        assert_eq!(normalize_url("devnet"), "https://api.devnet.solana.com");
+        assert_eq!(normalize_url("m"), "https://api.mainnet-beta.solana.com");
+        assert_eq!(normalize_url("https://rpc.test"), "https://rpc.test");
+    }
+}
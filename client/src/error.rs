@@ -0,0 +1,31 @@
+use anchor_lang::prelude::Pubkey;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    #[error(transparent)]
+    Rpc(#[from] solana_client::client_error::ClientError),
+
+    #[error("Account {0} could not be deserialized")]
+    AccountDeserialize(Pubkey),
+
+    #[error("Vault account {0} has version {1}, it must be migrated first")]
+    UnsupportedVaultVersion(Pubkey, u8),
+
+    #[error("Vault account {0} has no open position")]
+    NoActivePosition(Pubkey),
+
+    #[error("Position {0} is not owned by the vault")]
+    PositionNotFound(Pubkey),
+
+    #[error("Vault math failed: {0}")]
+    Math(anchor_lang::error::Error),
+
+    #[error(transparent)]
+    Serialize(#[from] bincode::Error),
+
+    #[error("Cannot read keypair {0}: {1}")]
+    Keypair(String, String),
+}
+
+pub type Result<T> = std::result::Result<T, ClientError>;
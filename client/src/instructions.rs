@@ -0,0 +1,323 @@
+//! Instruction builders, listing the program accounts structs followed by their remaining accounts
+pub mod admin;
+pub mod farm;
+pub mod keeper;
+pub mod user;
+
+pub use admin::*;
+pub use farm::*;
+pub use keeper::*;
+pub use user::*;
+
+use crate::pda::WhirlpoolKeys;
+use crate::tick_array::SWAP_TICK_ARRAYS;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::InstructionData;
+
+fn ggoldca_instruction(accounts: Vec<AccountMeta>, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: ggoldca::ID,
+        accounts,
+        data: data.data(),
+    }
+}
+
+/// Venue specific accounts of a swap through a whirlpool
+pub fn whirlpool_swap_accounts(
+    pool: &WhirlpoolKeys,
+    tick_arrays: [Pubkey; SWAP_TICK_ARRAYS],
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(pool.whirlpool, false),
+        AccountMeta::new(pool.token_vault_a, false),
+        AccountMeta::new(pool.token_vault_b, false),
+        AccountMeta::new(tick_arrays[0], false),
+        AccountMeta::new(tick_arrays[1], false),
+        AccountMeta::new(tick_arrays[2], false),
+        AccountMeta::new(pool.oracle, false),
+    ]
+}
+
+/// Venue specific accounts of a swap through an spl token swap pool (Orca v2 included)
+pub fn token_swap_accounts(
+    amm_id: Pubkey,
+    amm_authority: Pubkey,
+    pool_source_token_account: Pubkey,
+    pool_destination_token_account: Pubkey,
+    pool_lp_token_mint: Pubkey,
+    fees_account: Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(amm_id, false),
+        AccountMeta::new_readonly(amm_authority, false),
+        AccountMeta::new(pool_source_token_account, false),
+        AccountMeta::new(pool_destination_token_account, false),
+        AccountMeta::new(pool_lp_token_mint, false),
+        AccountMeta::new(fees_account, false),
+    ]
+}
+
+/// Accounts of a single hop of a rewards route
+pub struct RouteHopAccounts {
+    pub swap_program: Pubkey,
+    /// Destination for the last hop, vault ATA of the hop output mint otherwise
+    pub output_token_account: Pubkey,
+    pub venue_accounts: Vec<AccountMeta>,
+}
+
+/// Accounts needed to swap the rewards of a market, directly or through a route
+pub struct RewardsSwapAccounts {
+    /// Program of the market, or of the first hop for routes
+    pub swap_program: Pubkey,
+    /// Venue accounts of the market, or of every hop preceded by its program and output account
+    pub swap_accounts: Vec<AccountMeta>,
+    pub is_route: bool,
+    /// Pool used by ReferencePool price bounds
+    pub reference_pool: Option<Pubkey>,
+}
+
+impl RewardsSwapAccounts {
+    pub fn direct(swap_program: Pubkey, venue_accounts: Vec<AccountMeta>) -> Self {
+        Self {
+            swap_program,
+            swap_accounts: venue_accounts,
+            is_route: false,
+            reference_pool: None,
+        }
+    }
+
+    pub fn route(hops: Vec<RouteHopAccounts>) -> Self {
+        let swap_program = hops.first().map(|hop| hop.swap_program).unwrap_or_default();
+        let swap_accounts = hops
+            .into_iter()
+            .flat_map(|hop| {
+                [
+                    AccountMeta::new_readonly(hop.swap_program, false),
+                    AccountMeta::new(hop.output_token_account, false),
+                ]
+                .into_iter()
+                .chain(hop.venue_accounts)
+            })
+            .collect();
+
+        Self {
+            swap_program,
+            swap_accounts,
+            is_route: true,
+            reference_pool: None,
+        }
+    }
+
+    pub fn with_reference_pool(mut self, reference_pool: Pubkey) -> Self {
+        self.reference_pool = Some(reference_pool);
+        self
+    }
+
+    /// Remaining accounts of swap_rewards, where the swap program is a named account
+    fn swap_rewards_remaining_accounts(&self) -> Vec<AccountMeta> {
+        self.swap_accounts
+            .iter()
+            .cloned()
+            .chain(self.price_bound_accounts())
+            .collect()
+    }
+
+    /// Swap accounts of a harvest_and_compound rewards group. Direct markets
+    /// are preceded by their swap program
+    fn harvest_remaining_accounts(&self) -> Vec<AccountMeta> {
+        let swap_program =
+            (!self.is_route).then(|| AccountMeta::new_readonly(self.swap_program, false));
+
+        swap_program
+            .into_iter()
+            .chain(self.swap_accounts.iter().cloned())
+            .chain(self.price_bound_accounts())
+            .collect()
+    }
+
+    fn price_bound_accounts(&self) -> Option<AccountMeta> {
+        self.reference_pool
+            .map(|reference_pool| AccountMeta::new_readonly(reference_pool, false))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::pda::{PositionKeys, VaultId, VaultKeys};
+    use anchor_spl::associated_token::get_associated_token_address;
+
+    /// Accounts of the program PositionAccounts
+    const POSITION_ACCOUNTS: usize = 5;
+
+    fn test_keys() -> (VaultKeys, WhirlpoolKeys, PositionKeys) {
+        let keys = VaultKeys::new(
+            VaultId {
+                whirlpool: Pubkey::new_unique(),
+                id: 0,
+            },
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let pool = WhirlpoolKeys {
+            whirlpool: keys.vault_id.whirlpool,
+            token_vault_a: Pubkey::new_unique(),
+            token_vault_b: Pubkey::new_unique(),
+            oracle: Pubkey::new_unique(),
+            tick_spacing: 64,
+        };
+        let position = PositionKeys::new(&keys.vault_account, &pool, Pubkey::new_unique(), -64, 64);
+
+        (keys, pool, position)
+    }
+
+    #[test]
+    fn test_deposit_accounts() {
+        let user = Pubkey::new_unique();
+        let (keys, pool, position) = test_keys();
+
+        let ix = deposit(&user, &keys, &pool, &position, 1, 1, 1, false);
+        assert_eq!(ix.program_id, ggoldca::ID);
+        assert_eq!(ix.accounts.len(), 12 + POSITION_ACCOUNTS);
+        assert_eq!(ix.accounts[0], AccountMeta::new_readonly(user, true));
+        assert_eq!(ix.accounts[1].pubkey, keys.vault_account);
+        assert_eq!(ix.accounts.iter().filter(|meta| meta.is_signer).count(), 1);
+
+        let ix = withdraw(&user, &keys, &pool, &position, 1, 0, 0, true);
+        let (user_position, _) = crate::pda::user_position(&keys.vault_account, &user);
+        assert_eq!(ix.accounts.len(), 13 + POSITION_ACCOUNTS);
+        assert_eq!(
+            ix.accounts.last(),
+            Some(&AccountMeta::new(user_position, false))
+        );
+    }
+
+    #[test]
+    fn test_collect_fees_accounts() {
+        let keeper = Pubkey::new_unique();
+        let (keys, pool, position) = test_keys();
+
+        let ix = keeper::collect_fees(None, &keys, &pool, &position);
+        assert_eq!(ix.accounts.len(), 9 + POSITION_ACCOUNTS);
+        assert!(ix.accounts.iter().all(|meta| !meta.is_signer));
+
+        let ix = keeper::collect_fees(Some(&keeper), &keys, &pool, &position);
+        assert_eq!(ix.accounts.len(), 11 + POSITION_ACCOUNTS);
+        assert_eq!(
+            ix.accounts[ix.accounts.len() - 2..],
+            [
+                AccountMeta::new(
+                    get_associated_token_address(&keeper, &keys.input_token_a_mint),
+                    false
+                ),
+                AccountMeta::new(
+                    get_associated_token_address(&keeper, &keys.input_token_b_mint),
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_swap_rewards_accounts() {
+        let (keys, pool, _) = test_keys();
+        let rewards_mint = Pubkey::new_unique();
+        let reference_pool = Pubkey::new_unique();
+        let tick_arrays = [Pubkey::new_unique(); SWAP_TICK_ARRAYS];
+
+        let swap =
+            RewardsSwapAccounts::direct(whirlpool::ID, whirlpool_swap_accounts(&pool, tick_arrays))
+                .with_reference_pool(reference_pool);
+        let ix = keeper::swap_rewards(None, &keys, &rewards_mint, &keys.input_token_a_mint, &swap);
+
+        assert_eq!(ix.accounts.len(), 5 + 7 + 1);
+        assert_eq!(
+            ix.accounts[4],
+            AccountMeta::new_readonly(whirlpool::ID, false)
+        );
+        assert_eq!(ix.accounts[5], AccountMeta::new(pool.whirlpool, false));
+        assert_eq!(
+            ix.accounts.last(),
+            Some(&AccountMeta::new_readonly(reference_pool, false))
+        );
+    }
+
+    #[test]
+    fn test_reinvest_and_harvest_accounts() {
+        let (keys, pool, position) = test_keys();
+        let tick_arrays = [Pubkey::new_unique(); SWAP_TICK_ARRAYS];
+
+        let ix = keeper::reinvest(None, &keys, &pool, &position, tick_arrays, false);
+        assert_eq!(ix.accounts.len(), 12 + POSITION_ACCOUNTS);
+
+        let ix = keeper::reinvest(None, &keys, &pool, &position, tick_arrays, true);
+        let (vault_history, _) = crate::pda::vault_history(&keys.vault_account);
+        assert_eq!(
+            ix.accounts.last(),
+            Some(&AccountMeta::new(vault_history, false))
+        );
+
+        // The keeper ATAs follow the vault history
+        let keeper = Pubkey::new_unique();
+        let ix = keeper::reinvest(Some(&keeper), &keys, &pool, &position, tick_arrays, true);
+        assert_eq!(ix.accounts.len(), 12 + POSITION_ACCOUNTS + 3);
+        assert_eq!(
+            ix.accounts[12 + POSITION_ACCOUNTS],
+            AccountMeta::new(vault_history, false)
+        );
+        assert_eq!(
+            ix.accounts.last(),
+            Some(&AccountMeta::new(
+                get_associated_token_address(&keeper, &keys.input_token_b_mint),
+                false
+            ))
+        );
+
+        let rewards = [keeper::HarvestRewardsAccounts {
+            rewards_mint: Pubkey::new_unique(),
+            reward_vault: Pubkey::new_unique(),
+            swap: None,
+        }];
+        let ix = keeper::harvest_and_compound(None, &keys, &pool, &position, tick_arrays, &rewards);
+        assert_eq!(ix.accounts.len(), 14 + POSITION_ACCOUNTS + 3);
+        assert_eq!(
+            ix.accounts[14 + POSITION_ACCOUNTS],
+            AccountMeta::new(rewards[0].reward_vault, false)
+        );
+    }
+
+    #[test]
+    fn test_rebalance_accounts() {
+        let admin = Pubkey::new_unique();
+        let (keys, pool, position) = test_keys();
+        let new_position =
+            PositionKeys::new(&keys.vault_account, &pool, Pubkey::new_unique(), 0, 128);
+        let params = admin::RebalanceParams {
+            min_amount_a: 0,
+            min_amount_b: 0,
+            reference_sqrt_price: 1 << 64,
+            max_price_deviation_bps: 100,
+        };
+
+        let ix = admin::rebalance(&admin, &keys, &pool, &position, &new_position, params, None);
+        assert_eq!(ix.accounts.len(), 8 + 2 * POSITION_ACCOUNTS);
+        assert_eq!(ix.accounts[0], AccountMeta::new_readonly(admin, true));
+
+        let tick_arrays = [Pubkey::new_unique(); SWAP_TICK_ARRAYS];
+        let ix = admin::rebalance(
+            &admin,
+            &keys,
+            &pool,
+            &position,
+            &new_position,
+            params,
+            Some(tick_arrays),
+        );
+        assert_eq!(ix.accounts.len(), 12 + 2 * POSITION_ACCOUNTS);
+        assert_eq!(
+            ix.accounts.last(),
+            Some(&AccountMeta::new(pool.oracle, false))
+        );
+    }
+}
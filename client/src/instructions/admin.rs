@@ -0,0 +1,517 @@
+//! Instructions restricted to the program admins
+use super::ggoldca_instruction;
+use crate::pda::{self, PositionKeys, VaultKeys, WhirlpoolKeys};
+use crate::tick_array::SWAP_TICK_ARRAYS;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{system_program, sysvar};
+use anchor_lang::ToAccountMetas;
+use ggoldca::accounts;
+use ggoldca::instruction as ix;
+use ggoldca::instructions::MarketRewardsInfoInput;
+use ggoldca::state::{KeeperBounty, RangeConfig, RewardsPriceBound, RouteHop};
+
+pub fn initialize_vault(
+    admin: &Pubkey,
+    keys: &VaultKeys,
+    fee: u64,
+    min_slots_for_reinvest: u64,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::InitializeVault {
+            user_signer: *admin,
+            whirlpool: keys.vault_id.whirlpool,
+            input_token_a_mint_address: keys.input_token_a_mint,
+            input_token_b_mint_address: keys.input_token_b_mint,
+            vault_account: keys.vault_account,
+            vault_input_token_a_account: keys.vault_input_token_a_account,
+            vault_input_token_b_account: keys.vault_input_token_b_account,
+            vault_lp_token_mint_pubkey: keys.vault_lp_token_mint,
+            system_program: system_program::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            token_program: anchor_spl::token::ID,
+            rent: sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        ix::InitializeVault {
+            id: keys.vault_id.id,
+            fee,
+            min_slots_for_reinvest,
+        },
+    )
+}
+
+pub fn migrate_vault(admin: &Pubkey, vault_account: &Pubkey) -> Instruction {
+    ggoldca_instruction(
+        accounts::MigrateVault {
+            user_signer: *admin,
+            vault_account: *vault_account,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        ix::MigrateVault {},
+    )
+}
+
+/// Close an empty vault. The vault and treasury ATAs of other_mints are passed so the
+/// vault accounts holding them are swept and closed too
+pub fn close_vault(admin: &Pubkey, keys: &VaultKeys, other_mints: &[Pubkey]) -> Instruction {
+    let mut accounts = accounts::CloseVault {
+        user_signer: *admin,
+        vault_account: keys.vault_account,
+        vault_lp_token_mint_pubkey: keys.vault_lp_token_mint,
+        vault_input_token_a_account: keys.vault_input_token_a_account,
+        vault_input_token_b_account: keys.vault_input_token_b_account,
+        treasury_token_a_account: keys.treasury_token_a_account,
+        treasury_token_b_account: keys.treasury_token_b_account,
+        token_program: anchor_spl::token::ID,
+    }
+    .to_account_metas(None);
+    for mint in other_mints {
+        accounts.push(AccountMeta::new(keys.vault_token_account(mint), false));
+        accounts.push(AccountMeta::new(keys.treasury_token_account(mint), false));
+    }
+
+    ggoldca_instruction(accounts, ix::CloseVault {})
+}
+
+pub fn set_vault_pause_status(
+    admin: &Pubkey,
+    vault_account: &Pubkey,
+    is_paused: bool,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::SetVaultPauseStatus {
+            user_signer: *admin,
+            vault_account: *vault_account,
+        }
+        .to_account_metas(None),
+        ix::SetVaultPauseStatus { is_paused },
+    )
+}
+
+pub fn set_vault_ui_status(admin: &Pubkey, vault_account: &Pubkey, is_active: bool) -> Instruction {
+    ggoldca_instruction(
+        accounts::SetVaultUiStatus {
+            user_signer: *admin,
+            vault_account: *vault_account,
+        }
+        .to_account_metas(None),
+        ix::SetVaultUiStatus { is_active },
+    )
+}
+
+pub fn set_vault_fee(admin: &Pubkey, vault_account: &Pubkey, fee: u64) -> Instruction {
+    ggoldca_instruction(
+        accounts::SetVaultFee {
+            user_signer: *admin,
+            vault_account: *vault_account,
+        }
+        .to_account_metas(None),
+        ix::SetVaultFee { fee },
+    )
+}
+
+pub fn set_keeper_bounty(
+    admin: &Pubkey,
+    vault_account: &Pubkey,
+    keeper_bounty: KeeperBounty,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::SetKeeperBounty {
+            user_signer: *admin,
+            vault_account: *vault_account,
+        }
+        .to_account_metas(None),
+        ix::SetKeeperBounty { keeper_bounty },
+    )
+}
+
+pub fn set_max_price_deviation(
+    admin: &Pubkey,
+    vault_account: &Pubkey,
+    max_price_deviation_bps: u16,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::SetMaxPriceDeviation {
+            user_signer: *admin,
+            vault_account: *vault_account,
+        }
+        .to_account_metas(None),
+        ix::SetMaxPriceDeviation {
+            max_price_deviation_bps,
+        },
+    )
+}
+
+pub fn set_max_swap_slippage(
+    admin: &Pubkey,
+    vault_account: &Pubkey,
+    max_slippage_bps: u16,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::SetMaxSwapSlippage {
+            user_signer: *admin,
+            vault_account: *vault_account,
+        }
+        .to_account_metas(None),
+        ix::SetMaxSwapSlippage { max_slippage_bps },
+    )
+}
+
+pub fn set_min_fees(
+    admin: &Pubkey,
+    vault_account: &Pubkey,
+    min_fees_token_a: u64,
+    min_fees_token_b: u64,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::SetMinFees {
+            user_signer: *admin,
+            vault_account: *vault_account,
+        }
+        .to_account_metas(None),
+        ix::SetMinFees {
+            min_fees_token_a,
+            min_fees_token_b,
+        },
+    )
+}
+
+pub fn set_min_slots_for_reinvest(
+    admin: &Pubkey,
+    vault_account: &Pubkey,
+    min_slots: u64,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::SetMinSlotsForReinvest {
+            user_signer: *admin,
+            vault_account: *vault_account,
+        }
+        .to_account_metas(None),
+        ix::SetMinSlotsForReinvest { min_slots },
+    )
+}
+
+pub fn set_range_config(
+    admin: &Pubkey,
+    vault_account: &Pubkey,
+    range_config: RangeConfig,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::SetRangeConfig {
+            user_signer: *admin,
+            vault_account: *vault_account,
+        }
+        .to_account_metas(None),
+        ix::SetRangeConfig { range_config },
+    )
+}
+
+pub fn set_market_rewards(
+    admin: &Pubkey,
+    keys: &VaultKeys,
+    rewards_mint: &Pubkey,
+    destination_token_account: &Pubkey,
+    market_rewards: MarketRewardsInfoInput,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::SetMarketRewards {
+            user_signer: *admin,
+            vault_account: keys.vault_account,
+            whirlpool: keys.vault_id.whirlpool,
+            rewards_mint: *rewards_mint,
+            destination_token_account: *destination_token_account,
+        }
+        .to_account_metas(None),
+        ix::SetMarketRewards { market_rewards },
+    )
+}
+
+pub fn set_rewards_price_bound(
+    admin: &Pubkey,
+    vault_account: &Pubkey,
+    rewards_mint: &Pubkey,
+    price_bound: RewardsPriceBound,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::SetRewardsPriceBound {
+            user_signer: *admin,
+            vault_account: *vault_account,
+            rewards_mint: *rewards_mint,
+        }
+        .to_account_metas(None),
+        ix::SetRewardsPriceBound { price_bound },
+    )
+}
+
+/// Set the route swapping the rewards. No hops resets the route to the direct market
+pub fn set_rewards_route(
+    admin: &Pubkey,
+    vault_account: &Pubkey,
+    rewards_mint: &Pubkey,
+    destination_token_account: &Pubkey,
+    hops: Vec<RouteHop>,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::SetRewardsRoute {
+            user_signer: *admin,
+            vault_account: *vault_account,
+            rewards_mint: *rewards_mint,
+            destination_token_account: *destination_token_account,
+        }
+        .to_account_metas(None),
+        ix::SetRewardsRoute { hops },
+    )
+}
+
+pub fn set_rewards_swap_chunking(
+    admin: &Pubkey,
+    vault_account: &Pubkey,
+    rewards_mint: &Pubkey,
+    max_amount_in: u64,
+    min_slots_between_swaps: u64,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::SetRewardsSwapChunking {
+            user_signer: *admin,
+            vault_account: *vault_account,
+            rewards_mint: *rewards_mint,
+        }
+        .to_account_metas(None),
+        ix::SetRewardsSwapChunking {
+            max_amount_in,
+            min_slots_between_swaps,
+        },
+    )
+}
+
+/// Create (first_time) or update the metadata of the vault LP token
+pub fn set_token_metadata(
+    admin: &Pubkey,
+    keys: &VaultKeys,
+    token_name: String,
+    token_symbol: String,
+    token_uri: String,
+    first_time: bool,
+) -> Instruction {
+    let (metadata_account, _) = pda::token_metadata(&keys.vault_lp_token_mint);
+
+    ggoldca_instruction(
+        accounts::SetTokenMetadata {
+            metadata_account,
+            vault_account: keys.vault_account,
+            vault_lp_token_mint_pubkey: keys.vault_lp_token_mint,
+            user_signer: *admin,
+            token_metadata_program: mpl_token_metadata::id(),
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: system_program::ID,
+            token_program: anchor_spl::token::ID,
+            rent: sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        ix::SetTokenMetadata {
+            token_name,
+            token_symbol,
+            token_uri,
+            first_time,
+        },
+    )
+}
+
+fn open_position_accounts(
+    admin: &Pubkey,
+    keys: &VaultKeys,
+    pool: &WhirlpoolKeys,
+    position_mint: &Pubkey,
+) -> (Vec<AccountMeta>, u8) {
+    let (position, bump) = pda::whirlpool_position(position_mint);
+
+    let accounts = accounts::OpenPosition {
+        user_signer: *admin,
+        vault_account: keys.vault_account,
+        whirlpool_program_id: whirlpool::ID,
+        position,
+        position_mint: *position_mint,
+        position_token_account: keys.vault_token_account(position_mint),
+        whirlpool: pool.whirlpool,
+        token_program: anchor_spl::token::ID,
+        system_program: system_program::ID,
+        rent: sysvar::rent::ID,
+        associated_token_program: anchor_spl::associated_token::ID,
+    }
+    .to_account_metas(None);
+
+    (accounts, bump)
+}
+
+/// Open a vault position. position_mint is a new keypair that must sign the transaction
+pub fn open_position(
+    admin: &Pubkey,
+    keys: &VaultKeys,
+    pool: &WhirlpoolKeys,
+    position_mint: &Pubkey,
+    tick_lower_index: i32,
+    tick_upper_index: i32,
+) -> Instruction {
+    let (accounts, bump) = open_position_accounts(admin, keys, pool, position_mint);
+
+    ggoldca_instruction(
+        accounts,
+        ix::OpenPosition {
+            bump,
+            tick_lower_index,
+            tick_upper_index,
+        },
+    )
+}
+
+/// Open a vault position centered on the current price, following the vault range config
+pub fn open_centered_position(
+    admin: &Pubkey,
+    keys: &VaultKeys,
+    pool: &WhirlpoolKeys,
+    position_mint: &Pubkey,
+) -> Instruction {
+    let (accounts, bump) = open_position_accounts(admin, keys, pool, position_mint);
+
+    ggoldca_instruction(accounts, ix::OpenCenteredPosition { bump })
+}
+
+pub fn close_position(
+    admin: &Pubkey,
+    vault_account: &Pubkey,
+    position: &PositionKeys,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::ClosePosition {
+            user_signer: *admin,
+            vault_account: *vault_account,
+            whirlpool_program_id: whirlpool::ID,
+            position: position.position,
+            position_mint: position.position_mint,
+            position_token_account: position.position_token_account,
+            token_program: anchor_spl::token::ID,
+        }
+        .to_account_metas(None),
+        ix::ClosePosition {},
+    )
+}
+
+/// Arguments of the rebalance instruction
+#[derive(Copy, Clone, Debug)]
+pub struct RebalanceParams {
+    pub min_amount_a: u64,
+    pub min_amount_b: u64,
+    pub reference_sqrt_price: u128,
+    pub max_price_deviation_bps: u16,
+}
+
+/// Move the vault liquidity to new_position. When swap_tick_arrays is given, the excess
+/// tokens are swapped to match the ratio of the new position
+pub fn rebalance(
+    admin: &Pubkey,
+    keys: &VaultKeys,
+    pool: &WhirlpoolKeys,
+    current_position: &PositionKeys,
+    new_position: &PositionKeys,
+    params: RebalanceParams,
+    swap_tick_arrays: Option<[Pubkey; SWAP_TICK_ARRAYS]>,
+) -> Instruction {
+    let mut accounts = accounts::Rebalance {
+        user_signer: *admin,
+        vault_account: keys.vault_account,
+        vault_input_token_a_account: keys.vault_input_token_a_account,
+        vault_input_token_b_account: keys.vault_input_token_b_account,
+        whirlpool_program_id: whirlpool::ID,
+        token_vault_a: pool.token_vault_a,
+        token_vault_b: pool.token_vault_b,
+        current_position: current_position.accounts(),
+        new_position: new_position.accounts(),
+        token_program: anchor_spl::token::ID,
+    }
+    .to_account_metas(None);
+
+    if let Some(tick_arrays) = swap_tick_arrays {
+        accounts.extend(tick_arrays.map(|tick_array| AccountMeta::new(tick_array, false)));
+        accounts.push(AccountMeta::new(pool.oracle, false));
+    }
+
+    ggoldca_instruction(
+        accounts,
+        ix::Rebalance {
+            min_amount_a: params.min_amount_a,
+            min_amount_b: params.min_amount_b,
+            reference_sqrt_price: params.reference_sqrt_price,
+            max_price_deviation_bps: params.max_price_deviation_bps,
+            swap_to_ratio: swap_tick_arrays.is_some(),
+        },
+    )
+}
+
+/// Record the pool price when within max_price_deviation_bps of the reference price,
+/// restarting a stale price history
+pub fn refresh_price_history(
+    admin: &Pubkey,
+    vault_account: &Pubkey,
+    whirlpool: &Pubkey,
+    reference_sqrt_price: u128,
+    max_price_deviation_bps: u16,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::RefreshPriceHistory {
+            user_signer: *admin,
+            vault_account: *vault_account,
+            whirlpool: *whirlpool,
+        }
+        .to_account_metas(None),
+        ix::RefreshPriceHistory {
+            reference_sqrt_price,
+            max_price_deviation_bps,
+        },
+    )
+}
+
+/// Send the balance of a token account of the vault to the treasury
+pub fn sweep_token(
+    admin: &Pubkey,
+    vault_account: &Pubkey,
+    vault_token_account: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::SweepToken {
+            user_signer: *admin,
+            vault_account: *vault_account,
+            vault_token_account: *vault_token_account,
+            treasury_token_account: anchor_spl::associated_token::get_associated_token_address(
+                &ggoldca::TREASURY_PUBKEY,
+                mint,
+            ),
+            token_program: anchor_spl::token::ID,
+        }
+        .to_account_metas(None),
+        ix::SweepToken {},
+    )
+}
+
+pub fn init_vault_history(
+    admin: &Pubkey,
+    vault_account: &Pubkey,
+    min_slots_between_snapshots: u64,
+) -> Instruction {
+    let (vault_history, _) = pda::vault_history(vault_account);
+
+    ggoldca_instruction(
+        accounts::InitVaultHistory {
+            user_signer: *admin,
+            vault_account: *vault_account,
+            vault_history,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        ix::InitVaultHistory {
+            min_slots_between_snapshots,
+        },
+    )
+}
@@ -0,0 +1,139 @@
+//! Instructions of the LP staking farms
+use super::ggoldca_instruction;
+use crate::pda::{self, VaultKeys};
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{system_program, sysvar};
+use anchor_lang::ToAccountMetas;
+use anchor_spl::associated_token::get_associated_token_address;
+use ggoldca::accounts;
+use ggoldca::instruction as ix;
+
+/// Create the farm id of a vault, distributing rewards_mint funded by funder
+pub fn create_farm(
+    admin: &Pubkey,
+    keys: &VaultKeys,
+    rewards_mint: &Pubkey,
+    id: u8,
+    funder: Pubkey,
+) -> Instruction {
+    let (farm, _) = pda::farm(&keys.vault_account, id);
+
+    ggoldca_instruction(
+        accounts::CreateFarm {
+            user_signer: *admin,
+            vault_account: keys.vault_account,
+            vault_lp_token_mint_pubkey: keys.vault_lp_token_mint,
+            rewards_mint: *rewards_mint,
+            farm,
+            farm_lp_token_account: get_associated_token_address(&farm, &keys.vault_lp_token_mint),
+            farm_rewards_token_account: get_associated_token_address(&farm, rewards_mint),
+            system_program: system_program::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            token_program: anchor_spl::token::ID,
+            rent: sysvar::rent::ID,
+        }
+        .to_account_metas(None),
+        ix::CreateFarm { id, funder },
+    )
+}
+
+/// Fund a farm from the funder ATA, distributing amount over duration_slots
+pub fn fund_farm(
+    funder: &Pubkey,
+    farm: &Pubkey,
+    rewards_mint: &Pubkey,
+    amount: u64,
+    duration_slots: u64,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::FundFarm {
+            funder: *funder,
+            farm: *farm,
+            farm_rewards_token_account: get_associated_token_address(farm, rewards_mint),
+            funder_rewards_token_account: get_associated_token_address(funder, rewards_mint),
+            token_program: anchor_spl::token::ID,
+        }
+        .to_account_metas(None),
+        ix::FundFarm {
+            amount,
+            duration_slots,
+        },
+    )
+}
+
+pub fn init_farm_stake(user: &Pubkey, farm: &Pubkey) -> Instruction {
+    let (farm_stake, _) = pda::farm_stake(farm, user);
+
+    ggoldca_instruction(
+        accounts::InitFarmStake {
+            user_signer: *user,
+            farm: *farm,
+            farm_stake,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        ix::InitFarmStake {},
+    )
+}
+
+/// Accounts shared by stake_lp and unstake_lp, using the user LP ATA
+fn stake_unstake_accounts(
+    user: &Pubkey,
+    farm: &Pubkey,
+    lp_token_mint: &Pubkey,
+) -> Vec<AccountMeta> {
+    let (farm_stake, _) = pda::farm_stake(farm, user);
+
+    accounts::StakeUnstakeLp {
+        user_signer: *user,
+        farm: *farm,
+        farm_stake,
+        farm_lp_token_account: get_associated_token_address(farm, lp_token_mint),
+        user_lp_token_account: get_associated_token_address(user, lp_token_mint),
+        token_program: anchor_spl::token::ID,
+    }
+    .to_account_metas(None)
+}
+
+pub fn stake_lp(
+    user: &Pubkey,
+    farm: &Pubkey,
+    lp_token_mint: &Pubkey,
+    lp_amount: u64,
+) -> Instruction {
+    ggoldca_instruction(
+        stake_unstake_accounts(user, farm, lp_token_mint),
+        ix::StakeLp { lp_amount },
+    )
+}
+
+pub fn unstake_lp(
+    user: &Pubkey,
+    farm: &Pubkey,
+    lp_token_mint: &Pubkey,
+    lp_amount: u64,
+) -> Instruction {
+    ggoldca_instruction(
+        stake_unstake_accounts(user, farm, lp_token_mint),
+        ix::UnstakeLp { lp_amount },
+    )
+}
+
+/// Claim the farm rewards owed to the user into its rewards ATA
+pub fn claim_farm_rewards(user: &Pubkey, farm: &Pubkey, rewards_mint: &Pubkey) -> Instruction {
+    let (farm_stake, _) = pda::farm_stake(farm, user);
+
+    ggoldca_instruction(
+        accounts::ClaimFarmRewards {
+            user_signer: *user,
+            farm: *farm,
+            farm_stake,
+            farm_rewards_token_account: get_associated_token_address(farm, rewards_mint),
+            user_rewards_token_account: get_associated_token_address(user, rewards_mint),
+            token_program: anchor_spl::token::ID,
+        }
+        .to_account_metas(None),
+        ix::ClaimFarmRewards {},
+    )
+}
@@ -0,0 +1,221 @@
+//! Permissionless instructions harvesting and compounding the vault yield
+use super::{ggoldca_instruction, RewardsSwapAccounts};
+use crate::pda::{self, PositionKeys, VaultKeys, WhirlpoolKeys};
+use crate::tick_array::SWAP_TICK_ARRAYS;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::ToAccountMetas;
+use anchor_spl::associated_token::get_associated_token_address;
+use ggoldca::accounts;
+use ggoldca::instruction as ix;
+
+/// Keeper ATAs of the given mints receiving the keeper bounty, none when no keeper is given
+fn keeper_accounts(keeper: Option<&Pubkey>, mints: &[Pubkey]) -> Vec<AccountMeta> {
+    keeper
+        .map(|keeper| {
+            mints
+                .iter()
+                .map(|mint| AccountMeta::new(get_associated_token_address(keeper, mint), false))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Collect the fees of a vault position. The keeper bounty is paid to the keeper ATAs,
+/// when a keeper is given
+pub fn collect_fees(
+    keeper: Option<&Pubkey>,
+    keys: &VaultKeys,
+    pool: &WhirlpoolKeys,
+    position: &PositionKeys,
+) -> Instruction {
+    let mut accounts = accounts::CollectFees {
+        vault_account: keys.vault_account,
+        vault_input_token_a_account: keys.vault_input_token_a_account,
+        vault_input_token_b_account: keys.vault_input_token_b_account,
+        treasury_token_a_account: keys.treasury_token_a_account,
+        treasury_token_b_account: keys.treasury_token_b_account,
+        whirlpool_program_id: whirlpool::ID,
+        token_vault_a: pool.token_vault_a,
+        token_vault_b: pool.token_vault_b,
+        position: position.accounts(),
+        token_program: anchor_spl::token::ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(keeper_accounts(
+        keeper,
+        &[keys.input_token_a_mint, keys.input_token_b_mint],
+    ));
+
+    ggoldca_instruction(accounts, ix::CollectFees {})
+}
+
+/// Collect the rewards of a vault position. reward_vault is the whirlpool vault of the
+/// reward at reward_index. The keeper bounty of Transfer and Distribute markets is paid
+/// to the keeper ATA of rewards_mint, when a keeper is given
+pub fn collect_rewards(
+    keeper: Option<&Pubkey>,
+    keys: &VaultKeys,
+    position: &PositionKeys,
+    reward_index: u8,
+    rewards_mint: &Pubkey,
+    reward_vault: &Pubkey,
+) -> Instruction {
+    let mut accounts = accounts::CollectRewards {
+        vault_account: keys.vault_account,
+        vault_lp_token_mint_pubkey: keys.vault_lp_token_mint,
+        vault_rewards_token_account: keys.vault_token_account(rewards_mint),
+        treasury_rewards_token_account: keys.treasury_token_account(rewards_mint),
+        reward_vault: *reward_vault,
+        whirlpool_program_id: whirlpool::ID,
+        position: position.accounts(),
+        token_program: anchor_spl::token::ID,
+    }
+    .to_account_metas(None);
+    accounts.extend(keeper_accounts(keeper, &[*rewards_mint]));
+
+    ggoldca_instruction(accounts, ix::CollectRewards { reward_index })
+}
+
+/// Swap the collected rewards of rewards_mint into the vault destination_mint account.
+/// The keeper bounty is paid to the keeper ATA of destination_mint, when a keeper is given
+pub fn swap_rewards(
+    keeper: Option<&Pubkey>,
+    keys: &VaultKeys,
+    rewards_mint: &Pubkey,
+    destination_mint: &Pubkey,
+    swap: &RewardsSwapAccounts,
+) -> Instruction {
+    let mut accounts = accounts::SwapRewards {
+        vault_account: keys.vault_account,
+        vault_rewards_token_account: keys.vault_token_account(rewards_mint),
+        vault_destination_token_account: keys.vault_token_account(destination_mint),
+        token_program: anchor_spl::token::ID,
+        swap_program: swap.swap_program,
+    }
+    .to_account_metas(None);
+    accounts.extend(swap.swap_rewards_remaining_accounts());
+    accounts.extend(keeper_accounts(keeper, &[*destination_mint]));
+
+    ggoldca_instruction(accounts, ix::SwapRewards {})
+}
+
+/// Send the collected rewards of a Transfer market to its destination account
+pub fn transfer_rewards(
+    keys: &VaultKeys,
+    rewards_mint: &Pubkey,
+    destination_token_account: &Pubkey,
+) -> Instruction {
+    ggoldca_instruction(
+        accounts::TransferRewards {
+            vault_account: keys.vault_account,
+            vault_rewards_token_account: keys.vault_token_account(rewards_mint),
+            destination_token_account: *destination_token_account,
+            token_program: anchor_spl::token::ID,
+        }
+        .to_account_metas(None),
+        ix::TransferRewards {},
+    )
+}
+
+/// Reinvest the vault balances into the active position. tick_arrays are the ones
+/// traversed by the swap to the position ratio. A performance snapshot is recorded
+/// when record_history is set and the vault history was initialized. The keeper bounty
+/// of the amounts harvested without keeper is paid to the keeper ATAs, when a keeper is given
+pub fn reinvest(
+    keeper: Option<&Pubkey>,
+    keys: &VaultKeys,
+    pool: &WhirlpoolKeys,
+    position: &PositionKeys,
+    tick_arrays: [Pubkey; SWAP_TICK_ARRAYS],
+    record_history: bool,
+) -> Instruction {
+    let [tick_array_0, tick_array_1, tick_array_2] = tick_arrays;
+    let mut accounts = accounts::Reinvest {
+        vault_account: keys.vault_account,
+        vault_lp_token_mint_pubkey: keys.vault_lp_token_mint,
+        whirlpool_program_id: whirlpool::ID,
+        vault_input_token_a_account: keys.vault_input_token_a_account,
+        vault_input_token_b_account: keys.vault_input_token_b_account,
+        token_vault_a: pool.token_vault_a,
+        token_vault_b: pool.token_vault_b,
+        position: position.accounts(),
+        tick_array_0,
+        tick_array_1,
+        tick_array_2,
+        oracle: pool.oracle,
+        token_program: anchor_spl::token::ID,
+    }
+    .to_account_metas(None);
+    if record_history {
+        let (vault_history, _) = pda::vault_history(&keys.vault_account);
+        accounts.push(AccountMeta::new(vault_history, false));
+    }
+    accounts.extend(keeper_accounts(
+        keeper,
+        &[keys.input_token_a_mint, keys.input_token_b_mint],
+    ));
+
+    ggoldca_instruction(accounts, ix::Reinvest {})
+}
+
+/// Accounts to collect and swap the rewards of a market in harvest_and_compound
+pub struct HarvestRewardsAccounts {
+    pub rewards_mint: Pubkey,
+    /// Whirlpool vault of the reward
+    pub reward_vault: Pubkey,
+    /// None for Transfer and Distribute markets, whose rewards are only collected
+    pub swap: Option<RewardsSwapAccounts>,
+}
+
+/// Collect fees and rewards, swap the rewards and reinvest in a single instruction.
+/// rewards must follow the order of the vault markets which are set. The keeper bounty is
+/// paid to the keeper ATAs, when a keeper is given
+pub fn harvest_and_compound(
+    keeper: Option<&Pubkey>,
+    keys: &VaultKeys,
+    pool: &WhirlpoolKeys,
+    position: &PositionKeys,
+    tick_arrays: [Pubkey; SWAP_TICK_ARRAYS],
+    rewards: &[HarvestRewardsAccounts],
+) -> Instruction {
+    let [tick_array_0, tick_array_1, tick_array_2] = tick_arrays;
+    let mut accounts = accounts::HarvestAndCompound {
+        vault_account: keys.vault_account,
+        vault_lp_token_mint_pubkey: keys.vault_lp_token_mint,
+        whirlpool_program_id: whirlpool::ID,
+        vault_input_token_a_account: keys.vault_input_token_a_account,
+        vault_input_token_b_account: keys.vault_input_token_b_account,
+        treasury_token_a_account: keys.treasury_token_a_account,
+        treasury_token_b_account: keys.treasury_token_b_account,
+        token_vault_a: pool.token_vault_a,
+        token_vault_b: pool.token_vault_b,
+        position: position.accounts(),
+        tick_array_0,
+        tick_array_1,
+        tick_array_2,
+        oracle: pool.oracle,
+        token_program: anchor_spl::token::ID,
+    }
+    .to_account_metas(None);
+    for reward in rewards {
+        accounts.push(AccountMeta::new(reward.reward_vault, false));
+        accounts.push(AccountMeta::new(
+            keys.vault_token_account(&reward.rewards_mint),
+            false,
+        ));
+        accounts.push(AccountMeta::new(
+            keys.treasury_token_account(&reward.rewards_mint),
+            false,
+        ));
+        if let Some(swap) = &reward.swap {
+            accounts.extend(swap.harvest_remaining_accounts());
+        }
+    }
+    accounts.extend(keeper_accounts(
+        keeper,
+        &[keys.input_token_a_mint, keys.input_token_b_mint],
+    ));
+
+    ggoldca_instruction(accounts, ix::HarvestAndCompound {})
+}
@@ -0,0 +1,176 @@
+//! Instructions of the vault depositors
+use super::ggoldca_instruction;
+use crate::pda::{self, PositionKeys, VaultKeys, WhirlpoolKeys};
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::system_program;
+use anchor_lang::ToAccountMetas;
+use anchor_spl::associated_token::get_associated_token_address;
+use ggoldca::accounts;
+use ggoldca::instruction as ix;
+
+pub fn init_user_position(user: &Pubkey, vault_account: &Pubkey) -> Instruction {
+    let (user_position, _) = pda::user_position(vault_account, user);
+
+    ggoldca_instruction(
+        accounts::InitUserPosition {
+            user_signer: *user,
+            vault_account: *vault_account,
+            user_position,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        ix::InitUserPosition {},
+    )
+}
+
+/// Accounts shared by deposit and withdraw, using the user ATAs. The user position is
+/// updated when track_user_position is set, it must have been initialized before
+fn deposit_withdraw_accounts(
+    user: &Pubkey,
+    keys: &VaultKeys,
+    pool: &WhirlpoolKeys,
+    position: &PositionKeys,
+    track_user_position: bool,
+) -> Vec<AccountMeta> {
+    let mut accounts = accounts::DepositWithdraw {
+        user_signer: *user,
+        vault_account: keys.vault_account,
+        vault_lp_token_mint_pubkey: keys.vault_lp_token_mint,
+        vault_input_token_a_account: keys.vault_input_token_a_account,
+        vault_input_token_b_account: keys.vault_input_token_b_account,
+        user_lp_token_account: get_associated_token_address(user, &keys.vault_lp_token_mint),
+        user_token_a_account: get_associated_token_address(user, &keys.input_token_a_mint),
+        user_token_b_account: get_associated_token_address(user, &keys.input_token_b_mint),
+        whirlpool_program_id: whirlpool::ID,
+        position: position.accounts(),
+        wh_token_vault_a: pool.token_vault_a,
+        wh_token_vault_b: pool.token_vault_b,
+        token_program: anchor_spl::token::ID,
+    }
+    .to_account_metas(None);
+
+    if track_user_position {
+        let (user_position, _) = pda::user_position(&keys.vault_account, user);
+        accounts.push(AccountMeta::new(user_position, false));
+    }
+
+    accounts
+}
+
+/// Deposit into the active position of the vault
+#[allow(clippy::too_many_arguments)]
+pub fn deposit(
+    user: &Pubkey,
+    keys: &VaultKeys,
+    pool: &WhirlpoolKeys,
+    position: &PositionKeys,
+    lp_amount: u64,
+    max_amount_a: u64,
+    max_amount_b: u64,
+    track_user_position: bool,
+) -> Instruction {
+    ggoldca_instruction(
+        deposit_withdraw_accounts(user, keys, pool, position, track_user_position),
+        ix::Deposit {
+            lp_amount,
+            max_amount_a,
+            max_amount_b,
+        },
+    )
+}
+
+/// Withdraw from the active position of the vault
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw(
+    user: &Pubkey,
+    keys: &VaultKeys,
+    pool: &WhirlpoolKeys,
+    position: &PositionKeys,
+    lp_amount: u64,
+    min_amount_a: u64,
+    min_amount_b: u64,
+    track_user_position: bool,
+) -> Instruction {
+    ggoldca_instruction(
+        deposit_withdraw_accounts(user, keys, pool, position, track_user_position),
+        ix::Withdraw {
+            lp_amount,
+            min_amount_a,
+            min_amount_b,
+        },
+    )
+}
+
+pub fn init_rewards_checkpoint(user: &Pubkey, keys: &VaultKeys) -> Instruction {
+    let (rewards_checkpoint, _) = pda::rewards_checkpoint(&keys.vault_account, user);
+
+    ggoldca_instruction(
+        accounts::InitRewardsCheckpoint {
+            user_signer: *user,
+            vault_account: keys.vault_account,
+            rewards_checkpoint,
+            system_program: system_program::ID,
+        }
+        .to_account_metas(None),
+        ix::InitRewardsCheckpoint {},
+    )
+}
+
+/// Claim the rewards of a Distribute market owed to the user
+pub fn claim_rewards(user: &Pubkey, keys: &VaultKeys, rewards_mint: &Pubkey) -> Instruction {
+    let (rewards_checkpoint, _) = pda::rewards_checkpoint(&keys.vault_account, user);
+
+    ggoldca_instruction(
+        accounts::ClaimRewards {
+            user_signer: *user,
+            vault_account: keys.vault_account,
+            rewards_checkpoint,
+            vault_rewards_token_account: keys.vault_token_account(rewards_mint),
+            user_rewards_token_account: get_associated_token_address(user, rewards_mint),
+            token_program: anchor_spl::token::ID,
+        }
+        .to_account_metas(None),
+        ix::ClaimRewards {},
+    )
+}
+
+/// Accounts shared by lock_lp and unlock_lp, using the user LP ATA
+fn lock_unlock_accounts(user: &Pubkey, keys: &VaultKeys) -> Vec<AccountMeta> {
+    let (rewards_checkpoint, _) = pda::rewards_checkpoint(&keys.vault_account, user);
+
+    accounts::LockUnlockLp {
+        user_signer: *user,
+        vault_account: keys.vault_account,
+        vault_lp_token_mint_pubkey: keys.vault_lp_token_mint,
+        rewards_checkpoint,
+        vault_lp_token_account: keys.vault_token_account(&keys.vault_lp_token_mint),
+        user_lp_token_account: get_associated_token_address(user, &keys.vault_lp_token_mint),
+        token_program: anchor_spl::token::ID,
+    }
+    .to_account_metas(None)
+}
+
+/// Lock LP tokens in the vault LP ATA, which must exist, so that they accrue the rewards
+/// of Distribute markets
+pub fn lock_lp(user: &Pubkey, keys: &VaultKeys, lp_amount: u64) -> Instruction {
+    ggoldca_instruction(lock_unlock_accounts(user, keys), ix::LockLp { lp_amount })
+}
+
+/// Unlock LP tokens, settling the rewards they accrued
+pub fn unlock_lp(user: &Pubkey, keys: &VaultKeys, lp_amount: u64) -> Instruction {
+    ggoldca_instruction(lock_unlock_accounts(user, keys), ix::UnlockLp { lp_amount })
+}
+
+/// Read-only instruction returning the vault APR/APY between two history snapshots
+pub fn get_vault_apy(vault_account: &Pubkey, start_index: u8, end_index: u8) -> Instruction {
+    let (vault_history, _) = pda::vault_history(vault_account);
+
+    ggoldca_instruction(
+        accounts::GetVaultApy { vault_history }.to_account_metas(None),
+        ix::GetVaultApy {
+            start_index,
+            end_index,
+        },
+    )
+}
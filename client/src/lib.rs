@@ -0,0 +1,18 @@
+//! NAZARE: Rust client for the ggoldca program
+//!
+//! Typed instruction builders, PDA helpers, account fetching and whirlpool
+//! tick array derivation, so that callers don't need to hand-build account lists.
+pub mod accounts;
+pub mod config;
+pub mod error;
+pub mod instructions;
+pub mod pda;
+pub mod tick_array;
+pub mod transaction;
+
+pub use config::ClientConfig;
+pub use error::{ClientError, Result};
+pub use ggoldca;
+pub use ggoldca::math::{liquidity, quote};
+pub use pda::{PositionKeys, VaultId, VaultKeys, WhirlpoolKeys};
+pub use whirlpool;
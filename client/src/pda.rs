@@ -0,0 +1,244 @@
+use crate::tick_array::tick_array_address_for_tick;
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+use ggoldca::interfaces::whirlpool_position::__client_accounts_position_accounts::PositionAccounts;
+use ggoldca::state::VaultAccount;
+use ggoldca::{
+    FARM_SEED, FARM_STAKE_SEED, REWARDS_CHECKPOINT_SEED, TREASURY_PUBKEY, USER_POSITION_SEED,
+    VAULT_ACCOUNT_SEED, VAULT_HISTORY_SEED, VAULT_LP_TOKEN_MINT_SEED,
+};
+use whirlpool::state::position::Position;
+use whirlpool::state::whirlpool::Whirlpool;
+
+/// A vault is identified by the whirlpool it provides liquidity to and its id for that whirlpool
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VaultId {
+    pub whirlpool: Pubkey,
+    pub id: u8,
+}
+
+pub fn vault_account(vault_id: &VaultId) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            VAULT_ACCOUNT_SEED,
+            &[vault_id.id][..],
+            vault_id.whirlpool.as_ref(),
+        ],
+        &ggoldca::ID,
+    )
+}
+
+pub fn vault_lp_token_mint(vault_account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[VAULT_LP_TOKEN_MINT_SEED, vault_account.as_ref()],
+        &ggoldca::ID,
+    )
+}
+
+pub fn rewards_checkpoint(vault_account: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            REWARDS_CHECKPOINT_SEED,
+            vault_account.as_ref(),
+            user.as_ref(),
+        ],
+        &ggoldca::ID,
+    )
+}
+
+pub fn user_position(vault_account: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[USER_POSITION_SEED, vault_account.as_ref(), user.as_ref()],
+        &ggoldca::ID,
+    )
+}
+
+pub fn vault_history(vault_account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_HISTORY_SEED, vault_account.as_ref()], &ggoldca::ID)
+}
+
+pub fn farm(vault_account: &Pubkey, id: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[FARM_SEED, vault_account.as_ref(), &[id][..]],
+        &ggoldca::ID,
+    )
+}
+
+pub fn farm_stake(farm: &Pubkey, user: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[FARM_STAKE_SEED, farm.as_ref(), user.as_ref()],
+        &ggoldca::ID,
+    )
+}
+
+/// Metaplex metadata account of the given mint
+pub fn token_metadata(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            b"metadata",
+            mpl_token_metadata::id().as_ref(),
+            mint.as_ref(),
+        ],
+        &mpl_token_metadata::id(),
+    )
+}
+
+/// Whirlpool position of the given position mint
+pub fn whirlpool_position(position_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"position", position_mint.as_ref()], &whirlpool::ID)
+}
+
+pub fn whirlpool_oracle(whirlpool: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"oracle", whirlpool.as_ref()], &whirlpool::ID)
+}
+
+/// Addresses owned by a vault
+#[derive(Copy, Clone, Debug)]
+pub struct VaultKeys {
+    pub vault_id: VaultId,
+    pub vault_account: Pubkey,
+    pub vault_lp_token_mint: Pubkey,
+    pub input_token_a_mint: Pubkey,
+    pub input_token_b_mint: Pubkey,
+    pub vault_input_token_a_account: Pubkey,
+    pub vault_input_token_b_account: Pubkey,
+    pub treasury_token_a_account: Pubkey,
+    pub treasury_token_b_account: Pubkey,
+}
+
+impl VaultKeys {
+    pub fn new(vault_id: VaultId, input_token_a_mint: Pubkey, input_token_b_mint: Pubkey) -> Self {
+        let (vault_account, _) = vault_account(&vault_id);
+        let (vault_lp_token_mint, _) = vault_lp_token_mint(&vault_account);
+
+        Self {
+            vault_id,
+            vault_account,
+            vault_lp_token_mint,
+            input_token_a_mint,
+            input_token_b_mint,
+            vault_input_token_a_account: get_associated_token_address(
+                &vault_account,
+                &input_token_a_mint,
+            ),
+            vault_input_token_b_account: get_associated_token_address(
+                &vault_account,
+                &input_token_b_mint,
+            ),
+            treasury_token_a_account: get_associated_token_address(
+                &TREASURY_PUBKEY,
+                &input_token_a_mint,
+            ),
+            treasury_token_b_account: get_associated_token_address(
+                &TREASURY_PUBKEY,
+                &input_token_b_mint,
+            ),
+        }
+    }
+
+    pub fn from_vault_account(vault: &VaultAccount) -> Self {
+        Self::new(
+            VaultId {
+                whirlpool: vault.whirlpool_id,
+                id: vault.id,
+            },
+            vault.input_token_a_mint_pubkey,
+            vault.input_token_b_mint_pubkey,
+        )
+    }
+
+    /// Vault associated token account of the given mint
+    pub fn vault_token_account(&self, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address(&self.vault_account, mint)
+    }
+
+    /// Treasury associated token account of the given mint
+    pub fn treasury_token_account(&self, mint: &Pubkey) -> Pubkey {
+        get_associated_token_address(&TREASURY_PUBKEY, mint)
+    }
+}
+
+/// Whirlpool addresses used by the vault instructions
+#[derive(Copy, Clone, Debug)]
+pub struct WhirlpoolKeys {
+    pub whirlpool: Pubkey,
+    pub token_vault_a: Pubkey,
+    pub token_vault_b: Pubkey,
+    pub oracle: Pubkey,
+    pub tick_spacing: u16,
+}
+
+impl WhirlpoolKeys {
+    pub fn from_whirlpool(whirlpool: Pubkey, pool: &Whirlpool) -> Self {
+        Self {
+            whirlpool,
+            token_vault_a: pool.token_vault_a,
+            token_vault_b: pool.token_vault_b,
+            oracle: whirlpool_oracle(&whirlpool).0,
+            tick_spacing: pool.tick_spacing,
+        }
+    }
+}
+
+/// Addresses of a vault position, matching the PositionAccounts of the program
+#[derive(Copy, Clone, Debug)]
+pub struct PositionKeys {
+    pub whirlpool: Pubkey,
+    pub position: Pubkey,
+    pub position_mint: Pubkey,
+    pub position_token_account: Pubkey,
+    pub tick_array_lower: Pubkey,
+    pub tick_array_upper: Pubkey,
+}
+
+impl PositionKeys {
+    pub fn new(
+        vault_account: &Pubkey,
+        pool: &WhirlpoolKeys,
+        position_mint: Pubkey,
+        lower_tick: i32,
+        upper_tick: i32,
+    ) -> Self {
+        Self {
+            whirlpool: pool.whirlpool,
+            position: whirlpool_position(&position_mint).0,
+            position_mint,
+            position_token_account: get_associated_token_address(vault_account, &position_mint),
+            tick_array_lower: tick_array_address_for_tick(
+                &pool.whirlpool,
+                lower_tick,
+                pool.tick_spacing,
+            ),
+            tick_array_upper: tick_array_address_for_tick(
+                &pool.whirlpool,
+                upper_tick,
+                pool.tick_spacing,
+            ),
+        }
+    }
+
+    pub fn from_position(
+        vault_account: &Pubkey,
+        pool: &WhirlpoolKeys,
+        position: &Position,
+    ) -> Self {
+        Self::new(
+            vault_account,
+            pool,
+            position.position_mint,
+            position.tick_lower_index,
+            position.tick_upper_index,
+        )
+    }
+
+    /// PositionAccounts of the program instructions
+    pub fn accounts(&self) -> PositionAccounts {
+        PositionAccounts {
+            whirlpool: self.whirlpool,
+            position: self.position,
+            position_token_account: self.position_token_account,
+            tick_array_lower: self.tick_array_lower,
+            tick_array_upper: self.tick_array_upper,
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use anchor_lang::prelude::Pubkey;
+use whirlpool::state::{MAX_TICK_INDEX, MIN_TICK_INDEX, TICK_ARRAY_SIZE};
+
+/// Number of tick arrays a whirlpool swap may cross
+pub const SWAP_TICK_ARRAYS: usize = 3;
+
+/// Start tick index of the tick array containing tick
+pub fn tick_array_start_index(tick: i32, tick_spacing: u16) -> i32 {
+    let ticks_in_array = TICK_ARRAY_SIZE * i32::from(tick_spacing);
+    tick.div_euclid(ticks_in_array) * ticks_in_array
+}
+
+pub fn tick_array_address(whirlpool: &Pubkey, start_tick_index: i32) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"tick_array",
+            whirlpool.as_ref(),
+            start_tick_index.to_string().as_bytes(),
+        ],
+        &whirlpool::ID,
+    )
+    .0
+}
+
+/// Address of the tick array containing tick
+pub fn tick_array_address_for_tick(whirlpool: &Pubkey, tick: i32, tick_spacing: u16) -> Pubkey {
+    tick_array_address(whirlpool, tick_array_start_index(tick, tick_spacing))
+}
+
+/// Start tick indexes of the tick arrays traversed by a swap from tick_current_index.
+/// Arrays beyond the tick bounds are replaced by the last valid one
+pub fn swap_tick_array_start_indexes(
+    tick_current_index: i32,
+    tick_spacing: u16,
+    a_to_b: bool,
+) -> [i32; SWAP_TICK_ARRAYS] {
+    let ticks_in_array = TICK_ARRAY_SIZE * i32::from(tick_spacing);
+    // Swapping b to a moves the price up, the next initializable tick may be in the next array
+    let shift = if a_to_b { 0 } else { i32::from(tick_spacing) };
+    let first = tick_array_start_index(tick_current_index + shift, tick_spacing);
+
+    let mut start_indexes = [first; SWAP_TICK_ARRAYS];
+    for i in 1..SWAP_TICK_ARRAYS {
+        let start_index = if a_to_b {
+            start_indexes[i - 1] - ticks_in_array
+        } else {
+            start_indexes[i - 1] + ticks_in_array
+        };
+
+        start_indexes[i] = if is_valid_start_index(start_index, ticks_in_array) {
+            start_index
+        } else {
+            start_indexes[i - 1]
+        };
+    }
+
+    start_indexes
+}
+
+/// Addresses of the tick arrays traversed by a swap from tick_current_index
+pub fn swap_tick_arrays(
+    whirlpool: &Pubkey,
+    tick_current_index: i32,
+    tick_spacing: u16,
+    a_to_b: bool,
+) -> [Pubkey; SWAP_TICK_ARRAYS] {
+    swap_tick_array_start_indexes(tick_current_index, tick_spacing, a_to_b)
+        .map(|start_index| tick_array_address(whirlpool, start_index))
+}
+
+fn is_valid_start_index(start_index: i32, ticks_in_array: i32) -> bool {
+    start_index + ticks_in_array > MIN_TICK_INDEX && start_index <= MAX_TICK_INDEX
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tick_array_start_index() {
+        assert_eq!(tick_array_start_index(0, 1), 0);
+        assert_eq!(tick_array_start_index(87, 1), 0);
+        assert_eq!(tick_array_start_index(88, 1), 88);
+        assert_eq!(tick_array_start_index(-1, 1), -88);
+        assert_eq!(tick_array_start_index(-88, 1), -88);
+        assert_eq!(tick_array_start_index(-89, 1), -176);
+
+        assert_eq!(tick_array_start_index(5_000, 64), 0);
+        assert_eq!(tick_array_start_index(5_632, 64), 5_632);
+        assert_eq!(tick_array_start_index(-5_000, 64), -5_632);
+    }
+
+    #[test]
+    fn test_swap_tick_array_start_indexes() {
+        assert_eq!(swap_tick_array_start_indexes(10, 1, true), [0, -88, -176]);
+        assert_eq!(swap_tick_array_start_indexes(10, 1, false), [0, 88, 176]);
+
+        // The next initializable tick when moving up is in the next array
+        assert_eq!(swap_tick_array_start_indexes(87, 1, false), [88, 176, 264]);
+        assert_eq!(swap_tick_array_start_indexes(87, 1, true), [0, -88, -176]);
+    }
+
+    #[test]
+    fn test_swap_tick_array_start_indexes_at_bounds() {
+        let ticks_in_array = TICK_ARRAY_SIZE * 64;
+
+        let last = tick_array_start_index(MAX_TICK_INDEX, 64);
+        assert_eq!(
+            swap_tick_array_start_indexes(MAX_TICK_INDEX - 64, 64, false),
+            [last, last, last]
+        );
+
+        let first = tick_array_start_index(MIN_TICK_INDEX, 64);
+        assert_eq!(
+            swap_tick_array_start_indexes(MIN_TICK_INDEX + ticks_in_array, 64, true),
+            [first + ticks_in_array, first, first]
+        );
+    }
+
+    #[test]
+    fn test_tick_array_address() {
+        let whirlpool = Pubkey::new_unique();
+
+        assert_eq!(
+            tick_array_address_for_tick(&whirlpool, 87, 1),
+            tick_array_address(&whirlpool, 0)
+        );
+        assert_ne!(
+            tick_array_address(&whirlpool, 0),
+            tick_array_address(&whirlpool, 88)
+        );
+        assert_eq!(
+            swap_tick_arrays(&whirlpool, 10, 1, true)[1],
+            tick_array_address(&whirlpool, -88)
+        );
+    }
+}
@@ -0,0 +1,114 @@
+//! Signing, sending and simulating transactions of the built instructions
+use crate::error::Result;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::TokenAccount;
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{
+    RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig,
+};
+use solana_sdk::account::Account;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+
+/// Sign a transaction paid by payer with a recent blockhash. Extra signers are
+/// the ones required by the instructions, e.g. new position mints
+pub fn build_transaction(
+    rpc: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Keypair,
+    extra_signers: &[&Keypair],
+) -> Result<Transaction> {
+    let mut signers: Vec<&dyn Signer> = vec![payer];
+    signers.extend(extra_signers.iter().map(|signer| *signer as &dyn Signer));
+
+    let recent_blockhash = rpc.get_latest_blockhash()?;
+
+    Ok(Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &signers,
+        recent_blockhash,
+    ))
+}
+
+pub fn send_transaction(rpc: &RpcClient, transaction: &Transaction) -> Result<Signature> {
+    Ok(rpc.send_and_confirm_transaction(transaction)?)
+}
+
+/// Base64 wire format of a transaction, as accepted by the sendTransaction RPC
+pub fn serialize_transaction(transaction: &Transaction) -> Result<String> {
+    Ok(base64::encode(bincode::serialize(transaction)?))
+}
+
+/// Accounts written by the instructions, in order of appearance
+pub fn writable_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut accounts: Vec<Pubkey> = vec![];
+    for meta in instructions.iter().flat_map(|ix| ix.accounts.iter()) {
+        if meta.is_writable && !accounts.contains(&meta.pubkey) {
+            accounts.push(meta.pubkey);
+        }
+    }
+    accounts
+}
+
+/// Outcome of a simulated transaction
+pub struct Simulation {
+    pub err: Option<TransactionError>,
+    pub logs: Vec<String>,
+    /// State of the requested accounts after the simulation (None if not created)
+    pub accounts: Vec<(Pubkey, Option<UiAccount>)>,
+}
+
+impl Simulation {
+    /// Balance of a token account after the simulation
+    pub fn token_amount(&self, address: &Pubkey) -> Option<u64> {
+        let account: Account = self
+            .accounts
+            .iter()
+            .find(|(account_address, _)| account_address == address)?
+            .1
+            .as_ref()?
+            .decode()?;
+
+        TokenAccount::try_deserialize(&mut account.data.as_slice())
+            .ok()
+            .map(|token_account| token_account.amount)
+    }
+}
+
+/// Simulate a transaction, returning the state of the given accounts after its execution
+pub fn simulate_transaction(
+    rpc: &RpcClient,
+    transaction: &Transaction,
+    accounts: &[Pubkey],
+) -> Result<Simulation> {
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: true,
+        accounts: Some(RpcSimulateTransactionAccountsConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            addresses: accounts.iter().map(Pubkey::to_string).collect(),
+        }),
+        ..RpcSimulateTransactionConfig::default()
+    };
+
+    let result = rpc
+        .simulate_transaction_with_config(transaction, config)?
+        .value;
+
+    let simulated_accounts = result
+        .accounts
+        .unwrap_or_default()
+        .into_iter()
+        .zip(accounts.iter())
+        .map(|(account, address)| (*address, account))
+        .collect();
+
+    Ok(Simulation {
+        err: result.err,
+        logs: result.logs.unwrap_or_default(),
+        accounts: simulated_accounts,
+    })
+}
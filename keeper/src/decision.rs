@@ -0,0 +1,75 @@
+//! When to harvest and rebalance a vault, free of RPC calls
+use ggoldca_client::ggoldca::math::price::amount_a_to_b;
+use ggoldca_client::ggoldca::state::PositionInfo;
+
+/// Enough slots elapsed since the last reinvestment for reinvest to succeed
+pub fn is_reinvest_due(
+    current_slot: u64,
+    last_reinvestment_slot: u64,
+    min_slots_for_reinvest: u64,
+) -> bool {
+    current_slot.saturating_sub(last_reinvestment_slot) >= min_slots_for_reinvest
+}
+
+/// The position earns fees at the current tick, following the whirlpool convention
+pub fn is_in_range(tick_current_index: i32, position: &PositionInfo) -> bool {
+    position.lower_tick <= tick_current_index && tick_current_index < position.upper_tick
+}
+
+/// Value of the keeper bounty in token_b, at the pool price
+pub fn bounty_value_in_token_b(bounty_a: u64, bounty_b: u64, sqrt_price: u128) -> Option<u128> {
+    amount_a_to_b(bounty_a, sqrt_price)
+        .ok()?
+        .checked_add(u128::from(bounty_b))
+}
+
+/// Harvesting pays off when the bounty covers the transaction cost, in token_b
+pub fn is_harvest_profitable(
+    bounty_a: u64,
+    bounty_b: u64,
+    sqrt_price: u128,
+    tx_cost_token_b: u64,
+) -> bool {
+    bounty_value_in_token_b(bounty_a, bounty_b, sqrt_price)
+        .map_or(false, |value| value >= u128::from(tx_cost_token_b))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_reinvest_due() {
+        assert!(is_reinvest_due(1_100, 1_000, 100));
+        assert!(!is_reinvest_due(1_099, 1_000, 100));
+        assert!(is_reinvest_due(1_000, 1_000, 0));
+        // Stale slot reads never underflow
+        assert!(!is_reinvest_due(900, 1_000, 1));
+    }
+
+    #[test]
+    fn test_is_in_range() {
+        let position = PositionInfo {
+            lower_tick: -128,
+            upper_tick: 128,
+            ..PositionInfo::default()
+        };
+
+        assert!(is_in_range(-128, &position));
+        assert!(is_in_range(0, &position));
+        assert!(is_in_range(127, &position));
+        assert!(!is_in_range(128, &position));
+        assert!(!is_in_range(-129, &position));
+    }
+
+    #[test]
+    fn test_is_harvest_profitable() {
+        // price = 4
+        let sqrt_price = 2_u128 << 64;
+
+        assert_eq!(bounty_value_in_token_b(100, 50, sqrt_price), Some(450));
+        assert!(is_harvest_profitable(100, 50, sqrt_price, 450));
+        assert!(!is_harvest_profitable(100, 50, sqrt_price, 451));
+        assert!(is_harvest_profitable(0, 0, sqrt_price, 0));
+    }
+}
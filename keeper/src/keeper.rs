@@ -0,0 +1,435 @@
+use crate::decision::{is_harvest_profitable, is_in_range, is_reinvest_due};
+use crate::retry::with_retries;
+use anyhow::{anyhow, Result};
+use ggoldca_client::accounts::{fetch_whirlpool, VaultState};
+use ggoldca_client::ggoldca::state::{MarketRewards, PriceBoundKind, MAX_POSITIONS};
+use ggoldca_client::instructions::{self as ix, RebalanceParams, RewardsSwapAccounts};
+use ggoldca_client::tick_array::swap_tick_arrays;
+use ggoldca_client::transaction::{build_transaction, send_transaction, simulate_transaction};
+use ggoldca_client::{pda, PositionKeys, WhirlpoolKeys};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use spl_associated_token_account::{create_associated_token_account, get_associated_token_address};
+use std::time::Duration;
+
+/// Keeper settings shared by every watched vault
+pub struct KeeperConfig {
+    /// Cost of a harvest transaction in token_b, below which the bounty is not worth it
+    pub tx_cost_token_b: u64,
+    /// Rebalance out of range vaults and refresh stale price histories. Both are restricted
+    /// to the program admins
+    pub rebalance: bool,
+    /// Maximum price move allowed while rebalancing or refreshing the price history, in bps
+    pub max_price_deviation_bps: u16,
+    /// Slippage allowed on the amounts withdrawn from the previous position while
+    /// rebalancing, in bps of the amounts at the current price
+    pub rebalance_slippage_bps: u16,
+    pub max_retries: u32,
+    pub retry_delay: Duration,
+    /// Simulate the transactions instead of sending them
+    pub dry_run: bool,
+}
+
+pub struct Keeper {
+    pub rpc: RpcClient,
+    pub keypair: Keypair,
+    pub config: KeeperConfig,
+}
+
+impl Keeper {
+    /// Rebalance the vault when its active position is out of range, harvest it otherwise
+    pub fn process_vault(&self, vault_account: &Pubkey) -> Result<()> {
+        let state = VaultState::fetch(&self.rpc, vault_account)?;
+
+        if state.vault.is_paused {
+            println!("  Paused, skipping");
+            return Ok(());
+        }
+        if state.vault.positions.is_empty() {
+            println!("  No open position, skipping");
+            return Ok(());
+        }
+
+        self.refresh_price_history(&state)?;
+
+        let active_position = state.vault.positions[0];
+        if !is_in_range(state.pool.tick_current_index, &active_position) {
+            if self.config.rebalance {
+                return self.rebalance(&state);
+            }
+            println!(
+                "  Out of range (tick {} not in [{}, {})), rebalance disabled",
+                state.pool.tick_current_index,
+                active_position.lower_tick,
+                active_position.upper_tick
+            );
+        }
+
+        self.harvest(&state)
+    }
+
+    /// Restart a stale price history, without which deposits and reinvestments are rejected.
+    /// The current pool price is the reference, refreshing is restricted to the program admins
+    fn refresh_price_history(&self, state: &VaultState) -> Result<()> {
+        let current_slot = self.rpc.get_slot()?;
+        if state.vault.max_price_deviation_bps == 0 || state.vault.twap_tick(current_slot).is_some()
+        {
+            return Ok(());
+        }
+
+        if !self.config.rebalance {
+            println!("  Price history is stale, an admin must refresh it");
+            return Ok(());
+        }
+
+        println!("  Price history is stale, refreshing it");
+        self.send(
+            &[ix::refresh_price_history(
+                &self.keypair.pubkey(),
+                &state.keys.vault_account,
+                &state.pool_keys.whirlpool,
+                state.pool.sqrt_price,
+                self.config.max_price_deviation_bps,
+            )],
+            &[],
+        )
+    }
+
+    /// Collect the rewards, collect the fees and reinvest when the keeper bounty
+    /// covers the transaction cost
+    fn harvest(&self, state: &VaultState) -> Result<()> {
+        let current_slot = self.rpc.get_slot()?;
+        if !is_reinvest_due(
+            current_slot,
+            state.vault.last_reinvestment_slot,
+            state.vault.min_slots_for_reinvest,
+        ) {
+            println!("  Reinvest not due yet, skipping");
+            return Ok(());
+        }
+
+        let keeper_token_a = self.ensure_token_account(&state.keys.input_token_a_mint)?;
+        let keeper_token_b = self.ensure_token_account(&state.keys.input_token_b_mint)?;
+
+        // The bounty paid by the program is read from the simulated balances
+        let instructions = self.compound_instructions(state)?;
+        let transaction = build_transaction(&self.rpc, &instructions, &self.keypair, &[])?;
+        let simulation =
+            simulate_transaction(&self.rpc, &transaction, &[keeper_token_a, keeper_token_b])?;
+
+        if let Some(err) = simulation.err {
+            println!("  Harvest would fail ({}), skipping", err);
+            return Ok(());
+        }
+
+        let bounty_a = simulation
+            .token_amount(&keeper_token_a)
+            .unwrap_or_default()
+            .saturating_sub(self.token_amount(&keeper_token_a)?);
+        let bounty_b = simulation
+            .token_amount(&keeper_token_b)
+            .unwrap_or_default()
+            .saturating_sub(self.token_amount(&keeper_token_b)?);
+
+        if !is_harvest_profitable(
+            bounty_a,
+            bounty_b,
+            state.pool.sqrt_price,
+            self.config.tx_cost_token_b,
+        ) {
+            println!(
+                "  Bounty ({}, {}) below the transaction cost, skipping",
+                bounty_a, bounty_b
+            );
+            return Ok(());
+        }
+
+        self.harvest_rewards(state)?;
+
+        // The swapped rewards may change the reinvest swap direction
+        let state = VaultState::fetch(&self.rpc, &state.keys.vault_account)?;
+        println!("  Compounding, bounty ({}, {})", bounty_a, bounty_b);
+        self.send(&self.compound_instructions(&state)?, &[])
+    }
+
+    fn compound_instructions(&self, state: &VaultState) -> Result<Vec<Instruction>> {
+        let keeper = self.keypair.pubkey();
+        let position = state.active_position_keys(&self.rpc)?;
+        let tick_arrays = state.reinvest_swap_tick_arrays(&self.rpc)?;
+
+        let (vault_history, _) = pda::vault_history(&state.keys.vault_account);
+        let record_history = self.rpc.get_account(&vault_history).is_ok();
+
+        Ok(vec![
+            ix::collect_fees(Some(&keeper), &state.keys, &state.pool_keys, &position),
+            ix::reinvest(
+                Some(&keeper),
+                &state.keys,
+                &state.pool_keys,
+                &position,
+                tick_arrays,
+                record_history,
+            ),
+        ])
+    }
+
+    /// Collect the rewards of the active position and send them to their market, one
+    /// transaction per reward. Failures are logged without stopping the harvest
+    fn harvest_rewards(&self, state: &VaultState) -> Result<()> {
+        let keeper = self.keypair.pubkey();
+        let position = state.active_position_keys(&self.rpc)?;
+
+        for (index, reward_info) in state.pool.reward_infos.iter().enumerate() {
+            let market = state.vault.market_rewards[index];
+            if reward_info.mint == Pubkey::default() || market.id == MarketRewards::NotSet {
+                continue;
+            }
+
+            // Rewards which are not swapped pay the bounty when collected
+            let collect_keeper = match market.id {
+                MarketRewards::Transfer | MarketRewards::Distribute => {
+                    self.ensure_token_account(&reward_info.mint)?;
+                    Some(&keeper)
+                }
+                _ => None,
+            };
+
+            let mut instructions = vec![ix::collect_rewards(
+                collect_keeper,
+                &state.keys,
+                &position,
+                index as u8,
+                &reward_info.mint,
+                &reward_info.vault,
+            )];
+
+            match market.id {
+                MarketRewards::Transfer => instructions.push(ix::transfer_rewards(
+                    &state.keys,
+                    &reward_info.mint,
+                    &market.destination_token_account,
+                )),
+                MarketRewards::Whirlpool => match self.whirlpool_rewards_swap(state, index)? {
+                    Some(swap) => {
+                        let destination_mint = if market.destination_token_account
+                            == state.keys.vault_input_token_a_account
+                        {
+                            state.keys.input_token_a_mint
+                        } else {
+                            state.keys.input_token_b_mint
+                        };
+                        instructions.push(ix::swap_rewards(
+                            Some(&keeper),
+                            &state.keys,
+                            &reward_info.mint,
+                            &destination_mint,
+                            &swap,
+                        ));
+                    }
+                    None => println!(
+                        "  Reward {} has no reference pool to swap through, collecting only",
+                        index
+                    ),
+                },
+                // Distribute rewards stay in the vault, other markets need their venue accounts
+                _ => {}
+            }
+
+            println!("  Harvesting reward {} ({})", index, reward_info.mint);
+            if let Err(err) = self.send(&instructions, &[]) {
+                eprintln!("  Reward {} harvest failed: {}", index, err);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Swap accounts of a direct Whirlpool rewards market, through the reference pool of its
+    /// price bound. Routes and pools without reference are left to the operators
+    fn whirlpool_rewards_swap(
+        &self,
+        state: &VaultState,
+        index: usize,
+    ) -> Result<Option<RewardsSwapAccounts>> {
+        let price_bound = state.vault.rewards_price_bounds[index];
+        if state.vault.rewards_routes[index].len > 0
+            || price_bound.kind != PriceBoundKind::ReferencePool
+        {
+            return Ok(None);
+        }
+
+        let rewards_mint = state.pool.reward_infos[index].mint;
+        let pool = fetch_whirlpool(&self.rpc, &price_bound.reference_pool)?;
+        let pool_keys = WhirlpoolKeys::from_whirlpool(price_bound.reference_pool, &pool);
+        let tick_arrays = swap_tick_arrays(
+            &pool_keys.whirlpool,
+            pool.tick_current_index,
+            pool_keys.tick_spacing,
+            pool.token_mint_a == rewards_mint,
+        );
+
+        Ok(Some(
+            RewardsSwapAccounts::direct(
+                ggoldca_client::whirlpool::ID,
+                ix::whirlpool_swap_accounts(&pool_keys, tick_arrays),
+            )
+            .with_reference_pool(price_bound.reference_pool),
+        ))
+    }
+
+    /// Open a position around the current price, move the liquidity into it and
+    /// close the previous one
+    fn rebalance(&self, state: &VaultState) -> Result<()> {
+        if state.vault.positions.len() >= MAX_POSITIONS {
+            println!("  Out of range but the vault has no free position slot, skipping");
+            return Ok(());
+        }
+
+        let admin = self.keypair.pubkey();
+        let position_mint = Keypair::new();
+        println!(
+            "  Out of range, opening position {}",
+            position_mint.pubkey()
+        );
+
+        self.send(
+            &[ix::open_centered_position(
+                &admin,
+                &state.keys,
+                &state.pool_keys,
+                &position_mint.pubkey(),
+            )],
+            &[&position_mint],
+        )?;
+
+        // The next steps need the new position on chain
+        if self.config.dry_run {
+            return Ok(());
+        }
+
+        let state = VaultState::fetch(&self.rpc, &state.keys.vault_account)?;
+        let current_position = state.active_position_keys(&self.rpc)?;
+        let (new_position, _) = pda::whirlpool_position(&position_mint.pubkey());
+        let new_position = state.position_keys(&self.rpc, &new_position)?;
+
+        let (min_amount_a, min_amount_b) = state.rebalance_min_amounts(
+            &self.rpc,
+            &current_position.position,
+            self.config.rebalance_slippage_bps,
+        )?;
+        let tick_arrays = state.rebalance_swap_tick_arrays(
+            &self.rpc,
+            &current_position.position,
+            &new_position.position,
+        )?;
+
+        println!(
+            "  Rebalancing {} into {}",
+            current_position.position, new_position.position
+        );
+        self.send(
+            &[ix::rebalance(
+                &admin,
+                &state.keys,
+                &state.pool_keys,
+                &current_position,
+                &new_position,
+                RebalanceParams {
+                    min_amount_a,
+                    min_amount_b,
+                    reference_sqrt_price: state.pool.sqrt_price,
+                    max_price_deviation_bps: self.config.max_price_deviation_bps,
+                },
+                Some(tick_arrays),
+            )],
+            &[],
+        )?;
+
+        self.close_position(&state, &current_position);
+        Ok(())
+    }
+
+    /// Collect what is left in an emptied position and close it, logging failures
+    fn close_position(&self, state: &VaultState, position: &PositionKeys) {
+        let keeper = self.keypair.pubkey();
+
+        // The leftovers of the closed position are not worth a bounty
+        let mut instructions = vec![ix::collect_fees(
+            None,
+            &state.keys,
+            &state.pool_keys,
+            position,
+        )];
+        for (index, reward_info) in state.pool.reward_infos.iter().enumerate() {
+            if reward_info.mint != Pubkey::default() {
+                instructions.push(ix::collect_rewards(
+                    None,
+                    &state.keys,
+                    position,
+                    index as u8,
+                    &reward_info.mint,
+                    &reward_info.vault,
+                ));
+            }
+        }
+        instructions.push(ix::close_position(
+            &keeper,
+            &state.keys.vault_account,
+            position,
+        ));
+
+        println!("  Closing position {}", position.position);
+        if let Err(err) = self.send(&instructions, &[]) {
+            eprintln!("  Position {} not closed: {}", position.position, err);
+        }
+    }
+
+    /// Keeper ATA of mint, created when missing
+    fn ensure_token_account(&self, mint: &Pubkey) -> Result<Pubkey> {
+        let keeper = self.keypair.pubkey();
+        let address = get_associated_token_address(&keeper, mint);
+
+        if self.rpc.get_account(&address).is_err() {
+            println!("  Creating keeper token account {}", address);
+            self.send(
+                &[create_associated_token_account(&keeper, &keeper, mint)],
+                &[],
+            )?;
+        }
+
+        Ok(address)
+    }
+
+    fn token_amount(&self, address: &Pubkey) -> Result<u64> {
+        Ok(self
+            .rpc
+            .get_token_account_balance(address)?
+            .amount
+            .parse()?)
+    }
+
+    /// Send the instructions with retries, or simulate them in dry-run mode, failing
+    /// when the simulation does
+    fn send(&self, instructions: &[Instruction], extra_signers: &[&Keypair]) -> Result<()> {
+        if self.config.dry_run {
+            let transaction =
+                build_transaction(&self.rpc, instructions, &self.keypair, extra_signers)?;
+            if let Some(err) = simulate_transaction(&self.rpc, &transaction, &[])?.err {
+                return Err(anyhow!("Dry run failed: {}", err));
+            }
+            println!("  Dry run succeeded");
+            return Ok(());
+        }
+
+        let signature = with_retries(self.config.max_retries, self.config.retry_delay, || {
+            let transaction =
+                build_transaction(&self.rpc, instructions, &self.keypair, extra_signers)?;
+            send_transaction(&self.rpc, &transaction)
+        })?;
+
+        println!("  Sent {}", signature);
+        Ok(())
+    }
+}
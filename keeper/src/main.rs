@@ -0,0 +1,114 @@
+//! NAZARE: keeper daemon harvesting, compounding and rebalancing the ggoldca vaults
+mod decision;
+mod keeper;
+mod retry;
+
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use ggoldca_client::{pda, ClientConfig, VaultId};
+use keeper::{Keeper, KeeperConfig};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signer;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[clap(name = "ggoldca-keeper", version, about)]
+struct Opts {
+    /// RPC url or moniker (localhost, devnet, mainnet-beta). Defaults to the Solana CLI config
+    #[clap(long, short = 'u')]
+    url: Option<String>,
+    /// Keeper keypair file. Defaults to the Solana CLI config
+    #[clap(long, short = 'k')]
+    keypair: Option<String>,
+    /// Vault to watch, as <whirlpool>:<id>. Can be repeated
+    #[clap(long = "vault", required = true, parse(try_from_str = parse_vault_id))]
+    vaults: Vec<VaultId>,
+    /// Seconds between two rounds over the vaults
+    #[clap(long, default_value = "60")]
+    interval_secs: u64,
+    /// Process the vaults once and exit
+    #[clap(long)]
+    once: bool,
+    /// Simulate the transactions instead of sending them
+    #[clap(long)]
+    dry_run: bool,
+    /// Cost of a harvest transaction in token_b units, the keeper bounty must cover it
+    #[clap(long, default_value = "0")]
+    tx_cost_token_b: u64,
+    /// Rebalance the vaults out of range and refresh their stale price history. The keypair
+    /// must be a program admin
+    #[clap(long)]
+    rebalance: bool,
+    /// Maximum price move allowed while rebalancing or refreshing the price history, in bps
+    #[clap(long, default_value = "100")]
+    max_price_deviation_bps: u16,
+    /// Slippage allowed on the amounts withdrawn while rebalancing, in bps
+    #[clap(long, default_value = "100")]
+    rebalance_slippage_bps: u16,
+    /// Retries of a failed transaction
+    #[clap(long, default_value = "3")]
+    max_retries: u32,
+    /// Delay before the first retry, doubled on each retry
+    #[clap(long, default_value = "500")]
+    retry_delay_ms: u64,
+}
+
+fn parse_vault_id(value: &str) -> Result<VaultId, String> {
+    let (whirlpool, id) = value
+        .split_once(':')
+        .ok_or_else(|| format!("Expected <whirlpool>:<id>, got {}", value))?;
+
+    Ok(VaultId {
+        whirlpool: Pubkey::from_str(whirlpool).map_err(|err| err.to_string())?,
+        id: id.parse().map_err(|_| format!("Invalid vault id {}", id))?,
+    })
+}
+
+fn main() -> Result<()> {
+    let opts = Opts::parse();
+
+    let config = ClientConfig::load(opts.url.as_deref(), opts.keypair.as_deref())?;
+
+    println!("Keeper {} on {}", config.keypair.pubkey(), config.url);
+
+    let keeper = Keeper {
+        rpc: config.rpc_client(),
+        keypair: config.keypair,
+        config: KeeperConfig {
+            tx_cost_token_b: opts.tx_cost_token_b,
+            rebalance: opts.rebalance,
+            max_price_deviation_bps: opts.max_price_deviation_bps,
+            rebalance_slippage_bps: opts.rebalance_slippage_bps,
+            max_retries: opts.max_retries,
+            retry_delay: Duration::from_millis(opts.retry_delay_ms),
+            dry_run: opts.dry_run,
+        },
+    };
+
+    loop {
+        let mut failed_vaults = 0;
+        for vault_id in &opts.vaults {
+            let (vault_account, _) = pda::vault_account(vault_id);
+            println!(
+                "Vault {} ({}:{})",
+                vault_account, vault_id.whirlpool, vault_id.id
+            );
+
+            // A failing vault must not stop the others
+            if let Err(err) = keeper.process_vault(&vault_account) {
+                eprintln!("  Error: {:#}", err);
+                failed_vaults += 1;
+            }
+        }
+
+        if opts.once {
+            return match failed_vaults {
+                0 => Ok(()),
+                n => Err(anyhow!("{} vault(s) failed", n)),
+            };
+        }
+        thread::sleep(Duration::from_secs(opts.interval_secs));
+    }
+}
@@ -0,0 +1,58 @@
+use std::thread;
+use std::time::Duration;
+
+/// Call f until it succeeds, up to max_retries extra attempts, doubling the delay
+/// between attempts. The last error is returned when every attempt failed
+pub fn with_retries<T, E: std::fmt::Display>(
+    max_retries: u32,
+    initial_delay: Duration,
+    mut f: impl FnMut() -> Result<T, E>,
+) -> Result<T, E> {
+    let mut delay = initial_delay;
+    let mut attempt = 0;
+
+    loop {
+        match f() {
+            Ok(value) => return Ok(value),
+            Err(err) if attempt < max_retries => {
+                attempt += 1;
+                eprintln!("Attempt {} failed, retrying: {}", attempt, err);
+                thread::sleep(delay);
+                delay = delay.saturating_mul(2);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_with_retries_succeeds_after_failures() {
+        let mut calls = 0;
+        let result = with_retries(3, Duration::ZERO, || {
+            calls += 1;
+            if calls < 3 {
+                Err("failed")
+            } else {
+                Ok(calls)
+            }
+        });
+
+        assert_eq!(result, Ok(3));
+    }
+
+    #[test]
+    fn test_with_retries_returns_last_error() {
+        let mut calls = 0;
+        let result: Result<(), String> = with_retries(2, Duration::ZERO, || {
+            calls += 1;
+            Err(format!("failed {}", calls))
+        });
+
+        assert_eq!(result, Err("failed 3".to_string()));
+        assert_eq!(calls, 3);
+    }
+}
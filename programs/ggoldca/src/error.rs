@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 
-/// Error codes
+/// Error codes. Anchor numbers them by position (6000 + index), new variants
+/// must be appended at the end to keep the deployed codes stable
 #[error_code]
 pub enum ErrorCode {
     #[msg("Math overflow during add")]
@@ -80,4 +81,83 @@ pub enum ErrorCode {
     WhirlpoolLiquidityTooHigh,
     #[msg("whirlpool_cpi: Overflow while computing liquidity to token deltas")]
     WhirlpoolLiquidityToDeltasOverflow,
+
+    #[msg("Lower tick must be smaller than upper tick")]
+    InvalidTickRange,
+    #[msg("Ticks must be a multiple of the whirlpool tick spacing")]
+    TicksNotAligned,
+    #[msg("Ticks out of the whirlpool bounds")]
+    TicksOutOfBounds,
+    #[msg("Range config is not set")]
+    RangeConfigNotSet,
+    #[msg("Invalid range config")]
+    InvalidRangeConfig,
+    #[msg("Not enough price observations")]
+    NotEnoughPriceObservations,
+
+    #[msg("Reference price must be greater than zero")]
+    InvalidReferencePrice,
+    #[msg("Pool price deviates too much from the reference price")]
+    PriceDeviationExceeded,
+
+    #[msg("Slippage cannot exceed BPS_SCALE")]
+    InvalidSlippage,
+    #[msg("Price deviation cannot exceed BPS_SCALE")]
+    InvalidPriceDeviation,
+    #[msg("Keeper bounty cannot exceed BPS_SCALE")]
+    InvalidKeeperBounty,
+
+    #[msg("Invalid rewards token account")]
+    InvalidRewardsAccount,
+    #[msg("Swap pool does not match the swapped mints")]
+    SwapInvalidPool,
+    #[msg("Invalid swap route")]
+    InvalidSwapRoute,
+    #[msg("Swap output is below the minimum amount out")]
+    SwapAmountOutTooLow,
+    #[msg("Invalid rewards price bound")]
+    InvalidPriceBound,
+    #[msg("Invalid reference pool")]
+    InvalidReferencePool,
+
+    #[msg("Distribute is not set for the current rewards")]
+    DistributeNotSet,
+    #[msg("Distributed rewards cannot be moved to another market")]
+    DistributeMarketLocked,
+
+    #[msg("Farm funding is too low for the given duration")]
+    InvalidFarmFunding,
+    #[msg("Not enough LP tokens staked")]
+    NotEnoughStakedLp,
+
+    #[msg("Vault is already at the current version")]
+    VaultAlreadyMigrated,
+    #[msg("Vault still has open positions")]
+    VaultHasOpenPositions,
+    #[msg("Vault LP tokens are still in circulation")]
+    VaultHasLpSupply,
+    #[msg("Vault input and configured rewards mints cannot be swept")]
+    SweepMintNotAllowed,
+
+    #[msg("User position does not belong to the vault and user")]
+    InvalidUserPosition,
+    #[msg("Vault history does not belong to the vault")]
+    InvalidVaultHistory,
+    #[msg("Vault history snapshot not found")]
+    HistorySnapshotNotFound,
+
+    #[msg("Keeper token account does not match the bounty mint")]
+    InvalidKeeperAccount,
+    #[msg("Fee thresholds out of bounds")]
+    InvalidMinFees,
+    #[msg("Rewards swap chunking out of bounds")]
+    InvalidSwapChunking,
+    #[msg("No LP tokens locked to distribute the rewards")]
+    NoLockedLp,
+    #[msg("Not enough LP tokens locked")]
+    NotEnoughLockedLp,
+    #[msg("Price history is stale, it must be refreshed")]
+    StalePriceHistory,
+    #[msg("Distributed rewards are still to be claimed")]
+    VaultHasOwedRewards,
 }
@@ -1,35 +1,89 @@
+pub mod claim_farm_rewards;
+pub mod claim_rewards;
 pub mod close_position;
+pub mod close_vault;
 pub mod collect_fees;
 pub mod collect_rewards;
+pub mod create_farm;
 pub mod deposit;
+pub mod fund_farm;
+pub mod get_vault_apy;
+pub mod harvest_and_compound;
+pub mod init_farm_stake;
+pub mod init_rewards_checkpoint;
+pub mod init_user_position;
+pub mod init_vault_history;
 pub mod initialize_vault;
+pub mod lock_lp;
+pub mod migrate_vault;
+pub mod open_centered_position;
 pub mod open_position;
 pub mod rebalance;
+pub mod refresh_price_history;
 pub mod reinvest;
+pub mod set_keeper_bounty;
 pub mod set_market_rewards;
+pub mod set_max_price_deviation;
+pub mod set_max_swap_slippage;
+pub mod set_min_fees;
 pub mod set_min_slots_for_reinvest;
+pub mod set_range_config;
+pub mod set_rewards_price_bound;
+pub mod set_rewards_route;
+pub mod set_rewards_swap_chunking;
 pub mod set_token_metadata;
 pub mod set_vault_fee;
 pub mod set_vault_pause_status;
 pub mod set_vault_ui_status;
+pub mod stake_lp;
 pub mod swap_rewards;
+pub mod sweep_token;
 pub mod transfer_rewards;
+pub mod unlock_lp;
+pub mod unstake_lp;
 pub mod withdraw;
 
+pub use claim_farm_rewards::*;
+pub use claim_rewards::*;
 pub use close_position::*;
+pub use close_vault::*;
 pub use collect_fees::*;
 pub use collect_rewards::*;
+pub use create_farm::*;
 pub use deposit::*;
+pub use fund_farm::*;
+pub use get_vault_apy::*;
+pub use harvest_and_compound::*;
+pub use init_farm_stake::*;
+pub use init_rewards_checkpoint::*;
+pub use init_user_position::*;
+pub use init_vault_history::*;
 pub use initialize_vault::*;
+pub use lock_lp::*;
+pub use migrate_vault::*;
+pub use open_centered_position::*;
 pub use open_position::*;
 pub use rebalance::*;
+pub use refresh_price_history::*;
 pub use reinvest::*;
+pub use set_keeper_bounty::*;
 pub use set_market_rewards::*;
+pub use set_max_price_deviation::*;
+pub use set_max_swap_slippage::*;
+pub use set_min_fees::*;
 pub use set_min_slots_for_reinvest::*;
+pub use set_range_config::*;
+pub use set_rewards_price_bound::*;
+pub use set_rewards_route::*;
+pub use set_rewards_swap_chunking::*;
 pub use set_token_metadata::*;
 pub use set_vault_fee::*;
 pub use set_vault_pause_status::*;
 pub use set_vault_ui_status::*;
+pub use stake_lp::*;
 pub use swap_rewards::*;
+pub use sweep_token::*;
 pub use transfer_rewards::*;
+pub use unlock_lp::*;
+pub use unstake_lp::*;
 pub use withdraw::*;
@@ -0,0 +1,84 @@
+use crate::macros::generate_farm_seeds;
+use crate::state::{Farm, FarmStake};
+use crate::{FARM_SEED, FARM_STAKE_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+#[event]
+struct ClaimFarmRewardsEvent {
+    farm: Pubkey,
+    user: Pubkey,
+    amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct ClaimFarmRewards<'info> {
+    pub user_signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [FARM_SEED, farm.vault_account.as_ref(), &[farm.id][..]],
+        bump = farm.bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        mut,
+        seeds = [FARM_STAKE_SEED, farm.key().as_ref(), user_signer.key().as_ref()],
+        bump = farm_stake.bump
+    )]
+    pub farm_stake: Account<'info, FarmStake>,
+    #[account(
+        mut,
+        associated_token::mint = farm.rewards_mint,
+        associated_token::authority = farm,
+    )]
+    pub farm_rewards_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = farm.rewards_mint,
+    )]
+    pub user_rewards_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClaimFarmRewards<'info> {
+    fn transfer_from_farm_to_user_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.farm_rewards_token_account.to_account_info(),
+                to: self.user_rewards_token_account.to_account_info(),
+                authority: self.farm.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn handler(ctx: Context<ClaimFarmRewards>) -> Result<()> {
+    let current_slot = Clock::get()?.slot;
+    ctx.accounts.farm.update(current_slot)?;
+
+    let reward_per_share_x64 = ctx.accounts.farm.reward_per_share_x64;
+    let farm_stake = &mut ctx.accounts.farm_stake;
+    farm_stake.update(reward_per_share_x64)?;
+    let amount = std::mem::take(&mut farm_stake.owed_rewards);
+
+    if amount > 0 {
+        let seeds = generate_farm_seeds!(ctx.accounts.farm);
+        let signer = &[&seeds[..]];
+
+        token::transfer(
+            ctx.accounts
+                .transfer_from_farm_to_user_ctx()
+                .with_signer(signer),
+            amount,
+        )?;
+    }
+
+    emit!(ClaimFarmRewardsEvent {
+        farm: ctx.accounts.farm.key(),
+        user: ctx.accounts.user_signer.key(),
+        amount,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use crate::error::ErrorCode;
+use crate::macros::generate_seeds;
+use crate::state::{MarketRewards, UserRewardsCheckpoint, VaultAccount};
+use crate::{REWARDS_CHECKPOINT_SEED, VAULT_ACCOUNT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+#[event]
+struct ClaimRewardsEvent {
+    vault_account: Pubkey,
+    user: Pubkey,
+    rewards_mint: Pubkey,
+    amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct ClaimRewards<'info> {
+    pub user_signer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+    #[account(
+        mut,
+        seeds = [REWARDS_CHECKPOINT_SEED, vault_account.key().as_ref(), user_signer.key().as_ref()],
+        bump = rewards_checkpoint.bump
+    )]
+    pub rewards_checkpoint: Account<'info, UserRewardsCheckpoint>,
+    #[account(
+        mut,
+        associated_token::mint = vault_rewards_token_account.mint,
+        associated_token::authority = vault_account,
+    )]
+    pub vault_rewards_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = vault_rewards_token_account.mint,
+    )]
+    pub user_rewards_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClaimRewards<'info> {
+    fn transfer_from_vault_to_user_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.vault_rewards_token_account.to_account_info(),
+                to: self.user_rewards_token_account.to_account_info(),
+                authority: self.vault_account.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn handler(ctx: Context<ClaimRewards>) -> Result<()> {
+    let rewards_mint = ctx.accounts.vault_rewards_token_account.mint;
+
+    let index = ctx
+        .accounts
+        .vault_account
+        .market_rewards
+        .iter()
+        .position(|market| market.rewards_mint == rewards_mint)
+        .ok_or_else(|| error!(ErrorCode::InvalidRewardMint))?;
+
+    require!(
+        ctx.accounts.vault_account.market_rewards[index].id == MarketRewards::Distribute,
+        ErrorCode::DistributeNotSet
+    );
+
+    // Checkpoint every reward, as the locked LP tokens are shared by all of them
+    let vault = &mut ctx.accounts.vault_account;
+    let checkpoint = &mut ctx.accounts.rewards_checkpoint;
+    vault.checkpoint_rewards(checkpoint)?;
+    let amount = vault.claim_rewards(checkpoint, index)?;
+
+    if amount > 0 {
+        let seeds = generate_seeds!(ctx.accounts.vault_account);
+        let signer = &[&seeds[..]];
+
+        token::transfer(
+            ctx.accounts
+                .transfer_from_vault_to_user_ctx()
+                .with_signer(signer),
+            amount,
+        )?;
+    }
+
+    emit!(ClaimRewardsEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        user: ctx.accounts.user_signer.key(),
+        rewards_mint,
+        amount,
+    });
+
+    Ok(())
+}
@@ -7,6 +7,14 @@ use anchor_lang::solana_program::pubkey::Pubkey;
 use anchor_lang_for_whirlpool::context::CpiContext as CpiContextForWhirlpool;
 use anchor_spl::token::Token;
 
+#[event]
+struct ClosePositionEvent {
+    vault_account: Pubkey,
+    position: Pubkey,
+    lower_tick: i32,
+    upper_tick: i32,
+}
+
 #[derive(Accounts)]
 pub struct ClosePosition<'info> {
     #[account(mut)]
@@ -58,6 +66,15 @@ impl<'info> ClosePosition<'info> {
 }
 
 pub fn handler(ctx: Context<ClosePosition>) -> Result<()> {
+    let closed_position = *ctx
+        .accounts
+        .vault_account
+        .positions
+        .iter()
+        .find(|position| &position.pubkey == ctx.accounts.position.key)
+        // this cannot fail, existence of the position checked in constraints
+        .unwrap();
+
     let seeds = generate_seeds!(ctx.accounts.vault_account);
     let signer = &[&seeds[..]];
 
@@ -68,5 +85,12 @@ pub fn handler(ctx: Context<ClosePosition>) -> Result<()> {
         .positions
         .retain(|position| &position.pubkey != ctx.accounts.position.key);
 
+    emit!(ClosePositionEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        position: closed_position.pubkey,
+        lower_tick: closed_position.lower_tick,
+        upper_tick: closed_position.upper_tick,
+    });
+
     Ok(())
 }
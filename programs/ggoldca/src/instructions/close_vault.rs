@@ -0,0 +1,203 @@
+use crate::error::ErrorCode;
+use crate::macros::generate_seeds;
+use crate::state::VaultAccount;
+use crate::{TREASURY_PUBKEY, VAULT_ACCOUNT_SEED, VAULT_LP_TOKEN_MINT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token::instruction::AuthorityType;
+use anchor_spl::token::{self, CloseAccount, Mint, SetAuthority, Token, TokenAccount, Transfer};
+
+/// Number of remaining accounts per additional vault token account:
+/// vault_token_account, treasury_token_account
+const TOKEN_ACCOUNTS_GROUP_LEN: usize = 2;
+
+#[event]
+struct CloseVaultEvent {
+    vault_account: Pubkey,
+    dust_token_a: u64,
+    dust_token_b: u64,
+    closed_token_accounts: u8,
+}
+
+#[derive(Accounts)]
+pub struct CloseVault<'info> {
+    #[account(mut)]
+    pub user_signer: Signer<'info>,
+    #[account(
+        mut,
+        close = user_signer,
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        constraint = vault_account.positions.is_empty() @ ErrorCode::VaultHasOpenPositions,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+    #[account(
+        mut,
+        constraint = vault_lp_token_mint_pubkey.supply == 0 @ ErrorCode::VaultHasLpSupply,
+        mint::authority = vault_account.key(),
+        seeds = [VAULT_LP_TOKEN_MINT_SEED, vault_account.key().as_ref()],
+        bump = vault_account.bumps.lp_token_mint
+    )]
+    pub vault_lp_token_mint_pubkey: Account<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = vault_account.input_token_a_mint_pubkey,
+        associated_token::authority = vault_account,
+    )]
+    pub vault_input_token_a_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = vault_account.input_token_b_mint_pubkey,
+        associated_token::authority = vault_account,
+    )]
+    pub vault_input_token_b_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = vault_account.input_token_a_mint_pubkey,
+        associated_token::authority = TREASURY_PUBKEY
+    )]
+    pub treasury_token_a_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = vault_account.input_token_b_mint_pubkey,
+        associated_token::authority = TREASURY_PUBKEY
+    )]
+    pub treasury_token_b_account: Box<Account<'info, TokenAccount>>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CloseVault<'info> {
+    fn transfer_from_vault_to_treasury_ctx(
+        &self,
+        vault: &AccountInfo<'info>,
+        treasury: &AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: vault.to_account_info(),
+                to: treasury.to_account_info(),
+                authority: self.vault_account.to_account_info(),
+            },
+        )
+    }
+
+    fn close_vault_token_account_ctx(
+        &self,
+        vault: &AccountInfo<'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: vault.to_account_info(),
+                destination: self.user_signer.to_account_info(),
+                authority: self.vault_account.to_account_info(),
+            },
+        )
+    }
+
+    fn remove_lp_mint_authority_ctx(&self) -> CpiContext<'_, '_, '_, 'info, SetAuthority<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            SetAuthority {
+                current_authority: self.vault_account.to_account_info(),
+                account_or_mint: self.vault_lp_token_mint_pubkey.to_account_info(),
+            },
+        )
+    }
+
+    /// Send the remaining balance to the treasury and close the vault token account
+    fn sweep_and_close(
+        &self,
+        vault: &AccountInfo<'info>,
+        treasury: &AccountInfo<'info>,
+        amount: u64,
+        signer: &[&[&[u8]]],
+    ) -> Result<()> {
+        if amount > 0 {
+            token::transfer(
+                self.transfer_from_vault_to_treasury_ctx(vault, treasury)
+                    .with_signer(signer),
+                amount,
+            )?;
+        }
+
+        token::close_account(
+            self.close_vault_token_account_ctx(vault)
+                .with_signer(signer),
+        )
+    }
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CloseVault<'info>>) -> Result<()> {
+    let seeds = generate_seeds!(ctx.accounts.vault_account);
+    let signer = &[&seeds[..]];
+
+    let dust_token_a = ctx.accounts.vault_input_token_a_account.amount;
+    let dust_token_b = ctx.accounts.vault_input_token_b_account.amount;
+
+    ctx.accounts.sweep_and_close(
+        &ctx.accounts.vault_input_token_a_account.to_account_info(),
+        &ctx.accounts.treasury_token_a_account.to_account_info(),
+        dust_token_a,
+        signer,
+    )?;
+    ctx.accounts.sweep_and_close(
+        &ctx.accounts.vault_input_token_b_account.to_account_info(),
+        &ctx.accounts.treasury_token_b_account.to_account_info(),
+        dust_token_b,
+        signer,
+    )?;
+
+    // Other vault token accounts (e.g. rewards) as pairs of vault & treasury accounts
+    require!(
+        ctx.remaining_accounts.len() % TOKEN_ACCOUNTS_GROUP_LEN == 0,
+        ErrorCode::InvalidNumberOfAccounts
+    );
+
+    let vault_key = ctx.accounts.vault_account.key();
+    for group in ctx.remaining_accounts.chunks(TOKEN_ACCOUNTS_GROUP_LEN) {
+        let vault_token_account = Account::<TokenAccount>::try_from(&group[0])?;
+        let mint = vault_token_account.mint;
+
+        require!(
+            group[0].key() == get_associated_token_address(&vault_key, &mint),
+            ErrorCode::InvalidRewardsAccount
+        );
+        require!(
+            group[1].key() == get_associated_token_address(&TREASURY_PUBKEY, &mint),
+            ErrorCode::InvalidRewardsAccount
+        );
+        // Without LP supply no LP tokens are locked, so every checkpoint accrued its rewards
+        require!(
+            !ctx.accounts.vault_account.has_owed_rewards(mint),
+            ErrorCode::VaultHasOwedRewards
+        );
+
+        ctx.accounts
+            .sweep_and_close(&group[0], &group[1], vault_token_account.amount, signer)?;
+    }
+
+    // SPL mints cannot be closed, no more LP tokens can be minted instead
+    token::set_authority(
+        ctx.accounts
+            .remove_lp_mint_authority_ctx()
+            .with_signer(signer),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+
+    let closed_token_accounts =
+        u8::try_from(2 + ctx.remaining_accounts.len() / TOKEN_ACCOUNTS_GROUP_LEN)
+            .map_err(|_| ErrorCode::MathOverflowConversion)?;
+
+    emit!(CloseVaultEvent {
+        vault_account: vault_key,
+        dust_token_a,
+        dust_token_b,
+        closed_token_accounts,
+    });
+
+    Ok(())
+}
@@ -1,9 +1,9 @@
 use crate::error::ErrorCode;
 use crate::interfaces::whirlpool_position::*;
 use crate::macros::generate_seeds;
-use crate::math::safe_arithmetics::{SafeArithmetics, SafeMulDiv};
-use crate::state::VaultAccount;
-use crate::{FEE_SCALE, TREASURY_PUBKEY, VAULT_ACCOUNT_SEED, VAULT_VERSION};
+use crate::math::safe_arithmetics::SafeArithmetics;
+use crate::state::{amount_after_fee, VaultAccount};
+use crate::{TREASURY_PUBKEY, VAULT_ACCOUNT_SEED, VAULT_VERSION};
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::pubkey::Pubkey;
 use anchor_lang_for_whirlpool::context::CpiContext as CpiContextForWhirlpool;
@@ -17,6 +17,9 @@ struct CollectFeesEvent {
     total_fees_token_b: u64,
     treasury_fee_token_a: u64,
     treasury_fee_token_b: u64,
+    keeper_bounty_token_a: u64,
+    keeper_bounty_token_b: u64,
+    sqrt_price: u128,
 }
 
 #[derive(Accounts)]
@@ -107,41 +110,175 @@ impl<'info> CollectFees<'info> {
         )
     }
 
-    fn transfer_token_a_from_vault_to_treasury_ctx(
-        &self,
-    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        self._transfer_from_vault_to_treasury_ctx(
-            &self.vault_input_token_a_account,
-            &self.treasury_token_a_account,
-        )
+    fn fees_accounts(&self) -> FeesAccounts<'info> {
+        FeesAccounts {
+            token_program: self.token_program.to_account_info(),
+            vault_input_token_a_account: self.vault_input_token_a_account.to_account_info(),
+            vault_input_token_b_account: self.vault_input_token_b_account.to_account_info(),
+            treasury_token_a_account: self.treasury_token_a_account.to_account_info(),
+            treasury_token_b_account: self.treasury_token_b_account.to_account_info(),
+        }
     }
+}
 
-    fn transfer_token_b_from_vault_to_treasury_ctx(
-        &self,
-    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        self._transfer_from_vault_to_treasury_ctx(
-            &self.vault_input_token_b_account,
-            &self.treasury_token_b_account,
-        )
+/// Token accounts the collected fees are shared between
+pub(crate) struct FeesAccounts<'info> {
+    pub token_program: AccountInfo<'info>,
+    pub vault_input_token_a_account: AccountInfo<'info>,
+    pub vault_input_token_b_account: AccountInfo<'info>,
+    pub treasury_token_a_account: AccountInfo<'info>,
+    pub treasury_token_b_account: AccountInfo<'info>,
+}
+
+/// Charge the treasury fees on the collected fees and transfer them to the treasury
+pub(crate) fn transfer_treasury_fees<'info>(
+    vault_account: &mut Account<'info, VaultAccount>,
+    accounts: &FeesAccounts<'info>,
+    amount_a: u64,
+    amount_b: u64,
+    charge_all: bool,
+) -> Result<(u64, u64)> {
+    let (treasury_fee_a, treasury_fee_b) = vault_account.charge_fees(
+        amount_a,
+        amount_b,
+        token::accessor::amount(&accounts.vault_input_token_a_account)?,
+        token::accessor::amount(&accounts.vault_input_token_b_account)?,
+        charge_all,
+    )?;
+
+    let seeds = generate_seeds!(vault_account);
+    let signer = &[&seeds[..]];
+    let vault_account_info = vault_account.to_account_info();
+
+    transfer_from_vault(
+        &accounts.token_program,
+        &vault_account_info,
+        &accounts.vault_input_token_a_account,
+        &accounts.treasury_token_a_account,
+        treasury_fee_a,
+        signer,
+    )?;
+    transfer_from_vault(
+        &accounts.token_program,
+        &vault_account_info,
+        &accounts.vault_input_token_b_account,
+        &accounts.treasury_token_b_account,
+        treasury_fee_b,
+        signer,
+    )?;
+
+    Ok((treasury_fee_a, treasury_fee_b))
+}
+
+/// Pay the keeper bounty on the harvested amounts, when the keeper token accounts are
+/// provided. The amounts are carried forward otherwise
+pub(crate) fn pay_keeper_bounties<'info>(
+    vault_account: &mut Account<'info, VaultAccount>,
+    token_program: &AccountInfo<'info>,
+    vault_input_token_a_account: &AccountInfo<'info>,
+    vault_input_token_b_account: &AccountInfo<'info>,
+    keeper_accounts: Option<(AccountInfo<'info>, AccountInfo<'info>)>,
+    amount_a: u64,
+    amount_b: u64,
+) -> Result<(u64, u64)> {
+    let (keeper_bounty_a, keeper_bounty_b) = vault_account.keeper_bounties(
+        amount_a,
+        amount_b,
+        token::accessor::amount(vault_input_token_a_account)?,
+        token::accessor::amount(vault_input_token_b_account)?,
+        keeper_accounts.is_some(),
+    )?;
+
+    let (keeper_token_a_account, keeper_token_b_account) = match keeper_accounts {
+        Some(keeper_accounts) => keeper_accounts,
+        None => return Ok((0, 0)),
+    };
+
+    let seeds = generate_seeds!(vault_account);
+    let signer = &[&seeds[..]];
+    let vault_account_info = vault_account.to_account_info();
+
+    transfer_from_vault(
+        token_program,
+        &vault_account_info,
+        vault_input_token_a_account,
+        &keeper_token_a_account,
+        keeper_bounty_a,
+        signer,
+    )?;
+    transfer_from_vault(
+        token_program,
+        &vault_account_info,
+        vault_input_token_b_account,
+        &keeper_token_b_account,
+        keeper_bounty_b,
+        signer,
+    )?;
+
+    Ok((keeper_bounty_a, keeper_bounty_b))
+}
+
+/// Keeper token accounts of the input tokens, when provided as the last two remaining accounts
+pub(crate) fn keeper_input_token_accounts<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    vault_account: &VaultAccount,
+) -> Result<Option<(AccountInfo<'info>, AccountInfo<'info>)>> {
+    match remaining_accounts {
+        [] => Ok(None),
+        [keeper_token_a_account, keeper_token_b_account] => Ok(Some((
+            keeper_token_account(
+                keeper_token_a_account,
+                vault_account.input_token_a_mint_pubkey,
+            )?,
+            keeper_token_account(
+                keeper_token_b_account,
+                vault_account.input_token_b_mint_pubkey,
+            )?,
+        ))),
+        _ => Err(ErrorCode::InvalidNumberOfAccounts.into()),
     }
+}
 
-    fn _transfer_from_vault_to_treasury_ctx(
-        &self,
-        vault: &Account<'info, TokenAccount>,
-        treasury: &Account<'info, TokenAccount>,
-    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+/// Check the keeper token account holds the bounty mint
+pub(crate) fn keeper_token_account<'info>(
+    account_info: &AccountInfo<'info>,
+    mint: Pubkey,
+) -> Result<AccountInfo<'info>> {
+    let token_account = Account::<TokenAccount>::try_from(account_info)?;
+    require!(token_account.mint == mint, ErrorCode::InvalidKeeperAccount);
+    Ok(account_info.clone())
+}
+
+/// Transfer amount out of a vault token account, skipping empty transfers
+pub(crate) fn transfer_from_vault<'info>(
+    token_program: &AccountInfo<'info>,
+    vault_account: &AccountInfo<'info>,
+    vault_token_account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    amount: u64,
+    signer: &[&[&[u8]]],
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+
+    token::transfer(
         CpiContext::new(
-            self.token_program.to_account_info(),
+            token_program.clone(),
             Transfer {
-                from: vault.to_account_info(),
-                to: treasury.to_account_info(),
-                authority: self.vault_account.to_account_info(),
+                from: vault_token_account.clone(),
+                to: destination.clone(),
+                authority: vault_account.clone(),
             },
         )
-    }
+        .with_signer(signer),
+        amount,
+    )
 }
 
-pub fn handler(ctx: Context<CollectFees>) -> Result<()> {
+/// Collect the fees of a vault position. The keeper bounty is paid to the caller
+/// token accounts of token_a and token_b, when provided as remaining accounts
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, CollectFees<'info>>) -> Result<()> {
     let seeds = generate_seeds!(ctx.accounts.vault_account);
     let signer = &[&seeds[..]];
 
@@ -165,39 +302,53 @@ pub fn handler(ctx: Context<CollectFees>) -> Result<()> {
     let amount_a_increase = amount_a_after.safe_sub(amount_a_before)?;
     let amount_b_increase = amount_b_after.safe_sub(amount_b_before)?;
 
-    let mut treasury_fee_a: u64 = 0;
-    let mut treasury_fee_b: u64 = 0;
+    // skip the threshold in order to be able to claim all pending rewards & close the position
+    let charge_all = has_zero_liquidity;
+    require!(
+        charge_all
+            || ctx
+                .accounts
+                .vault_account
+                .fees_meet_threshold(amount_a_increase, amount_b_increase),
+        ErrorCode::NotEnoughFees
+    );
 
-    if ctx.accounts.vault_account.fee > 0 {
-        // amount increase > FEE SCALE in order to reduce the error produced by rounding
-        // skip the check in order to be able to claim all pending rewards & close the position
-        if !has_zero_liquidity {
-            require!(amount_a_increase > FEE_SCALE, ErrorCode::NotEnoughFees);
-            require!(amount_b_increase > FEE_SCALE, ErrorCode::NotEnoughFees);
-        }
+    let fees_accounts = ctx.accounts.fees_accounts();
 
-        treasury_fee_a =
-            amount_a_increase.safe_mul_div_round_up(ctx.accounts.vault_account.fee, FEE_SCALE)?;
-        treasury_fee_b =
-            amount_b_increase.safe_mul_div_round_up(ctx.accounts.vault_account.fee, FEE_SCALE)?;
-
-        token::transfer(
-            ctx.accounts
-                .transfer_token_a_from_vault_to_treasury_ctx()
-                .with_signer(signer),
-            treasury_fee_a,
-        )?;
-        token::transfer(
-            ctx.accounts
-                .transfer_token_b_from_vault_to_treasury_ctx()
-                .with_signer(signer),
-            treasury_fee_b,
-        )?;
-    }
+    let (treasury_fee_a, treasury_fee_b) = transfer_treasury_fees(
+        &mut ctx.accounts.vault_account,
+        &fees_accounts,
+        amount_a_increase,
+        amount_b_increase,
+        charge_all,
+    )?;
+
+    // Only the fees collected by this call pay the bounty
+    let keeper_accounts =
+        keeper_input_token_accounts(ctx.remaining_accounts, &ctx.accounts.vault_account)?;
+    let (keeper_bounty_a, keeper_bounty_b) = pay_keeper_bounties(
+        &mut ctx.accounts.vault_account,
+        &fees_accounts.token_program,
+        &fees_accounts.vault_input_token_a_account,
+        &fees_accounts.vault_input_token_b_account,
+        keeper_accounts,
+        amount_after_fee(amount_a_increase, treasury_fee_a),
+        amount_after_fee(amount_b_increase, treasury_fee_b),
+    )?;
+
+    let (sqrt_price, curr_tick) = ctx.accounts.position.sqrt_price_and_tick()?;
 
     let vault = &mut ctx.accounts.vault_account;
     vault.earned_rewards_token_a = vault.earned_rewards_token_a.safe_add(amount_a_increase)?;
     vault.earned_rewards_token_b = vault.earned_rewards_token_b.safe_add(amount_b_increase)?;
+    // Keep the price history fresh, with checked prices only
+    let current_slot = Clock::get()?.slot;
+    if vault
+        .check_price_deviation(sqrt_price, current_slot)
+        .is_ok()
+    {
+        vault.price_history.record(current_slot, curr_tick);
+    }
 
     emit!(CollectFeesEvent {
         vault_account: ctx.accounts.vault_account.key(),
@@ -205,6 +356,9 @@ pub fn handler(ctx: Context<CollectFees>) -> Result<()> {
         total_fees_token_b: amount_b_increase,
         treasury_fee_token_a: treasury_fee_a,
         treasury_fee_token_b: treasury_fee_b,
+        keeper_bounty_token_a: keeper_bounty_a,
+        keeper_bounty_token_b: keeper_bounty_b,
+        sqrt_price,
     });
 
     Ok(())
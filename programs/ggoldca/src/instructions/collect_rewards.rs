@@ -1,31 +1,43 @@
 use crate::error::ErrorCode;
+use crate::instructions::collect_fees::{keeper_token_account, transfer_from_vault};
 use crate::interfaces::whirlpool_position::*;
 use crate::macros::generate_seeds;
-use crate::math::safe_arithmetics::{SafeArithmetics, SafeMulDiv};
-use crate::state::VaultAccount;
-use crate::{FEE_SCALE, TREASURY_PUBKEY, VAULT_ACCOUNT_SEED, VAULT_VERSION};
+use crate::math::safe_arithmetics::SafeArithmetics;
+use crate::state::{MarketRewards, VaultAccount};
+use crate::{TREASURY_PUBKEY, VAULT_ACCOUNT_SEED, VAULT_LP_TOKEN_MINT_SEED, VAULT_VERSION};
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::pubkey::Pubkey;
 use anchor_lang_for_whirlpool::context::CpiContext as CpiContextForWhirlpool;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token::{Mint, Token, TokenAccount};
 use whirlpool::cpi::accounts::{CollectReward, UpdateFeesAndRewards};
 
 #[event]
-struct CollectRewardsEvent {
-    vault_account: Pubkey,
-    total_rewards: u64,
-    treasury_fee: u64,
+pub struct CollectRewardsEvent {
+    pub vault_account: Pubkey,
+    pub rewards_mint: Pubkey,
+    pub total_rewards: u64,
+    pub treasury_fee: u64,
+    pub keeper_bounty: u64,
+    pub distributed_rewards: u64,
+    pub sqrt_price: u128,
 }
 
 #[derive(Accounts)]
 pub struct CollectRewards<'info> {
     #[account(
+        mut,
         constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
         seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
         bump = vault_account.bumps.vault
     )]
     pub vault_account: Box<Account<'info, VaultAccount>>,
 
+    #[account(
+        seeds = [VAULT_LP_TOKEN_MINT_SEED, vault_account.key().as_ref()],
+        bump = vault_account.bumps.lp_token_mint
+    )]
+    pub vault_lp_token_mint_pubkey: Account<'info, Mint>,
+
     #[account(
         mut,
         associated_token::mint = vault_rewards_token_account.mint,
@@ -89,21 +101,110 @@ impl<'info> CollectRewards<'info> {
         )
     }
 
-    fn transfer_from_vault_to_treasury_ctx(
+    fn rewards_accounts(
         &self,
-    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        CpiContext::new(
-            self.token_program.to_account_info(),
-            Transfer {
-                from: self.vault_rewards_token_account.to_account_info(),
-                to: self.treasury_rewards_token_account.to_account_info(),
-                authority: self.vault_account.to_account_info(),
-            },
-        )
+        keeper_rewards_token_account: Option<AccountInfo<'info>>,
+    ) -> RewardsAccounts<'info> {
+        RewardsAccounts {
+            token_program: self.token_program.to_account_info(),
+            vault_rewards_token_account: self.vault_rewards_token_account.to_account_info(),
+            treasury_rewards_token_account: self.treasury_rewards_token_account.to_account_info(),
+            keeper_rewards_token_account,
+        }
     }
 }
 
-pub fn handler(ctx: Context<CollectRewards>, reward_index: u8) -> Result<()> {
+/// Token accounts the collected rewards are shared between
+pub(crate) struct RewardsAccounts<'info> {
+    pub token_program: AccountInfo<'info>,
+    pub vault_rewards_token_account: AccountInfo<'info>,
+    pub treasury_rewards_token_account: AccountInfo<'info>,
+    /// Caller token account of the rewards mint, receiving the keeper bounty
+    pub keeper_rewards_token_account: Option<AccountInfo<'info>>,
+}
+
+/// Split of the collected rewards
+pub(crate) struct CollectedRewards {
+    /// Treasury fee, plus the distribute rewards when no LP tokens are locked
+    pub treasury_fee: u64,
+    pub keeper_bounty: u64,
+    pub distributed_rewards: u64,
+}
+
+/// Charge the treasury fee on the collected rewards. The rewards of transfer and distribute
+/// markets are not swapped later, so they pay the keeper bounty here, and the rewards of
+/// distribute markets are streamed to the locked LP tokens, whose holders claim them later.
+/// Without locked LP tokens nobody accrues them, so they are sent to the treasury
+pub(crate) fn settle_collected_rewards<'info>(
+    vault_account: &mut Account<'info, VaultAccount>,
+    accounts: &RewardsAccounts<'info>,
+    reward_index: usize,
+    rewards_mint: Pubkey,
+    amount: u64,
+) -> Result<CollectedRewards> {
+    let market = vault_account.market_rewards[reward_index];
+    let treasury_fee = vault_account.rewards_treasury_fee(amount)?;
+
+    let keeper_bounty = match (&accounts.keeper_rewards_token_account, market.id) {
+        (Some(_), MarketRewards::Transfer | MarketRewards::Distribute) => vault_account
+            .keeper_bounty
+            .rewards_amount(reward_index, amount.safe_sub(treasury_fee)?)?,
+        _ => 0,
+    };
+
+    let mut treasury_amount = treasury_fee;
+    let mut distributed_rewards: u64 = 0;
+    if market.id == MarketRewards::Distribute {
+        require!(
+            market.rewards_mint == rewards_mint,
+            ErrorCode::InvalidRewardMint
+        );
+
+        let net_rewards = amount.safe_sub(treasury_fee)?.safe_sub(keeper_bounty)?;
+        if vault_account.rewards_locked_lp > 0 {
+            distributed_rewards = net_rewards;
+            vault_account.distribute_rewards(reward_index, distributed_rewards)?;
+        } else {
+            treasury_amount = treasury_amount.safe_add(net_rewards)?;
+        }
+    }
+
+    let seeds = generate_seeds!(vault_account);
+    let signer = &[&seeds[..]];
+    let vault_account_info = vault_account.to_account_info();
+
+    transfer_from_vault(
+        &accounts.token_program,
+        &vault_account_info,
+        &accounts.vault_rewards_token_account,
+        &accounts.treasury_rewards_token_account,
+        treasury_amount,
+        signer,
+    )?;
+    if let Some(keeper_rewards_token_account) = &accounts.keeper_rewards_token_account {
+        transfer_from_vault(
+            &accounts.token_program,
+            &vault_account_info,
+            &accounts.vault_rewards_token_account,
+            keeper_rewards_token_account,
+            keeper_bounty,
+            signer,
+        )?;
+    }
+
+    Ok(CollectedRewards {
+        treasury_fee: treasury_amount,
+        keeper_bounty,
+        distributed_rewards,
+    })
+}
+
+/// Collect the rewards of a vault position. The keeper bounty of transfer and distribute
+/// markets is paid to the caller rewards token account, when provided as first remaining account
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CollectRewards<'info>>,
+    reward_index: u8,
+) -> Result<()> {
     let seeds = generate_seeds!(ctx.accounts.vault_account);
     let signer = &[&seeds[..]];
 
@@ -125,29 +226,43 @@ pub fn handler(ctx: Context<CollectRewards>, reward_index: u8) -> Result<()> {
     let amount_after = ctx.accounts.vault_rewards_token_account.amount;
     let amount_increase = amount_after.safe_sub(amount_before)?;
 
-    let mut treasury_fee: u64 = 0;
-    if ctx.accounts.vault_account.fee > 0 {
-        // amount increase > FEE SCALE in order to reduce the error produced by rounding
-        // skip the check in order to be able to claim all pending rewards & close the position
-        if !has_zero_liquidity {
-            require!(amount_increase > FEE_SCALE, ErrorCode::NotEnoughRewards);
-        }
+    // skip the check in order to be able to claim all pending rewards & close the position
+    require!(
+        has_zero_liquidity
+            || ctx
+                .accounts
+                .vault_account
+                .rewards_meet_threshold(amount_increase),
+        ErrorCode::NotEnoughRewards
+    );
 
-        treasury_fee =
-            amount_increase.safe_mul_div_round_up(ctx.accounts.vault_account.fee, FEE_SCALE)?;
+    let rewards_mint = ctx.accounts.vault_rewards_token_account.mint;
+    let keeper_rewards_token_account = ctx
+        .remaining_accounts
+        .first()
+        .map(|account_info| keeper_token_account(account_info, rewards_mint))
+        .transpose()?;
 
-        token::transfer(
-            ctx.accounts
-                .transfer_from_vault_to_treasury_ctx()
-                .with_signer(signer),
-            treasury_fee,
-        )?;
-    }
+    let rewards_accounts = ctx.accounts.rewards_accounts(keeper_rewards_token_account);
+
+    let collected = settle_collected_rewards(
+        &mut ctx.accounts.vault_account,
+        &rewards_accounts,
+        usize::from(reward_index),
+        rewards_mint,
+        amount_increase,
+    )?;
+
+    let (sqrt_price, _) = ctx.accounts.position.sqrt_price_and_tick()?;
 
     emit!(CollectRewardsEvent {
         vault_account: ctx.accounts.vault_account.key(),
+        rewards_mint,
         total_rewards: amount_increase,
-        treasury_fee,
+        treasury_fee: collected.treasury_fee,
+        keeper_bounty: collected.keeper_bounty,
+        distributed_rewards: collected.distributed_rewards,
+        sqrt_price,
     });
 
     Ok(())
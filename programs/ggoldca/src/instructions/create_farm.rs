@@ -0,0 +1,82 @@
+use crate::error::ErrorCode;
+use crate::state::{Farm, VaultAccount};
+use crate::{FARM_SEED, VAULT_ACCOUNT_SEED, VAULT_LP_TOKEN_MINT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+
+#[event]
+struct CreateFarmEvent {
+    farm: Pubkey,
+    vault_account: Pubkey,
+    id: u8,
+    rewards_mint: Pubkey,
+    funder: Pubkey,
+}
+
+#[derive(Accounts)]
+#[instruction(id: u8)]
+pub struct CreateFarm<'info> {
+    #[account(mut)]
+    pub user_signer: Signer<'info>,
+    #[account(
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+    #[account(
+        seeds = [VAULT_LP_TOKEN_MINT_SEED, vault_account.key().as_ref()],
+        bump = vault_account.bumps.lp_token_mint
+    )]
+    pub vault_lp_token_mint_pubkey: Account<'info, Mint>,
+    pub rewards_mint: Account<'info, Mint>,
+    #[account(
+        init,
+        payer = user_signer,
+        space = 8 + Farm::SIZE,
+        seeds = [FARM_SEED, vault_account.key().as_ref(), &[id][..]],
+        bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        init,
+        payer = user_signer,
+        associated_token::mint = vault_lp_token_mint_pubkey,
+        associated_token::authority = farm,
+    )]
+    pub farm_lp_token_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        init,
+        payer = user_signer,
+        associated_token::mint = rewards_mint,
+        associated_token::authority = farm,
+    )]
+    pub farm_rewards_token_account: Box<Account<'info, TokenAccount>>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+pub fn handler(ctx: Context<CreateFarm>, id: u8, funder: Pubkey) -> Result<()> {
+    ctx.accounts.farm.set_inner(Farm {
+        vault_account: ctx.accounts.vault_account.key(),
+        id,
+        bump: *ctx.bumps.get("farm").unwrap(),
+        lp_token_mint: ctx.accounts.vault_lp_token_mint_pubkey.key(),
+        rewards_mint: ctx.accounts.rewards_mint.key(),
+        funder,
+        ..Farm::default()
+    });
+
+    emit!(CreateFarmEvent {
+        farm: ctx.accounts.farm.key(),
+        vault_account: ctx.accounts.vault_account.key(),
+        id,
+        rewards_mint: ctx.accounts.rewards_mint.key(),
+        funder,
+    });
+
+    Ok(())
+}
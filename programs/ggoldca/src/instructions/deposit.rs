@@ -1,8 +1,9 @@
 use crate::error::ErrorCode;
 use crate::interfaces::whirlpool_position::*;
 use crate::macros::generate_seeds;
-use crate::math::safe_arithmetics::{SafeArithmetics, SafeMulDiv};
-use crate::state::VaultAccount;
+use crate::math::quote::deposit_quote;
+use crate::math::safe_arithmetics::SafeArithmetics;
+use crate::state::{UserPosition, VaultAccount};
 use crate::{VAULT_ACCOUNT_SEED, VAULT_LP_TOKEN_MINT_SEED, VAULT_VERSION};
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::pubkey::Pubkey;
@@ -12,15 +13,21 @@ use anchor_spl::token::{self, Approve, Burn, Mint, MintTo, Revoke, Token, TokenA
 #[event]
 pub struct DepositWithdrawEvent {
     pub vault_account: Pubkey,
+    pub user: Pubkey,
+    pub is_deposit: bool,
+    pub lp_amount: u64,
     pub amount_a: u64,
     pub amount_b: u64,
     pub liquidity: u128,
+    /// Pool sqrt price after the liquidity change
+    pub sqrt_price: u128,
 }
 
 #[derive(Accounts)]
 pub struct DepositWithdraw<'info> {
     pub user_signer: Signer<'info>,
     #[account(
+        mut,
         constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
         seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
         bump = vault_account.bumps.vault
@@ -83,6 +90,42 @@ pub struct DepositWithdraw<'info> {
 }
 
 impl<'info> DepositWithdraw<'info> {
+    /// Check the pool price against the vault TWAP and record it
+    pub fn check_and_record_price(&mut self) -> Result<()> {
+        let current_slot = Clock::get()?.slot;
+        let (curr_sqrt_price, curr_tick) = self.position.sqrt_price_and_tick()?;
+
+        self.vault_account
+            .check_price_deviation(curr_sqrt_price, current_slot)?;
+        self.vault_account
+            .price_history
+            .record(current_slot, curr_tick);
+
+        Ok(())
+    }
+
+    /// Update the user position, when provided as first remaining account
+    pub fn update_user_position(
+        &self,
+        remaining_accounts: &[AccountInfo<'info>],
+        update: impl FnOnce(&mut UserPosition) -> Result<()>,
+    ) -> Result<()> {
+        let user_position_info = match remaining_accounts.first() {
+            Some(user_position_info) => user_position_info,
+            None => return Ok(()),
+        };
+
+        let mut user_position = Account::<UserPosition>::try_from(user_position_info)?;
+        require!(
+            user_position.vault_account == self.vault_account.key()
+                && user_position.user == self.user_signer.key(),
+            ErrorCode::InvalidUserPosition
+        );
+
+        update(&mut user_position)?;
+        user_position.exit(&crate::ID)
+    }
+
     fn transfer_token_a_from_user_to_vault_ctx(
         &self,
     ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
@@ -245,45 +288,44 @@ pub fn handler(
 ) -> Result<()> {
     require!(lp_amount > 0, ErrorCode::ZeroLpAmount);
 
+    ctx.accounts.check_and_record_price()?;
+
     let amount_user_a_before = ctx.accounts.user_token_a_account.amount;
     let amount_user_b_before = ctx.accounts.user_token_b_account.amount;
 
-    let supply = ctx.accounts.vault_lp_token_mint_pubkey.supply;
-    let liquidity = ctx.accounts.position.liquidity()?;
-
-    let user_liquidity = if supply > 0 {
-        liquidity.safe_mul_div_round_up(u128::from(lp_amount), u128::from(supply))?
-    } else {
-        u128::from(lp_amount)
-    };
-
-    if supply > 0 {
-        let vault_amount_a = ctx.accounts.vault_input_token_a_account.amount;
-        let vault_amount_b = ctx.accounts.vault_input_token_b_account.amount;
-
-        if vault_amount_a > 0 {
-            let amount_a = vault_amount_a.safe_mul_div_round_up(lp_amount, supply)?;
-
-            require!(amount_a <= max_amount_a, ErrorCode::ExceededTokenMax);
-            max_amount_a = max_amount_a.safe_sub(amount_a)?;
-
-            token::transfer(
-                ctx.accounts.transfer_token_a_from_user_to_vault_ctx(),
-                amount_a,
-            )?;
-        }
-
-        if vault_amount_b > 0 {
-            let amount_b = vault_amount_b.safe_mul_div_round_up(lp_amount, supply)?;
-
-            require!(amount_b <= max_amount_b, ErrorCode::ExceededTokenMax);
-            max_amount_b = max_amount_b.safe_sub(amount_b)?;
+    let quote = deposit_quote(
+        lp_amount,
+        ctx.accounts.vault_lp_token_mint_pubkey.supply,
+        ctx.accounts.position.liquidity()?,
+        ctx.accounts.vault_input_token_a_account.amount,
+        ctx.accounts.vault_input_token_b_account.amount,
+    )?;
+    let user_liquidity = quote.liquidity;
+
+    if quote.vault_amount_a > 0 {
+        require!(
+            quote.vault_amount_a <= max_amount_a,
+            ErrorCode::ExceededTokenMax
+        );
+        max_amount_a = max_amount_a.safe_sub(quote.vault_amount_a)?;
+
+        token::transfer(
+            ctx.accounts.transfer_token_a_from_user_to_vault_ctx(),
+            quote.vault_amount_a,
+        )?;
+    }
 
-            token::transfer(
-                ctx.accounts.transfer_token_b_from_user_to_vault_ctx(),
-                amount_b,
-            )?;
-        }
+    if quote.vault_amount_b > 0 {
+        require!(
+            quote.vault_amount_b <= max_amount_b,
+            ErrorCode::ExceededTokenMax
+        );
+        max_amount_b = max_amount_b.safe_sub(quote.vault_amount_b)?;
+
+        token::transfer(
+            ctx.accounts.transfer_token_b_from_user_to_vault_ctx(),
+            quote.vault_amount_b,
+        )?;
     }
 
     token::approve(ctx.accounts.delegate_user_to_vault_a_ctx(), max_amount_a)?;
@@ -316,11 +358,28 @@ pub fn handler(
     let amount_user_a_diff = amount_user_a_before.safe_sub(amount_user_a_after)?;
     let amount_user_b_diff = amount_user_b_before.safe_sub(amount_user_b_after)?;
 
+    let current_slot = Clock::get()?.slot;
+    ctx.accounts
+        .update_user_position(ctx.remaining_accounts, |user_position| {
+            user_position.record_deposit(
+                current_slot,
+                amount_user_a_diff,
+                amount_user_b_diff,
+                lp_amount,
+            )
+        })?;
+
+    let (sqrt_price, _) = ctx.accounts.position.sqrt_price_and_tick()?;
+
     emit!(DepositWithdrawEvent {
         vault_account: ctx.accounts.vault_account.key(),
+        user: ctx.accounts.user_signer.key(),
+        is_deposit: true,
+        lp_amount,
         amount_a: amount_user_a_diff,
         amount_b: amount_user_b_diff,
-        liquidity: user_liquidity
+        liquidity: user_liquidity,
+        sqrt_price,
     });
 
     Ok(())
@@ -0,0 +1,68 @@
+use crate::state::Farm;
+use crate::FARM_SEED;
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+#[event]
+struct FundFarmEvent {
+    farm: Pubkey,
+    amount: u64,
+    reward_rate_per_slot: u64,
+    end_slot: u64,
+}
+
+#[derive(Accounts)]
+pub struct FundFarm<'info> {
+    pub funder: Signer<'info>,
+    #[account(
+        mut,
+        has_one = funder,
+        seeds = [FARM_SEED, farm.vault_account.as_ref(), &[farm.id][..]],
+        bump = farm.bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        mut,
+        associated_token::mint = farm.rewards_mint,
+        associated_token::authority = farm,
+    )]
+    pub farm_rewards_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = farm.rewards_mint,
+        token::authority = funder.key(),
+    )]
+    pub funder_rewards_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> FundFarm<'info> {
+    fn transfer_from_funder_to_farm_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.funder_rewards_token_account.to_account_info(),
+                to: self.farm_rewards_token_account.to_account_info(),
+                authority: self.funder.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn handler(ctx: Context<FundFarm>, amount: u64, duration_slots: u64) -> Result<()> {
+    token::transfer(ctx.accounts.transfer_from_funder_to_farm_ctx(), amount)?;
+
+    let current_slot = Clock::get()?.slot;
+    ctx.accounts
+        .farm
+        .fund(amount, duration_slots, current_slot)?;
+
+    emit!(FundFarmEvent {
+        farm: ctx.accounts.farm.key(),
+        amount,
+        reward_rate_per_slot: ctx.accounts.farm.reward_rate_per_slot,
+        end_slot: ctx.accounts.farm.end_slot,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use crate::error::ErrorCode;
+use crate::math::performance::{apr_bps, apy_bps};
+use crate::state::VaultHistory;
+use crate::VAULT_HISTORY_SEED;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::set_return_data;
+
+/// Vault performance between two snapshots, set as return data
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default, Debug)]
+pub struct VaultApy {
+    pub start_slot: u64,
+    pub end_slot: u64,
+    pub elapsed_seconds: u64,
+    /// Annualized growth of the value per LP token in token b, both snapshots being valued
+    /// at the end snapshot price so that only the vault yield is measured, in bps
+    pub apr_bps: i64,
+    pub apy_bps: i64,
+}
+
+#[derive(Accounts)]
+pub struct GetVaultApy<'info> {
+    #[account(
+        seeds = [VAULT_HISTORY_SEED, vault_history.vault_account.as_ref()],
+        bump = vault_history.bump
+    )]
+    pub vault_history: Box<Account<'info, VaultHistory>>,
+}
+
+/// Snapshots indexes are chronological (0 = oldest)
+pub fn handler(ctx: Context<GetVaultApy>, start_index: u8, end_index: u8) -> Result<()> {
+    let history = &ctx.accounts.vault_history;
+
+    let start = history
+        .get(start_index.into())
+        .ok_or_else(|| error!(ErrorCode::HistorySnapshotNotFound))?;
+    let end = history
+        .get(end_index.into())
+        .ok_or_else(|| error!(ErrorCode::HistorySnapshotNotFound))?;
+
+    let elapsed_seconds = end
+        .unix_timestamp
+        .checked_sub(start.unix_timestamp)
+        .and_then(|elapsed_seconds| u64::try_from(elapsed_seconds).ok())
+        .ok_or(ErrorCode::MathOverflowSub)?;
+
+    let start_value_x64 = start.value_per_lp_x64(end.sqrt_price)?;
+    let end_value_x64 = end.value_per_lp_x64(end.sqrt_price)?;
+
+    let vault_apy = VaultApy {
+        start_slot: start.slot,
+        end_slot: end.slot,
+        elapsed_seconds,
+        apr_bps: apr_bps(start_value_x64, end_value_x64, elapsed_seconds)?,
+        apy_bps: apy_bps(start_value_x64, end_value_x64, elapsed_seconds)?,
+    };
+
+    set_return_data(&vault_apy.try_to_vec()?);
+
+    Ok(())
+}
@@ -0,0 +1,692 @@
+use crate::error::ErrorCode;
+use crate::instructions::collect_fees::{
+    keeper_input_token_accounts, pay_keeper_bounties, transfer_treasury_fees, FeesAccounts,
+};
+use crate::instructions::collect_rewards::{
+    settle_collected_rewards, CollectRewardsEvent, RewardsAccounts,
+};
+use crate::instructions::reinvest::ReinvestAccounts;
+use crate::instructions::swap_rewards::{
+    rewards_min_amount_out, rewards_swap_accounts_len, swap_rewards_cpi, SwapEvent,
+    SwapRewardsEvent,
+};
+use crate::interfaces::swap_adapter::SwapAccounts;
+use crate::interfaces::whirlpool_position::*;
+use crate::macros::generate_seeds;
+use crate::math::safe_arithmetics::SafeArithmetics;
+use crate::state::{
+    amount_after_fee, MarketRewards, MarketRewardsInfo, RewardsPriceBound, SwapRoute, VaultAccount,
+};
+use crate::{TREASURY_PUBKEY, VAULT_ACCOUNT_SEED, VAULT_LP_TOKEN_MINT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey::Pubkey;
+use anchor_lang_for_whirlpool::context::CpiContext as CpiContextForWhirlpool;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use whirlpool::cpi::accounts::{CollectFees as WhCollectFees, CollectReward, UpdateFeesAndRewards};
+
+/// Number of accounts preceding the swap accounts in each rewards group:
+/// reward_vault, vault_rewards_token_account, treasury_rewards_token_account
+const REWARDS_GROUP_BASE_LEN: usize = 3;
+
+#[event]
+struct HarvestAndCompoundEvent {
+    vault_account: Pubkey,
+    total_fees_token_a: u64,
+    total_fees_token_b: u64,
+    treasury_fee_token_a: u64,
+    treasury_fee_token_b: u64,
+    rewards_swapped_token_a: u64,
+    rewards_swapped_token_b: u64,
+    keeper_bounty_token_a: u64,
+    keeper_bounty_token_b: u64,
+    lp_supply: u64,
+    liquidity: u128,
+    liquidity_increase: u128,
+    elapsed_slots: u64,
+    /// Pool sqrt price after the reinvestment
+    sqrt_price: u128,
+}
+
+/// Collect the fees and rewards of the active position, swap the rewards and reinvest
+/// everything in a single instruction.
+///
+/// For each reward index whose market is set (in ascending order), the remaining accounts
+/// must contain the group: reward_vault, vault_rewards_token_account,
+/// treasury_rewards_token_account. The groups of swap markets continue with the swap accounts,
+/// either the swap_program and the market specific accounts, or the route hops accounts
+/// (see swap_rewards::RouteAccounts), and finally the accounts required by the rewards price
+/// bound. The rewards of transfer and distribute markets are only collected, as collect_rewards
+/// does.
+///
+/// The keeper bounty is paid on the fees and swapped rewards harvested by the call, to the
+/// caller token accounts of token_a and token_b when provided after the rewards groups.
+#[derive(Accounts)]
+pub struct HarvestAndCompound<'info> {
+    #[account(
+        mut,
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+    #[account(
+        mint::authority = vault_account.key(),
+        seeds = [VAULT_LP_TOKEN_MINT_SEED, vault_account.key().as_ref()],
+        bump = vault_account.bumps.lp_token_mint
+    )]
+    pub vault_lp_token_mint_pubkey: Account<'info, Mint>,
+
+    #[account(address = whirlpool::ID)]
+    /// CHECK: address is checked
+    pub whirlpool_program_id: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = vault_account.input_token_a_mint_pubkey,
+        associated_token::authority = vault_account,
+    )]
+    pub vault_input_token_a_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = vault_account.input_token_b_mint_pubkey,
+        associated_token::authority = vault_account,
+    )]
+    pub vault_input_token_b_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = vault_account.input_token_a_mint_pubkey,
+        associated_token::authority = TREASURY_PUBKEY
+    )]
+    pub treasury_token_a_account: Box<Account<'info, TokenAccount>>,
+    #[account(
+        mut,
+        associated_token::mint = vault_account.input_token_b_mint_pubkey,
+        associated_token::authority = TREASURY_PUBKEY
+    )]
+    pub treasury_token_b_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    /// CHECK: whirlpool cpi
+    pub token_vault_a: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: whirlpool cpi
+    pub token_vault_b: AccountInfo<'info>,
+
+    #[account(
+        constraint = position.whirlpool.key() == vault_account.whirlpool_id.key(),
+        constraint = position.position.key() == vault_account.active_position_key() @ ErrorCode::PositionNotActive,
+    )]
+    pub position: PositionAccounts<'info>,
+
+    #[account(mut)]
+    /// CHECK: whirlpool cpi
+    pub tick_array_0: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: whirlpool cpi
+    pub tick_array_1: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: whirlpool cpi
+    pub tick_array_2: AccountInfo<'info>,
+    #[account(mut)]
+    /// CHECK: whirlpool cpi
+    pub oracle: AccountInfo<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> HarvestAndCompound<'info> {
+    fn update_fees_and_rewards_ctx(
+        &self,
+    ) -> CpiContextForWhirlpool<'_, '_, '_, 'info, UpdateFeesAndRewards<'info>> {
+        CpiContextForWhirlpool::new(
+            self.whirlpool_program_id.to_account_info(),
+            UpdateFeesAndRewards {
+                whirlpool: self.position.whirlpool.to_account_info(),
+                position: self.position.position.to_account_info(),
+                tick_array_lower: self.position.tick_array_lower.to_account_info(),
+                tick_array_upper: self.position.tick_array_upper.to_account_info(),
+            },
+        )
+    }
+
+    fn collect_fees_ctx(&self) -> CpiContextForWhirlpool<'_, '_, '_, 'info, WhCollectFees<'info>> {
+        CpiContextForWhirlpool::new(
+            self.whirlpool_program_id.to_account_info(),
+            WhCollectFees {
+                whirlpool: self.position.whirlpool.to_account_info(),
+                position_authority: self.vault_account.to_account_info(),
+                position: self.position.position.to_account_info(),
+                position_token_account: self.position.position_token_account.to_account_info(),
+                token_owner_account_a: self.vault_input_token_a_account.to_account_info(),
+                token_owner_account_b: self.vault_input_token_b_account.to_account_info(),
+                token_vault_a: self.token_vault_a.to_account_info(),
+                token_vault_b: self.token_vault_b.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            },
+        )
+    }
+
+    fn collect_reward_ctx(
+        &self,
+        reward_vault: &AccountInfo<'info>,
+        vault_rewards_token_account: &AccountInfo<'info>,
+    ) -> CpiContextForWhirlpool<'_, '_, '_, 'info, CollectReward<'info>> {
+        CpiContextForWhirlpool::new(
+            self.whirlpool_program_id.to_account_info(),
+            CollectReward {
+                whirlpool: self.position.whirlpool.to_account_info(),
+                position_authority: self.vault_account.to_account_info(),
+                position: self.position.position.to_account_info(),
+                position_token_account: self.position.position_token_account.to_account_info(),
+                reward_vault: reward_vault.to_account_info(),
+                reward_owner_account: vault_rewards_token_account.to_account_info(),
+                token_program: self.token_program.to_account_info(),
+            },
+        )
+    }
+
+    fn fees_accounts(&self) -> FeesAccounts<'info> {
+        FeesAccounts {
+            token_program: self.token_program.to_account_info(),
+            vault_input_token_a_account: self.vault_input_token_a_account.to_account_info(),
+            vault_input_token_b_account: self.vault_input_token_b_account.to_account_info(),
+            treasury_token_a_account: self.treasury_token_a_account.to_account_info(),
+            treasury_token_b_account: self.treasury_token_b_account.to_account_info(),
+        }
+    }
+
+    fn reinvest_accounts(&self) -> ReinvestAccounts<'_, 'info> {
+        ReinvestAccounts {
+            whirlpool_program_id: &self.whirlpool_program_id,
+            token_program: self.token_program.to_account_info(),
+            vault_account: self.vault_account.to_account_info(),
+            vault_input_token_a_account: self.vault_input_token_a_account.to_account_info(),
+            vault_input_token_b_account: self.vault_input_token_b_account.to_account_info(),
+            input_token_a_mint: self.vault_account.input_token_a_mint_pubkey,
+            input_token_b_mint: self.vault_account.input_token_b_mint_pubkey,
+            token_vault_a: &self.token_vault_a,
+            token_vault_b: &self.token_vault_b,
+            position: &self.position,
+            tick_arrays: [&self.tick_array_0, &self.tick_array_1, &self.tick_array_2],
+            oracle: &self.oracle,
+        }
+    }
+}
+
+/// Number of remaining accounts of the rewards group of a market, None when the market
+/// has no group
+fn rewards_group_len(
+    market: &MarketRewardsInfo,
+    route: &SwapRoute,
+    price_bound: &RewardsPriceBound,
+) -> Result<Option<usize>> {
+    if market.rewards_mint == Pubkey::default() {
+        return Ok(None);
+    }
+
+    match market.id {
+        MarketRewards::NotSet => Ok(None),
+        MarketRewards::Transfer | MarketRewards::Distribute => Ok(Some(REWARDS_GROUP_BASE_LEN)),
+        _ => {
+            // Direct swaps are preceded by their swap_program
+            let swap_program_len = if route.is_set() { 0 } else { 1 };
+
+            Ok(Some(
+                REWARDS_GROUP_BASE_LEN
+                    .safe_add(swap_program_len)?
+                    .safe_add(rewards_swap_accounts_len(market, route)?)?
+                    .safe_add(price_bound.accounts_len())?,
+            ))
+        }
+    }
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, HarvestAndCompound<'info>>) -> Result<()> {
+    let last_slot = ctx.accounts.vault_account.last_reinvestment_slot;
+    let current_slot = Clock::get()?.slot;
+    let elapsed_slots = current_slot.safe_sub(last_slot)?;
+
+    require!(
+        elapsed_slots >= ctx.accounts.vault_account.min_slots_for_reinvest,
+        ErrorCode::NotEnoughSlots
+    );
+
+    ctx.accounts.vault_account.last_reinvestment_slot = current_slot;
+
+    let (curr_sqrt_price, curr_tick) = ctx.accounts.position.sqrt_price_and_tick()?;
+    ctx.accounts
+        .vault_account
+        .check_price_deviation(curr_sqrt_price, current_slot)?;
+    ctx.accounts
+        .vault_account
+        .price_history
+        .record(current_slot, curr_tick);
+
+    let vault_key = ctx.accounts.vault_account.key();
+    let market_rewards = ctx.accounts.vault_account.market_rewards;
+    let rewards_routes = ctx.accounts.vault_account.rewards_routes;
+    let rewards_price_bounds = ctx.accounts.vault_account.rewards_price_bounds;
+    let rewards_swap_chunking = ctx.accounts.vault_account.rewards_swap_chunking;
+
+    let liquidity_before = ctx.accounts.position.liquidity()?;
+
+    // ORCA doesn't allow to update the fees and rewards for a position with zero liquidity
+    if liquidity_before > 0 {
+        whirlpool::cpi::update_fees_and_rewards(ctx.accounts.update_fees_and_rewards_ctx())?;
+    }
+
+    // Fees
+    let (fee_owed_a, fee_owed_b) = ctx.accounts.position.fees_owed()?;
+    let fees_collectable = (fee_owed_a > 0 || fee_owed_b > 0)
+        && ctx
+            .accounts
+            .vault_account
+            .fees_meet_threshold(fee_owed_a, fee_owed_b);
+
+    let (amount_a_increase, amount_b_increase) = if fees_collectable {
+        let seeds = generate_seeds!(ctx.accounts.vault_account);
+        let signer = &[&seeds[..]];
+
+        let amount_a_before = ctx.accounts.vault_input_token_a_account.amount;
+        let amount_b_before = ctx.accounts.vault_input_token_b_account.amount;
+
+        whirlpool::cpi::collect_fees(ctx.accounts.collect_fees_ctx().with_signer(signer))?;
+
+        ctx.accounts.vault_input_token_a_account.reload()?;
+        ctx.accounts.vault_input_token_b_account.reload()?;
+
+        (
+            ctx.accounts
+                .vault_input_token_a_account
+                .amount
+                .safe_sub(amount_a_before)?,
+            ctx.accounts
+                .vault_input_token_b_account
+                .amount
+                .safe_sub(amount_b_before)?,
+        )
+    } else {
+        (0, 0)
+    };
+
+    let fees_accounts = ctx.accounts.fees_accounts();
+
+    let (treasury_fee_a, treasury_fee_b) = transfer_treasury_fees(
+        &mut ctx.accounts.vault_account,
+        &fees_accounts,
+        amount_a_increase,
+        amount_b_increase,
+        false,
+    )?;
+
+    // Rewards
+    let mut rewards_out_a: u64 = 0;
+    let mut rewards_out_b: u64 = 0;
+    let mut swapped_rewards: Vec<(usize, Pubkey, u64)> = Vec::new();
+    let mut remaining = ctx.remaining_accounts;
+
+    for (reward_index, market) in market_rewards.iter().enumerate() {
+        let route = &rewards_routes[reward_index];
+        let price_bound = &rewards_price_bounds[reward_index];
+
+        let group_len = match rewards_group_len(market, route, price_bound)? {
+            Some(group_len) => group_len,
+            None => continue,
+        };
+        require!(
+            remaining.len() >= group_len,
+            ErrorCode::InvalidNumberOfAccounts
+        );
+
+        let (group, rest) = remaining.split_at(group_len);
+        remaining = rest;
+
+        let reward_vault = &group[0];
+        let vault_rewards_info = &group[1];
+        let treasury_rewards_info = &group[2];
+
+        require!(
+            vault_rewards_info.key()
+                == get_associated_token_address(&vault_key, &market.rewards_mint),
+            ErrorCode::InvalidRewardsAccount
+        );
+        require!(
+            treasury_rewards_info.key()
+                == get_associated_token_address(&TREASURY_PUBKEY, &market.rewards_mint),
+            ErrorCode::InvalidRewardsAccount
+        );
+
+        let mut vault_rewards_token_account =
+            Account::<TokenAccount>::try_from(vault_rewards_info)?;
+
+        // Rewards below the threshold are left in the position for a later harvest
+        let reward_owed = ctx.accounts.position.reward_owed(reward_index)?;
+        if reward_owed > 0
+            && ctx
+                .accounts
+                .vault_account
+                .rewards_meet_threshold(reward_owed)
+        {
+            let seeds = generate_seeds!(ctx.accounts.vault_account);
+            let signer = &[&seeds[..]];
+
+            let amount_before = vault_rewards_token_account.amount;
+
+            whirlpool::cpi::collect_reward(
+                ctx.accounts
+                    .collect_reward_ctx(reward_vault, vault_rewards_info)
+                    .with_signer(signer),
+                reward_index as u8,
+            )?;
+
+            vault_rewards_token_account.reload()?;
+            let amount_increase = vault_rewards_token_account.amount.safe_sub(amount_before)?;
+
+            let collected = settle_collected_rewards(
+                &mut ctx.accounts.vault_account,
+                &RewardsAccounts {
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                    vault_rewards_token_account: vault_rewards_info.clone(),
+                    treasury_rewards_token_account: treasury_rewards_info.clone(),
+                    keeper_rewards_token_account: None,
+                },
+                reward_index,
+                market.rewards_mint,
+                amount_increase,
+            )?;
+
+            vault_rewards_token_account.reload()?;
+
+            emit!(CollectRewardsEvent {
+                vault_account: vault_key,
+                rewards_mint: market.rewards_mint,
+                total_rewards: amount_increase,
+                treasury_fee: collected.treasury_fee,
+                keeper_bounty: collected.keeper_bounty,
+                distributed_rewards: collected.distributed_rewards,
+                sqrt_price: ctx.accounts.position.sqrt_price_and_tick()?.0,
+            });
+        }
+
+        // Transfer & distribute rewards are not swapped
+        if matches!(
+            market.id,
+            MarketRewards::Transfer | MarketRewards::Distribute
+        ) {
+            continue;
+        }
+
+        let chunking = &rewards_swap_chunking[reward_index];
+        let amount_available = vault_rewards_token_account.amount;
+        let amount_to_swap = chunking.amount_to_swap(amount_available);
+
+        // Swapped too recently, the rewards are kept for a later tranche
+        if amount_to_swap == 0 || !chunking.can_swap(current_slot) {
+            continue;
+        }
+
+        let is_destination_a =
+            market.destination_token_account == ctx.accounts.vault_input_token_a_account.key();
+        require!(
+            is_destination_a
+                || market.destination_token_account
+                    == ctx.accounts.vault_input_token_b_account.key(),
+            ErrorCode::InvalidDestinationAccount
+        );
+
+        let (destination, destination_mint, amount_out_before) = if is_destination_a {
+            (
+                ctx.accounts.vault_input_token_a_account.to_account_info(),
+                ctx.accounts.vault_input_token_a_account.mint,
+                ctx.accounts.vault_input_token_a_account.amount,
+            )
+        } else {
+            (
+                ctx.accounts.vault_input_token_b_account.to_account_info(),
+                ctx.accounts.vault_input_token_b_account.mint,
+                ctx.accounts.vault_input_token_b_account.amount,
+            )
+        };
+
+        let swap_accounts_len = rewards_swap_accounts_len(market, route)?;
+        // The swap_program of routes is the first account of their first hop
+        let swap_program = &group[REWARDS_GROUP_BASE_LEN];
+        let swap_accounts = if route.is_set() {
+            &group[REWARDS_GROUP_BASE_LEN..]
+        } else {
+            &group[REWARDS_GROUP_BASE_LEN + 1..]
+        };
+        let (swap_accounts, price_bound_accounts) = swap_accounts.split_at(swap_accounts_len);
+
+        let min_amount_out = rewards_min_amount_out(
+            market,
+            price_bound,
+            destination_mint,
+            amount_to_swap,
+            swap_accounts,
+            price_bound_accounts.first(),
+        )?;
+
+        let seeds = generate_seeds!(ctx.accounts.vault_account);
+        let signer = &[&seeds[..]];
+
+        swap_rewards_cpi(
+            market,
+            route,
+            &SwapAccounts {
+                token_program: &ctx.accounts.token_program.to_account_info(),
+                authority: &ctx.accounts.vault_account.to_account_info(),
+                source_token_account: vault_rewards_info,
+                source_mint: market.rewards_mint,
+                destination_token_account: &destination,
+                destination_mint,
+                swap_program,
+                remaining: swap_accounts,
+            },
+            amount_to_swap,
+            min_amount_out,
+            signer,
+        )?;
+
+        ctx.accounts.vault_input_token_a_account.reload()?;
+        ctx.accounts.vault_input_token_b_account.reload()?;
+
+        let amount_out = if is_destination_a {
+            let amount_out = ctx
+                .accounts
+                .vault_input_token_a_account
+                .amount
+                .safe_sub(amount_out_before)?;
+            rewards_out_a = rewards_out_a.safe_add(amount_out)?;
+            amount_out
+        } else {
+            let amount_out = ctx
+                .accounts
+                .vault_input_token_b_account
+                .amount
+                .safe_sub(amount_out_before)?;
+            rewards_out_b = rewards_out_b.safe_add(amount_out)?;
+            amount_out
+        };
+
+        emit!(SwapEvent {
+            vault_account: vault_key,
+            mint_in: market.rewards_mint,
+            amount_in: amount_to_swap,
+            mint_out: destination_mint,
+            amount_out,
+        });
+
+        swapped_rewards.push((
+            reward_index,
+            market.rewards_mint,
+            amount_available.safe_sub(amount_to_swap)?,
+        ));
+    }
+
+    // Only the fees and rewards harvested by this call pay the bounty
+    let keeper_accounts = keeper_input_token_accounts(remaining, &ctx.accounts.vault_account)?;
+    let (keeper_bounty_a, keeper_bounty_b) = pay_keeper_bounties(
+        &mut ctx.accounts.vault_account,
+        &fees_accounts.token_program,
+        &fees_accounts.vault_input_token_a_account,
+        &fees_accounts.vault_input_token_b_account,
+        keeper_accounts,
+        amount_after_fee(amount_a_increase, treasury_fee_a).safe_add(rewards_out_a)?,
+        amount_after_fee(amount_b_increase, treasury_fee_b).safe_add(rewards_out_b)?,
+    )?;
+
+    ctx.accounts.vault_input_token_a_account.reload()?;
+    ctx.accounts.vault_input_token_b_account.reload()?;
+
+    {
+        let seeds = generate_seeds!(ctx.accounts.vault_account);
+        let signer = &[&seeds[..]];
+
+        let max_swap_slippage_bps = ctx.accounts.vault_account.max_swap_slippage_bps;
+        let reinvest_accounts = ctx.accounts.reinvest_accounts();
+        reinvest_accounts.swap_to_position_ratio(max_swap_slippage_bps, signer)?;
+        reinvest_accounts.deposit_max_possible_liquidity(signer)?;
+    }
+
+    ctx.accounts.vault_input_token_a_account.reload()?;
+    ctx.accounts.vault_input_token_b_account.reload()?;
+
+    let liquidity_after = ctx.accounts.position.liquidity()?;
+    let liquidity_increase = liquidity_after.safe_sub(liquidity_before)?;
+    let (sqrt_price, _) = ctx.accounts.position.sqrt_price_and_tick()?;
+
+    let vault = &mut ctx.accounts.vault_account;
+    vault.last_liquidity_increase = liquidity_increase;
+    vault.earned_rewards_token_a = vault
+        .earned_rewards_token_a
+        .safe_add(amount_a_increase)?
+        .safe_add(rewards_out_a)?;
+    vault.earned_rewards_token_b = vault
+        .earned_rewards_token_b
+        .safe_add(amount_b_increase)?
+        .safe_add(rewards_out_b)?;
+
+    for (reward_index, rewards_mint, remaining_amount) in swapped_rewards {
+        let chunking = &mut vault.rewards_swap_chunking[reward_index];
+        chunking.record_swap(current_slot)?;
+
+        emit!(SwapRewardsEvent {
+            vault_account: vault_key,
+            rewards_mint,
+            swap_count: chunking.swap_count,
+            remaining_amount,
+            // Paid on all the harvested amounts, see HarvestAndCompoundEvent
+            keeper_bounty: 0,
+        });
+    }
+
+    emit!(HarvestAndCompoundEvent {
+        vault_account: vault_key,
+        total_fees_token_a: amount_a_increase,
+        total_fees_token_b: amount_b_increase,
+        treasury_fee_token_a: treasury_fee_a,
+        treasury_fee_token_b: treasury_fee_b,
+        rewards_swapped_token_a: rewards_out_a,
+        rewards_swapped_token_b: rewards_out_b,
+        keeper_bounty_token_a: keeper_bounty_a,
+        keeper_bounty_token_b: keeper_bounty_b,
+        lp_supply: ctx.accounts.vault_lp_token_mint_pubkey.supply,
+        liquidity: liquidity_after,
+        liquidity_increase,
+        elapsed_slots,
+        sqrt_price,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::state::{PriceBoundKind, RouteHop};
+
+    fn market(id: MarketRewards) -> MarketRewardsInfo {
+        MarketRewardsInfo {
+            id,
+            rewards_mint: Pubkey::new_unique(),
+            destination_token_account: Pubkey::new_unique(),
+            min_amount_out: 1,
+        }
+    }
+
+    #[test]
+    fn test_rewards_group_len() {
+        let route = SwapRoute::default();
+        let price_bound = RewardsPriceBound::default();
+
+        let unset = MarketRewardsInfo {
+            rewards_mint: Pubkey::default(),
+            ..market(MarketRewards::Whirlpool)
+        };
+        assert_eq!(
+            rewards_group_len(&unset, &route, &price_bound).unwrap(),
+            None
+        );
+        assert_eq!(
+            rewards_group_len(&market(MarketRewards::NotSet), &route, &price_bound).unwrap(),
+            None
+        );
+
+        // Transfer & distribute markets only collect
+        for id in [MarketRewards::Transfer, MarketRewards::Distribute] {
+            assert_eq!(
+                rewards_group_len(&market(id), &route, &price_bound).unwrap(),
+                Some(REWARDS_GROUP_BASE_LEN)
+            );
+        }
+
+        // base + swap_program + venue accounts
+        assert_eq!(
+            rewards_group_len(&market(MarketRewards::Whirlpool), &route, &price_bound).unwrap(),
+            Some(3 + 1 + 7)
+        );
+        assert_eq!(
+            rewards_group_len(&market(MarketRewards::OrcaV2), &route, &price_bound).unwrap(),
+            Some(3 + 1 + 6)
+        );
+    }
+
+    #[test]
+    fn test_rewards_group_len_route_and_price_bound() {
+        let whirlpool = market(MarketRewards::Whirlpool);
+        let mint_mid = Pubkey::new_unique();
+        let destination_mint = Pubkey::new_unique();
+
+        let route = SwapRoute::new(
+            &[
+                RouteHop {
+                    market: MarketRewards::OrcaV2,
+                    mint_out: mint_mid,
+                },
+                RouteHop {
+                    market: MarketRewards::Whirlpool,
+                    mint_out: destination_mint,
+                },
+            ],
+            whirlpool.rewards_mint,
+            destination_mint,
+            &[destination_mint],
+        )
+        .unwrap();
+
+        let price_bound = RewardsPriceBound {
+            kind: PriceBoundKind::ReferencePool,
+            reference_pool: Pubkey::new_unique(),
+            ..RewardsPriceBound::default()
+        };
+
+        // base + hops (swap_program, output account & venue accounts) + reference pool
+        assert_eq!(
+            rewards_group_len(&whirlpool, &route, &price_bound).unwrap(),
+            Some(3 + (2 + 6) + (2 + 7) + 1)
+        );
+    }
+}
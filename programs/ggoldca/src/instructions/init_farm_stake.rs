@@ -0,0 +1,45 @@
+use crate::state::{Farm, FarmStake};
+use crate::{FARM_SEED, FARM_STAKE_SEED};
+use anchor_lang::prelude::*;
+
+#[event]
+struct InitFarmStakeEvent {
+    farm: Pubkey,
+    user: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct InitFarmStake<'info> {
+    #[account(mut)]
+    pub user_signer: Signer<'info>,
+    #[account(
+        seeds = [FARM_SEED, farm.vault_account.as_ref(), &[farm.id][..]],
+        bump = farm.bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        init,
+        payer = user_signer,
+        space = 8 + FarmStake::SIZE,
+        seeds = [FARM_STAKE_SEED, farm.key().as_ref(), user_signer.key().as_ref()],
+        bump
+    )]
+    pub farm_stake: Account<'info, FarmStake>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitFarmStake>) -> Result<()> {
+    ctx.accounts.farm_stake.set_inner(FarmStake {
+        farm: ctx.accounts.farm.key(),
+        user: ctx.accounts.user_signer.key(),
+        bump: *ctx.bumps.get("farm_stake").unwrap(),
+        ..FarmStake::default()
+    });
+
+    emit!(InitFarmStakeEvent {
+        farm: ctx.accounts.farm.key(),
+        user: ctx.accounts.user_signer.key(),
+    });
+
+    Ok(())
+}
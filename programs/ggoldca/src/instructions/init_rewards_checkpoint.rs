@@ -0,0 +1,51 @@
+use crate::error::ErrorCode;
+use crate::state::{UserRewardsCheckpoint, VaultAccount};
+use crate::{REWARDS_CHECKPOINT_SEED, VAULT_ACCOUNT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+
+#[event]
+struct InitRewardsCheckpointEvent {
+    vault_account: Pubkey,
+    user: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct InitRewardsCheckpoint<'info> {
+    #[account(mut)]
+    pub user_signer: Signer<'info>,
+    #[account(
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+    #[account(
+        init,
+        payer = user_signer,
+        space = 8 + UserRewardsCheckpoint::SIZE,
+        seeds = [REWARDS_CHECKPOINT_SEED, vault_account.key().as_ref(), user_signer.key().as_ref()],
+        bump
+    )]
+    pub rewards_checkpoint: Account<'info, UserRewardsCheckpoint>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitRewardsCheckpoint>) -> Result<()> {
+    // Rewards accrue from the current rewards per share onwards, once LP tokens are locked
+    ctx.accounts
+        .rewards_checkpoint
+        .set_inner(UserRewardsCheckpoint {
+            vault_account: ctx.accounts.vault_account.key(),
+            user: ctx.accounts.user_signer.key(),
+            bump: *ctx.bumps.get("rewards_checkpoint").unwrap(),
+            rewards_per_share_x64: ctx.accounts.vault_account.rewards_per_share_x64,
+            ..UserRewardsCheckpoint::default()
+        });
+
+    emit!(InitRewardsCheckpointEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        user: ctx.accounts.user_signer.key(),
+    });
+
+    Ok(())
+}
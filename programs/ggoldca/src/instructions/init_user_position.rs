@@ -0,0 +1,47 @@
+use crate::error::ErrorCode;
+use crate::state::{UserPosition, VaultAccount};
+use crate::{USER_POSITION_SEED, VAULT_ACCOUNT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+
+#[event]
+struct InitUserPositionEvent {
+    vault_account: Pubkey,
+    user: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct InitUserPosition<'info> {
+    #[account(mut)]
+    pub user_signer: Signer<'info>,
+    #[account(
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+    #[account(
+        init,
+        payer = user_signer,
+        space = 8 + UserPosition::SIZE,
+        seeds = [USER_POSITION_SEED, vault_account.key().as_ref(), user_signer.key().as_ref()],
+        bump
+    )]
+    pub user_position: Account<'info, UserPosition>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitUserPosition>) -> Result<()> {
+    ctx.accounts.user_position.set_inner(UserPosition {
+        vault_account: ctx.accounts.vault_account.key(),
+        user: ctx.accounts.user_signer.key(),
+        bump: *ctx.bumps.get("user_position").unwrap(),
+        ..UserPosition::default()
+    });
+
+    emit!(InitUserPositionEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        user: ctx.accounts.user_signer.key(),
+    });
+
+    Ok(())
+}
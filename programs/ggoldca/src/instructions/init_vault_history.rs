@@ -0,0 +1,49 @@
+use crate::error::ErrorCode;
+use crate::state::{VaultAccount, VaultHistory};
+use crate::{VAULT_ACCOUNT_SEED, VAULT_HISTORY_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+
+#[event]
+struct InitVaultHistoryEvent {
+    vault_account: Pubkey,
+    vault_history: Pubkey,
+    min_slots_between_snapshots: u64,
+}
+
+#[derive(Accounts)]
+pub struct InitVaultHistory<'info> {
+    #[account(mut)]
+    pub user_signer: Signer<'info>,
+    #[account(
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+    #[account(
+        init,
+        payer = user_signer,
+        space = 8 + VaultHistory::SIZE,
+        seeds = [VAULT_HISTORY_SEED, vault_account.key().as_ref()],
+        bump
+    )]
+    pub vault_history: Box<Account<'info, VaultHistory>>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<InitVaultHistory>, min_slots_between_snapshots: u64) -> Result<()> {
+    ctx.accounts.vault_history.set_inner(VaultHistory {
+        vault_account: ctx.accounts.vault_account.key(),
+        bump: *ctx.bumps.get("vault_history").unwrap(),
+        min_slots_between_snapshots,
+        ..VaultHistory::default()
+    });
+
+    emit!(InitVaultHistoryEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        vault_history: ctx.accounts.vault_history.key(),
+        min_slots_between_snapshots,
+    });
+
+    Ok(())
+}
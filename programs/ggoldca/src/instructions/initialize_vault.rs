@@ -5,6 +5,19 @@ use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 
+#[event]
+struct InitializeVaultEvent {
+    vault_account: Pubkey,
+    id: u8,
+    whirlpool_id: Pubkey,
+    input_token_a_mint_pubkey: Pubkey,
+    input_token_b_mint_pubkey: Pubkey,
+    lp_token_mint: Pubkey,
+    fee: u64,
+    min_slots_for_reinvest: u64,
+    sqrt_price: u128,
+}
+
 #[derive(Accounts)]
 #[instruction(id: u8)]
 pub struct InitializeVault<'info> {
@@ -61,14 +74,14 @@ pub fn handler(
     min_slots_for_reinvest: u64,
 ) -> Result<()> {
     // Ensure the whirlpool has the right account data
-    let (token_mint_a, token_mint_b) = {
+    let (token_mint_a, token_mint_b, sqrt_price) = {
         use anchor_lang_for_whirlpool::AccountDeserialize;
         use std::borrow::Borrow;
 
         let acc_data_slice: &[u8] = &ctx.accounts.whirlpool.try_borrow_data()?;
         let pool =
             whirlpool::state::whirlpool::Whirlpool::try_deserialize(&mut acc_data_slice.borrow())?;
-        (pool.token_mint_a, pool.token_mint_b)
+        (pool.token_mint_a, pool.token_mint_b, pool.sqrt_price)
     };
 
     require!(
@@ -98,5 +111,17 @@ pub fn handler(
             min_slots_for_reinvest,
         }));
 
+    emit!(InitializeVaultEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        id,
+        whirlpool_id: ctx.accounts.whirlpool.key(),
+        input_token_a_mint_pubkey: token_mint_a,
+        input_token_b_mint_pubkey: token_mint_b,
+        lp_token_mint: ctx.accounts.vault_lp_token_mint_pubkey.key(),
+        fee,
+        min_slots_for_reinvest,
+        sqrt_price,
+    });
+
     Ok(())
 }
@@ -0,0 +1,105 @@
+use crate::error::ErrorCode;
+use crate::math::safe_arithmetics::SafeArithmetics;
+use crate::state::{UserRewardsCheckpoint, VaultAccount};
+use crate::{REWARDS_CHECKPOINT_SEED, VAULT_ACCOUNT_SEED, VAULT_LP_TOKEN_MINT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
+
+#[event]
+pub struct LockUnlockLpEvent {
+    pub vault_account: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub rewards_locked_lp: u64,
+}
+
+#[derive(Accounts)]
+pub struct LockUnlockLp<'info> {
+    pub user_signer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+    #[account(
+        seeds = [VAULT_LP_TOKEN_MINT_SEED, vault_account.key().as_ref()],
+        bump = vault_account.bumps.lp_token_mint
+    )]
+    pub vault_lp_token_mint_pubkey: Account<'info, Mint>,
+    #[account(
+        mut,
+        seeds = [REWARDS_CHECKPOINT_SEED, vault_account.key().as_ref(), user_signer.key().as_ref()],
+        bump = rewards_checkpoint.bump
+    )]
+    pub rewards_checkpoint: Account<'info, UserRewardsCheckpoint>,
+    #[account(
+        mut,
+        associated_token::mint = vault_lp_token_mint_pubkey,
+        associated_token::authority = vault_account,
+    )]
+    pub vault_lp_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = vault_lp_token_mint_pubkey,
+        token::authority = user_signer.key(),
+    )]
+    pub user_lp_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> LockUnlockLp<'info> {
+    /// Accrue the distributed rewards before the locked amount changes
+    pub fn update_rewards(&mut self) -> Result<()> {
+        self.vault_account
+            .checkpoint_rewards(&mut self.rewards_checkpoint)
+    }
+
+    fn transfer_from_user_to_vault_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.user_lp_token_account.to_account_info(),
+                to: self.vault_lp_token_account.to_account_info(),
+                authority: self.user_signer.to_account_info(),
+            },
+        )
+    }
+
+    pub fn transfer_from_vault_to_user_ctx(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.vault_lp_token_account.to_account_info(),
+                to: self.user_lp_token_account.to_account_info(),
+                authority: self.vault_account.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn handler(ctx: Context<LockUnlockLp>, lp_amount: u64) -> Result<()> {
+    require!(lp_amount > 0, ErrorCode::ZeroLpAmount);
+
+    ctx.accounts.update_rewards()?;
+
+    token::transfer(ctx.accounts.transfer_from_user_to_vault_ctx(), lp_amount)?;
+
+    let checkpoint = &mut ctx.accounts.rewards_checkpoint;
+    checkpoint.lp_balance = checkpoint.lp_balance.safe_add(lp_amount)?;
+
+    let vault = &mut ctx.accounts.vault_account;
+    vault.rewards_locked_lp = vault.rewards_locked_lp.safe_add(lp_amount)?;
+
+    emit!(LockUnlockLpEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        user: ctx.accounts.user_signer.key(),
+        amount: lp_amount,
+        rewards_locked_lp: ctx.accounts.vault_account.rewards_locked_lp,
+    });
+
+    Ok(())
+}
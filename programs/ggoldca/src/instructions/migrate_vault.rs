@@ -0,0 +1,313 @@
+use crate::error::ErrorCode;
+use crate::state::{
+    Bumps, MarketRewardsInfo, PositionInfo, VaultAccount, VaultAccountParams, MAX_POSITIONS,
+    WHIRLPOOL_NUM_REWARDS,
+};
+use crate::VAULT_VERSION;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_lang::Discriminator;
+
+#[event]
+struct MigrateVaultEvent {
+    vault_account: Pubkey,
+    from_version: u8,
+    to_version: u8,
+}
+
+#[derive(Accounts)]
+pub struct MigrateVault<'info> {
+    #[account(mut)]
+    pub user_signer: Signer<'info>,
+    #[account(mut, owner = crate::ID)]
+    /// CHECK: discriminator and versioned layout are checked in the handler
+    pub vault_account: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateVault<'info> {
+    fn transfer_from_user_to_vault_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.system_program.to_account_info(),
+            Transfer {
+                from: self.user_signer.to_account_info(),
+                to: self.vault_account.to_account_info(),
+            },
+        )
+    }
+}
+
+/// Account size of each vault version, discriminator excluded
+pub const VAULT_SIZES: [(u8, usize); 2] = [
+    (1, VaultAccountV1::SIZE),
+    (VAULT_VERSION, VaultAccount::SIZE),
+];
+
+/// Layout of the vault account at version 1
+#[derive(AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct VaultAccountV1 {
+    pub version: u8,
+    pub is_active_from_ui: bool,
+    pub is_paused: bool,
+    pub id: u8,
+    pub bumps: Bumps,
+    pub whirlpool_id: Pubkey,
+    pub input_token_a_mint_pubkey: Pubkey,
+    pub input_token_b_mint_pubkey: Pubkey,
+    pub fee: u64,
+    pub min_slots_for_reinvest: u64,
+    pub last_reinvestment_slot: u64,
+    pub last_liquidity_increase: u128,
+    pub earned_rewards_token_a: u64,
+    pub earned_rewards_token_b: u64,
+    pub market_rewards: [MarketRewardsInfo; WHIRLPOOL_NUM_REWARDS],
+    pub positions: Vec<PositionInfo>,
+    pub _padding: [u64; 10],
+}
+
+impl VaultAccountV1 {
+    pub const SIZE: usize = 1
+        + 1
+        + 1
+        + 1
+        + Bumps::SIZE
+        + 32
+        + 32
+        + 32
+        + 8
+        + 8
+        + 8
+        + 16
+        + 8
+        + 8
+        + WHIRLPOOL_NUM_REWARDS * MarketRewardsInfo::SIZE
+        + 4
+        + MAX_POSITIONS * PositionInfo::SIZE
+        + 8 * 10;
+}
+
+impl From<VaultAccountV1> for VaultAccount {
+    fn from(vault: VaultAccountV1) -> Self {
+        // Fields added after version 1 take the defaults of new vaults
+        Self {
+            is_active_from_ui: vault.is_active_from_ui,
+            is_paused: vault.is_paused,
+            last_reinvestment_slot: vault.last_reinvestment_slot,
+            last_liquidity_increase: vault.last_liquidity_increase,
+            earned_rewards_token_a: vault.earned_rewards_token_a,
+            earned_rewards_token_b: vault.earned_rewards_token_b,
+            market_rewards: vault.market_rewards,
+            positions: vault.positions,
+            ..VaultAccount::new(VaultAccountParams {
+                id: vault.id,
+                bumps: vault.bumps,
+                whirlpool_id: vault.whirlpool_id,
+                input_token_a_mint_pubkey: vault.input_token_a_mint_pubkey,
+                input_token_b_mint_pubkey: vault.input_token_b_mint_pubkey,
+                fee: vault.fee,
+                min_slots_for_reinvest: vault.min_slots_for_reinvest,
+            })
+        }
+    }
+}
+
+/// Map the account data of a previous vault version into the current layout
+pub fn migrate_vault_data(data: &[u8]) -> Result<VaultAccount> {
+    require!(
+        data.len() > 8 && data[..8] == VaultAccount::discriminator(),
+        anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+    );
+
+    let mut vault_data: &[u8] = &data[8..];
+    match vault_data[0] {
+        1 => Ok(VaultAccountV1::deserialize(&mut vault_data)?.into()),
+        VAULT_VERSION => Err(ErrorCode::VaultAlreadyMigrated.into()),
+        _ => Err(ErrorCode::InvalidVaultVersion.into()),
+    }
+}
+
+pub fn handler(ctx: Context<MigrateVault>) -> Result<()> {
+    let vault_account = &ctx.accounts.vault_account;
+
+    let (from_version, vault) = {
+        let data = vault_account.try_borrow_data()?;
+        (
+            data.get(8).copied().unwrap_or_default(),
+            migrate_vault_data(&data)?,
+        )
+    };
+
+    let new_len = 8 + VaultAccount::SIZE;
+    if vault_account.data_len() < new_len {
+        let min_balance = Rent::get()?.minimum_balance(new_len);
+        let lamports = min_balance.saturating_sub(vault_account.lamports());
+        if lamports > 0 {
+            system_program::transfer(ctx.accounts.transfer_from_user_to_vault_ctx(), lamports)?;
+        }
+
+        vault_account.realloc(new_len, true)?;
+    }
+
+    let mut data = vault_account.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    vault.try_serialize(&mut writer)?;
+
+    emit!(MigrateVaultEvent {
+        vault_account: vault_account.key(),
+        from_version,
+        to_version: VAULT_VERSION,
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::state::MarketRewards;
+
+    /// Version 1 vault with two positions and a transfer market
+    fn vault_v1_fixture() -> Vec<u8> {
+        let mut data = VaultAccount::discriminator().to_vec();
+
+        data.extend_from_slice(&[1, 1, 0, 7]); // version, is_active_from_ui, is_paused, id
+        data.extend_from_slice(&[254, 253]); // bumps
+        data.extend_from_slice(&[1; 32]); // whirlpool_id
+        data.extend_from_slice(&[2; 32]); // input_token_a_mint_pubkey
+        data.extend_from_slice(&[3; 32]); // input_token_b_mint_pubkey
+        data.extend_from_slice(&10_u64.to_le_bytes()); // fee
+        data.extend_from_slice(&100_u64.to_le_bytes()); // min_slots_for_reinvest
+        data.extend_from_slice(&12_345_u64.to_le_bytes()); // last_reinvestment_slot
+        data.extend_from_slice(&999_u128.to_le_bytes()); // last_liquidity_increase
+        data.extend_from_slice(&500_u64.to_le_bytes()); // earned_rewards_token_a
+        data.extend_from_slice(&600_u64.to_le_bytes()); // earned_rewards_token_b
+
+        // market_rewards
+        data.push(1); // Transfer
+        data.extend_from_slice(&[4; 32]);
+        data.extend_from_slice(&[5; 32]);
+        data.extend_from_slice(&0_u64.to_le_bytes());
+        for _ in 1..WHIRLPOOL_NUM_REWARDS {
+            data.push(0); // NotSet
+            data.extend_from_slice(&[0; 32 + 32 + 8]);
+        }
+
+        // positions
+        data.extend_from_slice(&2_u32.to_le_bytes());
+        for (key, lower_tick, upper_tick) in [(6, -128_i32, 128_i32), (7, -256, 256)] {
+            data.extend_from_slice(&[key; 32]);
+            data.extend_from_slice(&lower_tick.to_le_bytes());
+            data.extend_from_slice(&upper_tick.to_le_bytes());
+        }
+
+        // _padding
+        data.extend_from_slice(&[0; 8 * 10]);
+
+        // Account allocated for MAX_POSITIONS
+        data.resize(data.len() + PositionInfo::SIZE, 0);
+        data
+    }
+
+    #[test]
+    fn test_migrate_vault_v1() {
+        let vault = migrate_vault_data(&vault_v1_fixture()).unwrap();
+
+        assert_eq!(vault.version, VAULT_VERSION);
+        assert!(vault.is_active_from_ui);
+        assert!(!vault.is_paused);
+        assert_eq!(vault.id, 7);
+        assert_eq!((vault.bumps.vault, vault.bumps.lp_token_mint), (254, 253));
+        assert_eq!(vault.whirlpool_id, Pubkey::new_from_array([1; 32]));
+        assert_eq!(
+            vault.input_token_a_mint_pubkey,
+            Pubkey::new_from_array([2; 32])
+        );
+        assert_eq!(
+            vault.input_token_b_mint_pubkey,
+            Pubkey::new_from_array([3; 32])
+        );
+        assert_eq!(vault.fee, 10);
+        assert_eq!(vault.min_slots_for_reinvest, 100);
+        assert_eq!(vault.last_reinvestment_slot, 12_345);
+        assert_eq!(vault.last_liquidity_increase, 999);
+        assert_eq!(vault.earned_rewards_token_a, 500);
+        assert_eq!(vault.earned_rewards_token_b, 600);
+
+        assert_eq!(vault.market_rewards[0].id, MarketRewards::Transfer);
+        assert_eq!(
+            vault.market_rewards[0].rewards_mint,
+            Pubkey::new_from_array([4; 32])
+        );
+        assert_eq!(vault.market_rewards[1].id, MarketRewards::NotSet);
+
+        assert_eq!(vault.positions.len(), 2);
+        assert_eq!(vault.active_position_key(), Pubkey::new_from_array([6; 32]));
+        assert_eq!(vault.positions[1].lower_tick, -256);
+        assert_eq!(vault.positions[1].upper_tick, 256);
+
+        // New fields take the defaults of new vaults
+        let defaults = VaultAccount::new(VaultAccountParams {
+            id: 0,
+            bumps: Bumps::default(),
+            whirlpool_id: Pubkey::default(),
+            input_token_a_mint_pubkey: Pubkey::default(),
+            input_token_b_mint_pubkey: Pubkey::default(),
+            fee: 0,
+            min_slots_for_reinvest: 0,
+        });
+        assert_eq!(vault.max_swap_slippage_bps, defaults.max_swap_slippage_bps);
+        assert_eq!(
+            vault.max_price_deviation_bps,
+            defaults.max_price_deviation_bps
+        );
+        assert_eq!(vault.min_fees_token_a, defaults.min_fees_token_a);
+        assert_eq!(vault.price_history.len, 0);
+    }
+
+    #[test]
+    fn test_migrated_vault_fits_current_size() {
+        let vault = migrate_vault_data(&vault_v1_fixture()).unwrap();
+
+        let mut data = vec![0; 8 + VaultAccount::SIZE];
+        let mut writer: &mut [u8] = &mut data;
+        vault.try_serialize(&mut writer).unwrap();
+
+        let vault = VaultAccount::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(vault.version, VAULT_VERSION);
+        assert_eq!(vault.positions.len(), 2);
+
+        // Already migrated vaults are rejected
+        assert!(migrate_vault_data(&data).is_err());
+    }
+
+    #[test]
+    fn test_vault_v1_size() {
+        assert_eq!(vault_v1_fixture().len(), 8 + VaultAccountV1::SIZE);
+        assert_eq!(VAULT_SIZES[0], (1, 584));
+    }
+
+    #[test]
+    fn test_vault_max_size_fits() {
+        let mut vault = migrate_vault_data(&vault_v1_fixture()).unwrap();
+        vault.positions.push(PositionInfo::default());
+        for route in vault.rewards_routes.iter_mut() {
+            route.len = crate::state::MAX_ROUTE_HOPS as u8;
+        }
+
+        let mut data = vec![0; 8 + VaultAccount::SIZE];
+        let mut writer: &mut [u8] = &mut data;
+        vault.try_serialize(&mut writer).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_vault_invalid_data() {
+        let mut data = vault_v1_fixture();
+
+        data[8] = VAULT_VERSION + 1;
+        assert!(migrate_vault_data(&data).is_err());
+
+        data[8] = 1;
+        data[0] ^= 1;
+        assert!(migrate_vault_data(&data).is_err());
+    }
+}
@@ -0,0 +1,21 @@
+use crate::instructions::open_position::{self, OpenPosition};
+use crate::math::tick_range::centered_tick_range;
+use crate::math::volatility::realized_volatility;
+use anchor_lang::prelude::*;
+
+pub fn handler(ctx: Context<OpenPosition>, bump: u8) -> Result<()> {
+    let pool = ctx.accounts.whirlpool_state()?;
+
+    let vault = &ctx.accounts.vault_account;
+    let volatility = realized_volatility(&vault.price_history.sorted_observations())?;
+
+    let (tick_lower_index, tick_upper_index) = centered_tick_range(
+        pool.sqrt_price,
+        pool.tick_current_index,
+        pool.tick_spacing,
+        &vault.range_config,
+        volatility,
+    )?;
+
+    open_position::handler(ctx, bump, tick_lower_index, tick_upper_index)
+}
@@ -1,11 +1,25 @@
 use crate::error::ErrorCode;
+use crate::math::tick_range::validate_tick_range;
 use crate::state::{PositionInfo, VaultAccount, MAX_POSITIONS};
 use crate::{VAULT_ACCOUNT_SEED, VAULT_VERSION};
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::pubkey::Pubkey;
-use anchor_lang_for_whirlpool::context::CpiContext as CpiContextForWhirlpool;
+use anchor_lang_for_whirlpool::{
+    context::CpiContext as CpiContextForWhirlpool, AccountDeserialize,
+};
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token::Token;
+use std::borrow::Borrow;
+use whirlpool::state::whirlpool::Whirlpool;
+
+#[event]
+struct OpenPositionEvent {
+    vault_account: Pubkey,
+    position: Pubkey,
+    lower_tick: i32,
+    upper_tick: i32,
+    sqrt_price: u128,
+}
 
 #[derive(Accounts)]
 pub struct OpenPosition<'info> {
@@ -31,6 +45,7 @@ pub struct OpenPosition<'info> {
     #[account(mut)]
     /// CHECK: whirlpool cpi
     pub position_token_account: AccountInfo<'info>,
+    #[account(constraint = whirlpool.key() == vault_account.whirlpool_id.key())]
     /// CHECK: whirlpool cpi
     pub whirlpool: AccountInfo<'info>,
 
@@ -41,6 +56,11 @@ pub struct OpenPosition<'info> {
 }
 
 impl<'info> OpenPosition<'info> {
+    pub fn whirlpool_state(&self) -> Result<Whirlpool> {
+        let acc_data_slice: &[u8] = &self.whirlpool.try_borrow_data()?;
+        Ok(Whirlpool::try_deserialize(&mut acc_data_slice.borrow())?)
+    }
+
     fn open_position_ctx(
         &self,
     ) -> CpiContextForWhirlpool<'_, '_, '_, 'info, whirlpool::cpi::accounts::OpenPosition<'info>>
@@ -69,6 +89,9 @@ pub fn handler(
     tick_lower_index: i32,
     tick_upper_index: i32,
 ) -> Result<()> {
+    let pool = ctx.accounts.whirlpool_state()?;
+    validate_tick_range(tick_lower_index, tick_upper_index, pool.tick_spacing)?;
+
     whirlpool::cpi::open_position(
         ctx.accounts.open_position_ctx(),
         whirlpool::state::position::OpenPositionBumps {
@@ -96,5 +119,13 @@ pub fn handler(
         upper_tick: tick_upper_index,
     });
 
+    emit!(OpenPositionEvent {
+        vault_account: vault.key(),
+        position: ctx.accounts.position.key(),
+        lower_tick: tick_lower_index,
+        upper_tick: tick_upper_index,
+        sqrt_price: pool.sqrt_price,
+    });
+
     Ok(())
 }
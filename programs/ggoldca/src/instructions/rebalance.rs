@@ -1,7 +1,10 @@
 use crate::error::ErrorCode;
+use crate::instructions::swap_rewards::SwapEvent;
 use crate::interfaces::whirlpool_position::*;
 use crate::macros::generate_seeds;
-use crate::math::safe_arithmetics::SafeMulDiv;
+use crate::math::price::{is_price_within_deviation, sqrt_price_limit};
+use crate::math::quote::swap_params_for_ratio;
+use crate::math::safe_arithmetics::{SafeArithmetics, SafeMulDiv};
 use crate::state::VaultAccount;
 use crate::{VAULT_ACCOUNT_SEED, VAULT_VERSION};
 use anchor_lang::prelude::*;
@@ -12,8 +15,16 @@ use anchor_spl::token::{Token, TokenAccount};
 #[event]
 struct RebalanceEvent {
     vault_account: Pubkey,
+    old_position: Pubkey,
+    old_lower_tick: i32,
+    old_upper_tick: i32,
     old_liquidity: u128,
+    new_position: Pubkey,
+    new_lower_tick: i32,
+    new_upper_tick: i32,
     new_liquidity: u128,
+    /// Pool sqrt price after the rebalance
+    sqrt_price: u128,
 }
 
 #[derive(Accounts)]
@@ -66,6 +77,28 @@ pub struct Rebalance<'info> {
 }
 
 impl<'info> Rebalance<'info> {
+    fn swap_ctx(
+        &self,
+        remaining: &[AccountInfo<'info>],
+    ) -> CpiContextForWhirlpool<'_, '_, '_, 'info, whirlpool::cpi::accounts::Swap<'info>> {
+        CpiContextForWhirlpool::new(
+            self.whirlpool_program_id.to_account_info(),
+            whirlpool::cpi::accounts::Swap {
+                token_program: self.token_program.to_account_info(),
+                token_authority: self.vault_account.to_account_info(),
+                whirlpool: self.current_position.whirlpool.to_account_info(),
+                token_owner_account_a: self.vault_input_token_a_account.to_account_info(),
+                token_vault_a: self.token_vault_a.to_account_info(),
+                token_owner_account_b: self.vault_input_token_b_account.to_account_info(),
+                token_vault_b: self.token_vault_b.to_account_info(),
+                tick_array_0: remaining[0].to_account_info(),
+                tick_array_1: remaining[1].to_account_info(),
+                tick_array_2: remaining[2].to_account_info(),
+                oracle: remaining[3].to_account_info(),
+            },
+        )
+    }
+
     fn modify_liquidity_ctx(
         &self,
         position: &PositionAccounts<'info>,
@@ -90,7 +123,39 @@ impl<'info> Rebalance<'info> {
     }
 }
 
-pub fn handler(ctx: Context<Rebalance>) -> Result<()> {
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, Rebalance<'info>>,
+    min_amount_a: u64,
+    min_amount_b: u64,
+    reference_sqrt_price: u128,
+    max_price_deviation_bps: u16,
+    swap_to_ratio: bool,
+) -> Result<()> {
+    require!(reference_sqrt_price > 0, ErrorCode::InvalidReferencePrice);
+
+    let (curr_sqrt_price, curr_tick) = ctx.accounts.current_position.sqrt_price_and_tick()?;
+
+    require!(
+        is_price_within_deviation(
+            curr_sqrt_price,
+            reference_sqrt_price,
+            max_price_deviation_bps
+        )?,
+        ErrorCode::PriceDeviationExceeded
+    );
+
+    // The admin reference price stands in for a stale TWAP
+    let current_slot = Clock::get()?.slot;
+    if ctx.accounts.vault_account.twap_tick(current_slot).is_some() {
+        ctx.accounts
+            .vault_account
+            .check_price_deviation(curr_sqrt_price, current_slot)?;
+    }
+    ctx.accounts
+        .vault_account
+        .price_history
+        .record(current_slot, curr_tick);
+
     let seeds = generate_seeds!(ctx.accounts.vault_account);
     let signer = &[&seeds[..]];
 
@@ -101,13 +166,94 @@ pub fn handler(ctx: Context<Rebalance>) -> Result<()> {
             .modify_liquidity_ctx(&ctx.accounts.current_position)
             .with_signer(signer),
         init_liquidity,
-        0,
-        0,
+        min_amount_a,
+        min_amount_b,
     )?;
 
     ctx.accounts.vault_input_token_a_account.reload()?;
     ctx.accounts.vault_input_token_b_account.reload()?;
 
+    // Swap the excess tokens in order to match the ratio of the new position
+    if swap_to_ratio && init_liquidity > 0 {
+        require!(
+            ctx.remaining_accounts.len() == 4,
+            ErrorCode::InvalidNumberOfAccounts
+        );
+
+        let amount_a = ctx.accounts.vault_input_token_a_account.amount;
+        let amount_b = ctx.accounts.vault_input_token_b_account.amount;
+
+        let (position_amount_a, position_amount_b) = ctx
+            .accounts
+            .new_position
+            .token_amounts_from_liquidity(init_liquidity)?;
+
+        let (curr_sqrt_price, _) = ctx.accounts.current_position.sqrt_price_and_tick()?;
+
+        let mut swap_params = swap_params_for_ratio(
+            amount_a,
+            amount_b,
+            position_amount_a,
+            position_amount_b,
+            curr_sqrt_price,
+        )?
+        .with_max_slippage(
+            curr_sqrt_price,
+            ctx.accounts.vault_account.max_swap_slippage_bps,
+        )?;
+
+        // The swap cannot move the price further than the allowed deviation either
+        let reference_limit = sqrt_price_limit(
+            reference_sqrt_price,
+            max_price_deviation_bps,
+            swap_params.a_to_b,
+        )?;
+        swap_params.sqrt_price_limit = if swap_params.a_to_b {
+            swap_params.sqrt_price_limit.max(reference_limit)
+        } else {
+            swap_params.sqrt_price_limit.min(reference_limit)
+        };
+
+        if swap_params.amount > 0 {
+            whirlpool::cpi::swap(
+                ctx.accounts
+                    .swap_ctx(ctx.remaining_accounts)
+                    .with_signer(signer),
+                swap_params.amount,
+                swap_params.other_amount_threshold,
+                swap_params.sqrt_price_limit,
+                swap_params.amount_specified_is_input,
+                swap_params.a_to_b,
+            )?;
+
+            ctx.accounts.vault_input_token_a_account.reload()?;
+            ctx.accounts.vault_input_token_b_account.reload()?;
+
+            let amount_a_after = ctx.accounts.vault_input_token_a_account.amount;
+            let amount_b_after = ctx.accounts.vault_input_token_b_account.amount;
+
+            let event = if swap_params.a_to_b {
+                SwapEvent {
+                    vault_account: ctx.accounts.vault_account.key(),
+                    mint_in: ctx.accounts.vault_input_token_a_account.mint,
+                    amount_in: amount_a.safe_sub(amount_a_after)?,
+                    mint_out: ctx.accounts.vault_input_token_b_account.mint,
+                    amount_out: amount_b_after.safe_sub(amount_b)?,
+                }
+            } else {
+                SwapEvent {
+                    vault_account: ctx.accounts.vault_account.key(),
+                    mint_in: ctx.accounts.vault_input_token_b_account.mint,
+                    amount_in: amount_b.safe_sub(amount_b_after)?,
+                    mint_out: ctx.accounts.vault_input_token_a_account.mint,
+                    amount_out: amount_a_after.safe_sub(amount_a)?,
+                }
+            };
+
+            emit!(event);
+        }
+    }
+
     let amount_a = ctx.accounts.vault_input_token_a_account.amount;
     let amount_b = ctx.accounts.vault_input_token_b_account.amount;
 
@@ -125,7 +271,10 @@ pub fn handler(ctx: Context<Rebalance>) -> Result<()> {
         amount_b,
     )?;
 
+    let (sqrt_price, _) = ctx.accounts.current_position.sqrt_price_and_tick()?;
+
     let vault = &mut ctx.accounts.vault_account;
+    let old_position = vault.positions[0];
 
     let proportional_liquidity_increase = vault
         .last_liquidity_increase
@@ -133,11 +282,19 @@ pub fn handler(ctx: Context<Rebalance>) -> Result<()> {
 
     vault.last_liquidity_increase = proportional_liquidity_increase;
     vault.update_active_position(ctx.accounts.new_position.position.key());
+    let new_position = vault.positions[0];
 
     emit!(RebalanceEvent {
-        vault_account: ctx.accounts.vault_account.key(),
+        vault_account: vault.key(),
+        old_position: old_position.pubkey,
+        old_lower_tick: old_position.lower_tick,
+        old_upper_tick: old_position.upper_tick,
         old_liquidity: init_liquidity,
+        new_position: new_position.pubkey,
+        new_lower_tick: new_position.lower_tick,
+        new_upper_tick: new_position.upper_tick,
         new_liquidity,
+        sqrt_price,
     });
 
     Ok(())
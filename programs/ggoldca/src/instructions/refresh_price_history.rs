@@ -0,0 +1,65 @@
+use crate::error::ErrorCode;
+use crate::math::price::is_price_within_deviation;
+use crate::state::VaultAccount;
+use crate::{VAULT_ACCOUNT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+use anchor_lang_for_whirlpool::AccountDeserialize;
+use std::borrow::Borrow;
+
+#[event]
+struct RefreshPriceHistoryEvent {
+    vault_account: Pubkey,
+    sqrt_price: u128,
+    tick: i32,
+}
+
+#[derive(Accounts)]
+pub struct RefreshPriceHistory<'info> {
+    #[account()]
+    pub user_signer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+    #[account(constraint = whirlpool.key() == vault_account.whirlpool_id.key())]
+    /// CHECK: address is checked
+    pub whirlpool: AccountInfo<'info>,
+}
+
+/// Record the pool price when it is within max_price_deviation_bps of the reference price,
+/// restarting a stale price history
+pub fn handler(
+    ctx: Context<RefreshPriceHistory>,
+    reference_sqrt_price: u128,
+    max_price_deviation_bps: u16,
+) -> Result<()> {
+    require!(reference_sqrt_price > 0, ErrorCode::InvalidReferencePrice);
+
+    let (sqrt_price, tick) = {
+        let acc_data_slice: &[u8] = &ctx.accounts.whirlpool.try_borrow_data()?;
+        let pool =
+            whirlpool::state::whirlpool::Whirlpool::try_deserialize(&mut acc_data_slice.borrow())?;
+        (pool.sqrt_price, pool.tick_current_index)
+    };
+
+    require!(
+        is_price_within_deviation(sqrt_price, reference_sqrt_price, max_price_deviation_bps)?,
+        ErrorCode::PriceDeviationExceeded
+    );
+
+    ctx.accounts
+        .vault_account
+        .price_history
+        .record(Clock::get()?.slot, tick);
+
+    emit!(RefreshPriceHistoryEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        sqrt_price,
+        tick,
+    });
+
+    Ok(())
+}
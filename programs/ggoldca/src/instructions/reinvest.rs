@@ -1,19 +1,16 @@
 use crate::error::ErrorCode;
+use crate::instructions::collect_fees::{keeper_input_token_accounts, pay_keeper_bounties};
 use crate::instructions::swap_rewards::SwapEvent;
 use crate::interfaces::whirlpool_position::*;
 use crate::macros::generate_seeds;
-use crate::math::safe_arithmetics::{SafeArithmetics, SafeMulDiv};
-use crate::state::VaultAccount;
+use crate::math::quote::reinvest_quote;
+use crate::math::safe_arithmetics::SafeArithmetics;
+use crate::state::{HistorySnapshot, VaultAccount, VaultHistory};
 use crate::{VAULT_ACCOUNT_SEED, VAULT_LP_TOKEN_MINT_SEED, VAULT_VERSION};
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::pubkey::Pubkey;
 use anchor_lang_for_whirlpool::context::CpiContext as CpiContextForWhirlpool;
-use anchor_spl::token::{Mint, Token, TokenAccount};
-use whirlpool::math::{
-    bit_math,
-    tick_math::{MAX_SQRT_PRICE_X64, MIN_SQRT_PRICE_X64},
-    U256,
-};
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
 
 #[event]
 struct ReinvestEvent {
@@ -22,6 +19,10 @@ struct ReinvestEvent {
     liquidity: u128,
     liquidity_increase: u128,
     elapsed_slots: u64,
+    keeper_bounty_token_a: u64,
+    keeper_bounty_token_b: u64,
+    /// Pool sqrt price after the reinvestment
+    sqrt_price: u128,
 }
 
 #[derive(Accounts)]
@@ -87,6 +88,85 @@ pub struct Reinvest<'info> {
 }
 
 impl<'info> Reinvest<'info> {
+    fn reinvest_accounts(&self) -> ReinvestAccounts<'_, 'info> {
+        ReinvestAccounts {
+            whirlpool_program_id: &self.whirlpool_program_id,
+            token_program: self.token_program.to_account_info(),
+            vault_account: self.vault_account.to_account_info(),
+            vault_input_token_a_account: self.vault_input_token_a_account.to_account_info(),
+            vault_input_token_b_account: self.vault_input_token_b_account.to_account_info(),
+            input_token_a_mint: self.vault_account.input_token_a_mint_pubkey,
+            input_token_b_mint: self.vault_account.input_token_b_mint_pubkey,
+            token_vault_a: &self.token_vault_a,
+            token_vault_b: &self.token_vault_b,
+            position: &self.position,
+            tick_arrays: [&self.tick_array_0, &self.tick_array_1, &self.tick_array_2],
+            oracle: &self.oracle,
+        }
+    }
+
+    /// Append a snapshot to the vault history, when provided
+    fn record_history(&mut self, vault_history_info: Option<&AccountInfo<'info>>) -> Result<()> {
+        let vault_history_info = match vault_history_info {
+            Some(vault_history_info) => vault_history_info,
+            None => return Ok(()),
+        };
+
+        let mut vault_history = Account::<VaultHistory>::try_from(vault_history_info)?;
+        require!(
+            vault_history.vault_account == self.vault_account.key(),
+            ErrorCode::InvalidVaultHistory
+        );
+
+        let lp_supply = self.vault_lp_token_mint_pubkey.supply;
+        if lp_supply == 0 {
+            return Ok(());
+        }
+
+        self.vault_input_token_a_account.reload()?;
+        self.vault_input_token_b_account.reload()?;
+
+        let liquidity = self.position.liquidity()?;
+        let (position_amount_a, position_amount_b) =
+            self.position.token_amounts_from_liquidity(liquidity)?;
+
+        let (sqrt_price, _) = self.position.sqrt_price_and_tick()?;
+
+        let snapshot = HistorySnapshot::new(
+            &Clock::get()?,
+            sqrt_price,
+            liquidity,
+            position_amount_a.safe_add(self.vault_input_token_a_account.amount)?,
+            position_amount_b.safe_add(self.vault_input_token_b_account.amount)?,
+            lp_supply,
+        )?;
+
+        if vault_history.record(snapshot) {
+            vault_history.exit(&crate::ID)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Accounts needed to reinvest the vault balances into its active position. The vault
+/// token accounts must be reloaded by the caller after a reinvestment
+pub(crate) struct ReinvestAccounts<'a, 'info> {
+    pub whirlpool_program_id: &'a AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub vault_account: AccountInfo<'info>,
+    pub vault_input_token_a_account: AccountInfo<'info>,
+    pub vault_input_token_b_account: AccountInfo<'info>,
+    pub input_token_a_mint: Pubkey,
+    pub input_token_b_mint: Pubkey,
+    pub token_vault_a: &'a AccountInfo<'info>,
+    pub token_vault_b: &'a AccountInfo<'info>,
+    pub position: &'a PositionAccounts<'info>,
+    pub tick_arrays: [&'a AccountInfo<'info>; 3],
+    pub oracle: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> ReinvestAccounts<'a, 'info> {
     fn swap_ctx(
         &self,
     ) -> CpiContextForWhirlpool<'_, '_, '_, 'info, whirlpool::cpi::accounts::Swap<'info>> {
@@ -100,9 +180,9 @@ impl<'info> Reinvest<'info> {
                 token_vault_a: self.token_vault_a.to_account_info(),
                 token_owner_account_b: self.vault_input_token_b_account.to_account_info(),
                 token_vault_b: self.token_vault_b.to_account_info(),
-                tick_array_0: self.tick_array_0.to_account_info(),
-                tick_array_1: self.tick_array_1.to_account_info(),
-                tick_array_2: self.tick_array_2.to_account_info(),
+                tick_array_0: self.tick_arrays[0].to_account_info(),
+                tick_array_1: self.tick_arrays[1].to_account_info(),
+                tick_array_2: self.tick_arrays[2].to_account_info(),
                 oracle: self.oracle.to_account_info(),
             },
         )
@@ -130,9 +210,73 @@ impl<'info> Reinvest<'info> {
         )
     }
 
-    fn deposit_max_possible_liquidity_cpi(&self, signer: &[&[&[u8]]]) -> Result<()> {
-        let amount_a = self.vault_input_token_a_account.amount;
-        let amount_b = self.vault_input_token_b_account.amount;
+    /// Swap some tokens in order to maintain the position ratio
+    pub fn swap_to_position_ratio(
+        &self,
+        max_swap_slippage_bps: u16,
+        signer: &[&[&[u8]]],
+    ) -> Result<()> {
+        let (position_amount_a, position_amount_b) = self
+            .position
+            .token_amounts_from_liquidity(self.position.liquidity()?)?;
+        let (curr_sqrt_price, _) = self.position.sqrt_price_and_tick()?;
+
+        let amount_a = token::accessor::amount(&self.vault_input_token_a_account)?;
+        let amount_b = token::accessor::amount(&self.vault_input_token_b_account)?;
+
+        let swap_params = reinvest_quote(
+            amount_a,
+            amount_b,
+            position_amount_a,
+            position_amount_b,
+            curr_sqrt_price,
+            max_swap_slippage_bps,
+        )?
+        .swap;
+
+        if swap_params.amount == 0 {
+            return Ok(());
+        }
+
+        whirlpool::cpi::swap(
+            self.swap_ctx().with_signer(signer),
+            swap_params.amount,
+            swap_params.other_amount_threshold,
+            swap_params.sqrt_price_limit,
+            swap_params.amount_specified_is_input,
+            swap_params.a_to_b,
+        )?;
+
+        let event = if swap_params.a_to_b {
+            SwapEvent {
+                vault_account: self.vault_account.key(),
+                mint_in: self.input_token_a_mint,
+                amount_in: swap_params.amount,
+                mint_out: self.input_token_b_mint,
+                amount_out: token::accessor::amount(&self.vault_input_token_b_account)?
+                    .safe_sub(amount_b)?,
+            }
+        } else {
+            // The specified amount is the token_a output, unless all token_b is swapped
+            SwapEvent {
+                vault_account: self.vault_account.key(),
+                mint_in: self.input_token_b_mint,
+                amount_in: amount_b
+                    .safe_sub(token::accessor::amount(&self.vault_input_token_b_account)?)?,
+                mint_out: self.input_token_a_mint,
+                amount_out: token::accessor::amount(&self.vault_input_token_a_account)?
+                    .safe_sub(amount_a)?,
+            }
+        };
+
+        emit!(event);
+
+        Ok(())
+    }
+
+    pub fn deposit_max_possible_liquidity(&self, signer: &[&[&[u8]]]) -> Result<()> {
+        let amount_a = token::accessor::amount(&self.vault_input_token_a_account)?;
+        let amount_b = token::accessor::amount(&self.vault_input_token_b_account)?;
 
         if amount_a > 0 && amount_b > 0 {
             let liquidity = self
@@ -151,7 +295,25 @@ impl<'info> Reinvest<'info> {
     }
 }
 
-pub fn handler(ctx: Context<Reinvest>) -> Result<()> {
+/// Optional remaining accounts: the vault history, followed by the keeper token accounts of
+/// token_a and token_b
+fn split_remaining_accounts<'a, 'info>(
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> (Option<&'a AccountInfo<'info>>, &'a [AccountInfo<'info>]) {
+    if remaining_accounts.len() % 2 == 1 {
+        (remaining_accounts.first(), &remaining_accounts[1..])
+    } else {
+        (None, remaining_accounts)
+    }
+}
+
+/// Reinvest the vault balances into the active position. The keeper bounty of the amounts
+/// harvested without keeper accounts is paid to the caller token accounts, when provided
+pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, Reinvest<'info>>) -> Result<()> {
+    let (vault_history_info, keeper_remaining) = split_remaining_accounts(ctx.remaining_accounts);
+    let keeper_accounts =
+        keeper_input_token_accounts(keeper_remaining, &ctx.accounts.vault_account)?;
+
     let last_slot = ctx.accounts.vault_account.last_reinvestment_slot;
     let current_slot = Clock::get()?.slot;
     let elapsed_slots = current_slot.safe_sub(last_slot)?;
@@ -163,161 +325,53 @@ pub fn handler(ctx: Context<Reinvest>) -> Result<()> {
 
     ctx.accounts.vault_account.last_reinvestment_slot = current_slot;
 
+    let (curr_sqrt_price, curr_tick) = ctx.accounts.position.sqrt_price_and_tick()?;
+    ctx.accounts
+        .vault_account
+        .check_price_deviation(curr_sqrt_price, current_slot)?;
+    ctx.accounts
+        .vault_account
+        .price_history
+        .record(current_slot, curr_tick);
+
+    let (keeper_bounty_a, keeper_bounty_b) = pay_keeper_bounties(
+        &mut ctx.accounts.vault_account,
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.vault_input_token_a_account.to_account_info(),
+        &ctx.accounts.vault_input_token_b_account.to_account_info(),
+        keeper_accounts,
+        0,
+        0,
+    )?;
+
     let seeds = generate_seeds!(ctx.accounts.vault_account);
     let signer = &[&seeds[..]];
 
     let liquidity_before = ctx.accounts.position.liquidity()?;
 
-    // Swap some tokens in order to maintain the position ratio. Formula described in docs/math.tex
-    {
-        let amount_a = ctx.accounts.vault_input_token_a_account.amount;
-        let amount_b = ctx.accounts.vault_input_token_b_account.amount;
-
-        let (position_amount_a, position_amount_b) = ctx
-            .accounts
-            .position
-            .token_amounts_from_liquidity(ctx.accounts.position.liquidity()?)?;
-
-        let swap_params = if position_amount_a == 0 {
-            swap_params_from_a_to_b(amount_a)
-        } else if position_amount_b == 0 {
-            swap_params_from_b_to_a(amount_b)
-        } else {
-            let price_x128: U256 = {
-                use anchor_lang_for_whirlpool::AccountDeserialize;
-                use std::borrow::Borrow;
-
-                let acc_data_slice: &[u8] = &ctx.accounts.position.whirlpool.try_borrow_data()?;
-                let pool = whirlpool::state::whirlpool::Whirlpool::try_deserialize(
-                    &mut acc_data_slice.borrow(),
-                )?;
-
-                U256::from(pool.sqrt_price).pow(2.into())
-            };
-
-            let ratio_x64 = (1_u128 << bit_math::Q64_RESOLUTION)
-                .safe_mul_div(position_amount_a.into(), position_amount_b.into())?;
-
-            let ratio_times_price_x192 = U256::from(ratio_x64)
-                .checked_mul(price_x128)
-                .ok_or(ErrorCode::MathOverflowMul)?;
-
-            let ratio_amount_b_x64 = ratio_x64.safe_mul(amount_b.into())?;
-            let amount_a_x64 = u128::from(amount_a) << bit_math::Q64_RESOLUTION;
-
-            let is_delta_a_positive = amount_a_x64 > ratio_amount_b_x64;
-
-            let numerator_x64 = if is_delta_a_positive {
-                amount_a_x64.safe_sub(ratio_amount_b_x64)?
-            } else {
-                ratio_amount_b_x64.safe_sub(amount_a_x64)?
-            };
-
-            let numerator_x192 =
-                U256::from(numerator_x64) << bit_math::Q64_RESOLUTION << bit_math::Q64_RESOLUTION;
-
-            let denominator_x192 = (U256::from(1)
-                << bit_math::Q64_RESOLUTION
-                << bit_math::Q64_RESOLUTION
-                << bit_math::Q64_RESOLUTION)
-                .checked_add(ratio_times_price_x192)
-                .ok_or(ErrorCode::MathOverflowAdd)?;
-
-            let amount_to_swap: u64 = numerator_x192
-                .checked_div(denominator_x192)
-                .ok_or(ErrorCode::MathZeroDivision)?
-                .try_into()
-                .map_err(|_| error!(ErrorCode::MathOverflowConversion))?;
-
-            if is_delta_a_positive {
-                swap_params_from_a_to_b(amount_to_swap)
-            } else {
-                swap_params_from_b_to_a(amount_to_swap)
-            }
-        };
-
-        whirlpool::cpi::swap(
-            ctx.accounts.swap_ctx().with_signer(signer),
-            swap_params.amount,
-            swap_params.other_amount_threshold,
-            swap_params.sqrt_price_limit,
-            swap_params.amount_specified_is_input,
-            swap_params.a_to_b,
-        )?;
-
-        ctx.accounts.vault_input_token_a_account.reload()?;
-        ctx.accounts.vault_input_token_b_account.reload()?;
-
-        let event = if swap_params.a_to_b {
-            SwapEvent {
-                vault_account: ctx.accounts.vault_account.key(),
-                mint_in: ctx.accounts.vault_input_token_a_account.mint,
-                amount_in: swap_params.amount,
-                mint_out: ctx.accounts.vault_input_token_b_account.mint,
-                amount_out: ctx
-                    .accounts
-                    .vault_input_token_b_account
-                    .amount
-                    .safe_sub(amount_b)?,
-            }
-        } else {
-            SwapEvent {
-                vault_account: ctx.accounts.vault_account.key(),
-                mint_in: ctx.accounts.vault_input_token_b_account.mint,
-                amount_in: swap_params.amount,
-                mint_out: ctx.accounts.vault_input_token_a_account.mint,
-                amount_out: ctx
-                    .accounts
-                    .vault_input_token_a_account
-                    .amount
-                    .safe_sub(amount_a)?,
-            }
-        };
-
-        emit!(event);
-    }
-
-    ctx.accounts.deposit_max_possible_liquidity_cpi(signer)?;
+    let max_swap_slippage_bps = ctx.accounts.vault_account.max_swap_slippage_bps;
+    let reinvest_accounts = ctx.accounts.reinvest_accounts();
+    reinvest_accounts.swap_to_position_ratio(max_swap_slippage_bps, signer)?;
+    reinvest_accounts.deposit_max_possible_liquidity(signer)?;
 
     let liquidity_after = ctx.accounts.position.liquidity()?;
     let liquidity_increase = liquidity_after.safe_sub(liquidity_before)?;
     ctx.accounts.vault_account.last_liquidity_increase = liquidity_increase;
 
+    ctx.accounts.record_history(vault_history_info)?;
+
+    let (sqrt_price, _) = ctx.accounts.position.sqrt_price_and_tick()?;
+
     emit!(ReinvestEvent {
         vault_account: ctx.accounts.vault_account.key(),
         lp_supply: ctx.accounts.vault_lp_token_mint_pubkey.supply,
         liquidity: liquidity_after,
         liquidity_increase,
-        elapsed_slots
+        elapsed_slots,
+        keeper_bounty_token_a: keeper_bounty_a,
+        keeper_bounty_token_b: keeper_bounty_b,
+        sqrt_price,
     });
 
     Ok(())
 }
-
-struct SwapParams {
-    amount: u64,
-    other_amount_threshold: u64,
-    sqrt_price_limit: u128,
-    amount_specified_is_input: bool,
-    a_to_b: bool,
-}
-
-fn swap_params_from_a_to_b(amount_a: u64) -> SwapParams {
-    SwapParams {
-        amount: amount_a,
-        other_amount_threshold: 1,
-        sqrt_price_limit: MIN_SQRT_PRICE_X64,
-        amount_specified_is_input: true,
-        a_to_b: true,
-    }
-}
-
-fn swap_params_from_b_to_a(amount_b: u64) -> SwapParams {
-    SwapParams {
-        amount: amount_b,
-        other_amount_threshold: u64::MAX,
-        sqrt_price_limit: MAX_SQRT_PRICE_X64,
-        amount_specified_is_input: false,
-        a_to_b: false,
-    }
-}
@@ -0,0 +1,36 @@
+use crate::error::ErrorCode;
+use crate::state::{KeeperBounty, VaultAccount};
+use crate::{VAULT_ACCOUNT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+
+#[event]
+struct SetKeeperBountyEvent {
+    vault_account: Pubkey,
+    keeper_bounty: KeeperBounty,
+}
+
+#[derive(Accounts)]
+pub struct SetKeeperBounty<'info> {
+    #[account()]
+    pub user_signer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+}
+
+pub fn handler(ctx: Context<SetKeeperBounty>, keeper_bounty: KeeperBounty) -> Result<()> {
+    keeper_bounty.validate()?;
+
+    ctx.accounts.vault_account.keeper_bounty = keeper_bounty;
+
+    emit!(SetKeeperBountyEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        keeper_bounty,
+    });
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use crate::error::ErrorCode;
-use crate::state::{MarketRewards, MarketRewardsInfo, VaultAccount};
+use crate::state::{MarketRewards, MarketRewardsInfo, RewardsPriceBound, SwapRoute, VaultAccount};
 use crate::{VAULT_ACCOUNT_SEED, VAULT_VERSION};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Mint, TokenAccount};
@@ -12,6 +12,13 @@ pub struct MarketRewardsInfoInput {
     pub min_amount_out: u64,
 }
 
+#[event]
+struct SetMarketRewardsEvent {
+    vault_account: Pubkey,
+    reward_index: u8,
+    market_rewards: MarketRewardsInfo,
+}
+
 #[derive(Accounts)]
 pub struct SetMarketRewards<'info> {
     #[account()]
@@ -63,7 +70,25 @@ pub fn handler(
         ctx.accounts.vault_account.input_token_b_mint_pubkey,
     )?;
 
+    // The vault rewards account holds rewards owed to the LP holders
+    let current_market = ctx.accounts.vault_account.market_rewards[index];
+    require!(
+        current_market.id != MarketRewards::Distribute || market.id == MarketRewards::Distribute,
+        ErrorCode::DistributeMarketLocked
+    );
+
     ctx.accounts.vault_account.market_rewards[index] = market;
+    // The previous route and price bound may not apply to the new destination. The fixed
+    // default bound cannot be chunked
+    ctx.accounts.vault_account.rewards_routes[index] = SwapRoute::default();
+    ctx.accounts.vault_account.rewards_price_bounds[index] = RewardsPriceBound::default();
+    ctx.accounts.vault_account.rewards_swap_chunking[index].max_amount_in = 0;
+
+    emit!(SetMarketRewardsEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        reward_index: index as u8,
+        market_rewards: market,
+    });
 
     Ok(())
 }
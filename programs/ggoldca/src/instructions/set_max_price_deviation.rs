@@ -0,0 +1,39 @@
+use crate::error::ErrorCode;
+use crate::state::VaultAccount;
+use crate::{BPS_SCALE, VAULT_ACCOUNT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+
+#[event]
+struct SetMaxPriceDeviationEvent {
+    vault_account: Pubkey,
+    max_price_deviation_bps: u16,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxPriceDeviation<'info> {
+    #[account()]
+    pub user_signer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+}
+
+pub fn handler(ctx: Context<SetMaxPriceDeviation>, max_price_deviation_bps: u16) -> Result<()> {
+    require!(
+        u64::from(max_price_deviation_bps) <= BPS_SCALE,
+        ErrorCode::InvalidPriceDeviation
+    );
+
+    ctx.accounts.vault_account.max_price_deviation_bps = max_price_deviation_bps;
+
+    emit!(SetMaxPriceDeviationEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        max_price_deviation_bps,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::error::ErrorCode;
+use crate::state::VaultAccount;
+use crate::{BPS_SCALE, VAULT_ACCOUNT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+
+#[event]
+struct SetMaxSwapSlippageEvent {
+    vault_account: Pubkey,
+    max_slippage_bps: u16,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxSwapSlippage<'info> {
+    #[account()]
+    pub user_signer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+}
+
+pub fn handler(ctx: Context<SetMaxSwapSlippage>, max_slippage_bps: u16) -> Result<()> {
+    // Slippage can't be more than 100%
+    require!(
+        u64::from(max_slippage_bps) <= BPS_SCALE,
+        ErrorCode::InvalidSlippage
+    );
+
+    ctx.accounts.vault_account.max_swap_slippage_bps = max_slippage_bps;
+
+    emit!(SetMaxSwapSlippageEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        max_slippage_bps,
+    });
+
+    Ok(())
+}
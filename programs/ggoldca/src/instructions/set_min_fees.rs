@@ -0,0 +1,44 @@
+use crate::error::ErrorCode;
+use crate::state::VaultAccount;
+use crate::{VAULT_ACCOUNT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+
+#[event]
+struct SetMinFeesEvent {
+    vault_account: Pubkey,
+    min_fees_token_a: u64,
+    min_fees_token_b: u64,
+}
+
+#[derive(Accounts)]
+pub struct SetMinFees<'info> {
+    #[account()]
+    pub user_signer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+}
+
+pub fn handler(
+    ctx: Context<SetMinFees>,
+    min_fees_token_a: u64,
+    min_fees_token_b: u64,
+) -> Result<()> {
+    VaultAccount::validate_min_fees(min_fees_token_a, min_fees_token_b)?;
+
+    let vault = &mut ctx.accounts.vault_account;
+    vault.min_fees_token_a = min_fees_token_a;
+    vault.min_fees_token_b = min_fees_token_b;
+
+    emit!(SetMinFeesEvent {
+        vault_account: vault.key(),
+        min_fees_token_a,
+        min_fees_token_b,
+    });
+
+    Ok(())
+}
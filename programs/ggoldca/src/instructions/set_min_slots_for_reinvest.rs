@@ -3,6 +3,12 @@ use crate::state::VaultAccount;
 use crate::{VAULT_ACCOUNT_SEED, VAULT_VERSION};
 use anchor_lang::prelude::*;
 
+#[event]
+struct SetMinSlotsForReinvestEvent {
+    vault_account: Pubkey,
+    min_slots: u64,
+}
+
 #[derive(Accounts)]
 pub struct SetMinSlotsForReinvest<'info> {
     #[account()]
@@ -18,5 +24,11 @@ pub struct SetMinSlotsForReinvest<'info> {
 
 pub fn handler(ctx: Context<SetMinSlotsForReinvest>, min_slots: u64) -> Result<()> {
     ctx.accounts.vault_account.min_slots_for_reinvest = min_slots;
+
+    emit!(SetMinSlotsForReinvestEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        min_slots,
+    });
+
     Ok(())
 }
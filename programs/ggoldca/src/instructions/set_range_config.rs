@@ -0,0 +1,36 @@
+use crate::error::ErrorCode;
+use crate::state::{RangeConfig, VaultAccount};
+use crate::{VAULT_ACCOUNT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+
+#[event]
+struct SetRangeConfigEvent {
+    vault_account: Pubkey,
+    range_config: RangeConfig,
+}
+
+#[derive(Accounts)]
+pub struct SetRangeConfig<'info> {
+    #[account()]
+    pub user_signer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+}
+
+pub fn handler(ctx: Context<SetRangeConfig>, range_config: RangeConfig) -> Result<()> {
+    range_config.validate()?;
+
+    ctx.accounts.vault_account.range_config = range_config;
+
+    emit!(SetRangeConfigEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        range_config,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use crate::error::ErrorCode;
+use crate::state::{RewardsPriceBound, VaultAccount};
+use crate::{VAULT_ACCOUNT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[event]
+struct SetRewardsPriceBoundEvent {
+    vault_account: Pubkey,
+    rewards_mint: Pubkey,
+    price_bound: RewardsPriceBound,
+}
+
+#[derive(Accounts)]
+pub struct SetRewardsPriceBound<'info> {
+    #[account()]
+    pub user_signer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+    pub rewards_mint: Account<'info, Mint>,
+}
+
+pub fn handler(ctx: Context<SetRewardsPriceBound>, price_bound: RewardsPriceBound) -> Result<()> {
+    let index: usize = ctx
+        .accounts
+        .vault_account
+        .market_rewards
+        .iter()
+        .position(|market| market.rewards_mint == ctx.accounts.rewards_mint.key())
+        .ok_or_else(|| error!(ErrorCode::InvalidRewardMint))?;
+
+    price_bound.validate()?;
+    ctx.accounts.vault_account.rewards_swap_chunking[index].validate(price_bound.kind)?;
+
+    ctx.accounts.vault_account.rewards_price_bounds[index] = price_bound;
+
+    emit!(SetRewardsPriceBoundEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        rewards_mint: ctx.accounts.rewards_mint.key(),
+        price_bound,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use crate::error::ErrorCode;
+use crate::state::{RouteHop, SwapRoute, VaultAccount};
+use crate::{VAULT_ACCOUNT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, TokenAccount};
+
+#[event]
+struct SetRewardsRouteEvent {
+    vault_account: Pubkey,
+    rewards_mint: Pubkey,
+    route: SwapRoute,
+}
+
+#[derive(Accounts)]
+pub struct SetRewardsRoute<'info> {
+    #[account()]
+    pub user_signer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+    pub rewards_mint: Account<'info, Mint>,
+    pub destination_token_account: Account<'info, TokenAccount>,
+}
+
+pub fn handler(ctx: Context<SetRewardsRoute>, hops: Vec<RouteHop>) -> Result<()> {
+    let index: usize = ctx
+        .accounts
+        .vault_account
+        .market_rewards
+        .iter()
+        .position(|market| market.rewards_mint == ctx.accounts.rewards_mint.key())
+        .ok_or_else(|| error!(ErrorCode::InvalidRewardMint))?;
+
+    require!(
+        ctx.accounts.vault_account.market_rewards[index].destination_token_account
+            == ctx.accounts.destination_token_account.key(),
+        ErrorCode::InvalidDestinationAccount
+    );
+
+    // An empty list of hops resets the route to the direct market
+    let route = SwapRoute::new(
+        &hops,
+        ctx.accounts.rewards_mint.key(),
+        ctx.accounts.destination_token_account.mint,
+        &[
+            ctx.accounts.vault_account.input_token_a_mint_pubkey,
+            ctx.accounts.vault_account.input_token_b_mint_pubkey,
+        ],
+    )?;
+
+    ctx.accounts.vault_account.rewards_routes[index] = route;
+
+    emit!(SetRewardsRouteEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        rewards_mint: ctx.accounts.rewards_mint.key(),
+        route,
+    });
+
+    Ok(())
+}
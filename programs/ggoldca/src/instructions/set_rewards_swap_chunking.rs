@@ -0,0 +1,57 @@
+use crate::error::ErrorCode;
+use crate::state::VaultAccount;
+use crate::{VAULT_ACCOUNT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+use anchor_spl::token::Mint;
+
+#[event]
+struct SetRewardsSwapChunkingEvent {
+    vault_account: Pubkey,
+    rewards_mint: Pubkey,
+    max_amount_in: u64,
+    min_slots_between_swaps: u64,
+}
+
+#[derive(Accounts)]
+pub struct SetRewardsSwapChunking<'info> {
+    #[account()]
+    pub user_signer: Signer<'info>,
+    #[account(
+        mut,
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+    pub rewards_mint: Account<'info, Mint>,
+}
+
+pub fn handler(
+    ctx: Context<SetRewardsSwapChunking>,
+    max_amount_in: u64,
+    min_slots_between_swaps: u64,
+) -> Result<()> {
+    let index: usize = ctx
+        .accounts
+        .vault_account
+        .market_rewards
+        .iter()
+        .position(|market| market.rewards_mint == ctx.accounts.rewards_mint.key())
+        .ok_or_else(|| error!(ErrorCode::InvalidRewardMint))?;
+
+    // Keep the swaps tracking, only the config is updated
+    let price_bound_kind = ctx.accounts.vault_account.rewards_price_bounds[index].kind;
+    let chunking = &mut ctx.accounts.vault_account.rewards_swap_chunking[index];
+    chunking.max_amount_in = max_amount_in;
+    chunking.min_slots_between_swaps = min_slots_between_swaps;
+    chunking.validate(price_bound_kind)?;
+
+    emit!(SetRewardsSwapChunkingEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        rewards_mint: ctx.accounts.rewards_mint.key(),
+        max_amount_in,
+        min_slots_between_swaps,
+    });
+
+    Ok(())
+}
@@ -13,6 +13,14 @@ use {
     },
 };
 
+#[event]
+struct SetTokenMetadataEvent {
+    vault_account: Pubkey,
+    token_name: String,
+    token_symbol: String,
+    token_uri: String,
+}
+
 #[derive(Accounts)]
 pub struct SetTokenMetadata<'info> {
     /// CHECK: Metadata key (pda of ['metadata', program id, mint id])
@@ -58,6 +66,13 @@ pub fn handler(
     token_uri: String,
     first_time: bool,
 ) -> Result<()> {
+    let event = SetTokenMetadataEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        token_name: token_name.clone(),
+        token_symbol: token_symbol.clone(),
+        token_uri: token_uri.clone(),
+    };
+
     let ix = match first_time {
         true => create_metadata_accounts_v2(
             *ctx.accounts.token_metadata_program.key,
@@ -110,5 +125,7 @@ pub fn handler(
         &[seeds],
     )?;
 
+    emit!(event);
+
     Ok(())
 }
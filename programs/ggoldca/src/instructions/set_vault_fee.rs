@@ -3,6 +3,12 @@ use crate::state::VaultAccount;
 use crate::{FEE_SCALE, VAULT_ACCOUNT_SEED, VAULT_VERSION};
 use anchor_lang::prelude::*;
 
+#[event]
+struct SetVaultFeeEvent {
+    vault_account: Pubkey,
+    fee: u64,
+}
+
 #[derive(Accounts)]
 pub struct SetVaultFee<'info> {
     #[account()]
@@ -21,5 +27,11 @@ pub fn handler(ctx: Context<SetVaultFee>, fee: u64) -> Result<()> {
     require!(fee <= FEE_SCALE, ErrorCode::InvalidFee);
 
     ctx.accounts.vault_account.fee = fee;
+
+    emit!(SetVaultFeeEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        fee,
+    });
+
     Ok(())
 }
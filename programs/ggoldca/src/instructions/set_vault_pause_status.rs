@@ -3,6 +3,12 @@ use crate::state::VaultAccount;
 use crate::{VAULT_ACCOUNT_SEED, VAULT_VERSION};
 use anchor_lang::prelude::*;
 
+#[event]
+struct SetVaultPauseStatusEvent {
+    vault_account: Pubkey,
+    is_paused: bool,
+}
+
 #[derive(Accounts)]
 pub struct SetVaultPauseStatus<'info> {
     #[account()]
@@ -18,5 +24,11 @@ pub struct SetVaultPauseStatus<'info> {
 
 pub fn handler(ctx: Context<SetVaultPauseStatus>, is_paused: bool) -> Result<()> {
     ctx.accounts.vault_account.is_paused = is_paused;
+
+    emit!(SetVaultPauseStatusEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        is_paused,
+    });
+
     Ok(())
 }
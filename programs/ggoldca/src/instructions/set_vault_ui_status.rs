@@ -3,6 +3,12 @@ use crate::state::VaultAccount;
 use crate::{VAULT_ACCOUNT_SEED, VAULT_VERSION};
 use anchor_lang::prelude::*;
 
+#[event]
+struct SetVaultUiStatusEvent {
+    vault_account: Pubkey,
+    is_active: bool,
+}
+
 #[derive(Accounts)]
 pub struct SetVaultUiStatus<'info> {
     #[account()]
@@ -18,5 +24,11 @@ pub struct SetVaultUiStatus<'info> {
 
 pub fn handler(ctx: Context<SetVaultUiStatus>, is_active: bool) -> Result<()> {
     ctx.accounts.vault_account.is_active_from_ui = is_active;
+
+    emit!(SetVaultUiStatusEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        is_active,
+    });
+
     Ok(())
 }
@@ -0,0 +1,98 @@
+use crate::error::ErrorCode;
+use crate::math::safe_arithmetics::SafeArithmetics;
+use crate::state::{Farm, FarmStake};
+use crate::{FARM_SEED, FARM_STAKE_SEED};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+#[event]
+pub struct StakeUnstakeLpEvent {
+    pub farm: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub total_staked: u64,
+}
+
+#[derive(Accounts)]
+pub struct StakeUnstakeLp<'info> {
+    pub user_signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [FARM_SEED, farm.vault_account.as_ref(), &[farm.id][..]],
+        bump = farm.bump
+    )]
+    pub farm: Box<Account<'info, Farm>>,
+    #[account(
+        mut,
+        seeds = [FARM_STAKE_SEED, farm.key().as_ref(), user_signer.key().as_ref()],
+        bump = farm_stake.bump
+    )]
+    pub farm_stake: Account<'info, FarmStake>,
+    #[account(
+        mut,
+        associated_token::mint = farm.lp_token_mint,
+        associated_token::authority = farm,
+    )]
+    pub farm_lp_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        token::mint = farm.lp_token_mint,
+        token::authority = user_signer.key(),
+    )]
+    pub user_lp_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> StakeUnstakeLp<'info> {
+    /// Accrue the farm rewards before the staked amount changes
+    pub fn update_rewards(&mut self) -> Result<()> {
+        let current_slot = Clock::get()?.slot;
+        self.farm.update(current_slot)?;
+        self.farm_stake.update(self.farm.reward_per_share_x64)
+    }
+
+    fn transfer_from_user_to_farm_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.user_lp_token_account.to_account_info(),
+                to: self.farm_lp_token_account.to_account_info(),
+                authority: self.user_signer.to_account_info(),
+            },
+        )
+    }
+
+    pub fn transfer_from_farm_to_user_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.farm_lp_token_account.to_account_info(),
+                to: self.user_lp_token_account.to_account_info(),
+                authority: self.farm.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn handler(ctx: Context<StakeUnstakeLp>, lp_amount: u64) -> Result<()> {
+    require!(lp_amount > 0, ErrorCode::ZeroLpAmount);
+
+    ctx.accounts.update_rewards()?;
+
+    token::transfer(ctx.accounts.transfer_from_user_to_farm_ctx(), lp_amount)?;
+
+    let farm_stake = &mut ctx.accounts.farm_stake;
+    farm_stake.amount = farm_stake.amount.safe_add(lp_amount)?;
+
+    let farm = &mut ctx.accounts.farm;
+    farm.total_staked = farm.total_staked.safe_add(lp_amount)?;
+
+    emit!(StakeUnstakeLpEvent {
+        farm: ctx.accounts.farm.key(),
+        user: ctx.accounts.user_signer.key(),
+        amount: lp_amount,
+        total_staked: ctx.accounts.farm.total_staked,
+    });
+
+    Ok(())
+}
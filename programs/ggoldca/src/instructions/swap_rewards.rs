@@ -1,16 +1,16 @@
 use crate::error::ErrorCode;
-use crate::interfaces::orca_swap_v2;
+use crate::instructions::collect_fees::{keeper_token_account, transfer_from_vault};
+use crate::interfaces::swap_adapter::{swap_adapter, SwapAccounts};
 use crate::macros::generate_seeds;
-use crate::math::safe_arithmetics::SafeArithmetics;
-use crate::state::{MarketRewards, MarketRewardsInfo, VaultAccount};
-use crate::{VAULT_ACCOUNT_SEED, VAULT_VERSION};
+use crate::math::price::{amount_a_to_b, amount_at_price, amount_b_to_a};
+use crate::math::safe_arithmetics::{SafeArithmetics, SafeMulDiv};
+use crate::state::{MarketRewardsInfo, PriceBoundKind, RewardsPriceBound, SwapRoute, VaultAccount};
+use crate::{BPS_SCALE, VAULT_ACCOUNT_SEED, VAULT_VERSION};
 use anchor_lang::prelude::*;
-use anchor_lang_for_whirlpool::{
-    context::CpiContext as CpiContextForWhirlpool, AccountDeserialize,
-};
-use anchor_spl::token::{Token, TokenAccount};
+use anchor_lang_for_whirlpool::AccountDeserialize;
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::{self, Token, TokenAccount};
 use std::borrow::Borrow;
-use whirlpool::math::tick_math::{MAX_SQRT_PRICE_X64, MIN_SQRT_PRICE_X64};
 
 #[event]
 pub struct SwapEvent {
@@ -21,6 +21,18 @@ pub struct SwapEvent {
     pub amount_out: u64,
 }
 
+#[event]
+pub struct SwapRewardsEvent {
+    pub vault_account: Pubkey,
+    pub rewards_mint: Pubkey,
+    /// Number of swaps done for the rewards mint
+    pub swap_count: u64,
+    /// Rewards left to be swapped in the next tranches
+    pub remaining_amount: u64,
+    /// Part of the amount out paid to the caller
+    pub keeper_bounty: u64,
+}
+
 #[derive(Accounts)]
 pub struct SwapRewards<'info> {
     #[account(
@@ -47,77 +59,30 @@ pub struct SwapRewards<'info> {
     pub vault_destination_token_account: Account<'info, TokenAccount>,
     pub token_program: Program<'info, Token>,
 
-    /// CHECK: address is checked
+    /// CHECK: address is checked. Program of the first hop for multi-hop routes
     pub swap_program: AccountInfo<'info>,
 }
 
-impl<'info> SwapRewards<'info> {
-    fn swap_orca_ctx(
-        &self,
-        remaining: &[AccountInfo<'info>],
-    ) -> CpiContext<'_, '_, '_, 'info, orca_swap_v2::Swap<'info>> {
-        CpiContext::new(
-            self.swap_program.to_account_info(),
-            orca_swap_v2::Swap {
-                token_program: self.token_program.to_account_info(),
-                user_account: self.vault_account.to_account_info(),
-                user_token_a_account: self.vault_rewards_token_account.to_account_info(),
-                user_token_b_account: self.vault_destination_token_account.to_account_info(),
-                amm_id: remaining[0].to_account_info(),
-                amm_authority: remaining[1].to_account_info(),
-                pool_token_a_account: remaining[2].to_account_info(),
-                pool_token_b_account: remaining[3].to_account_info(),
-                lp_token_mint: remaining[4].to_account_info(),
-                fees_account: remaining[5].to_account_info(),
-            },
-        )
-    }
-
-    fn whirlpool_swap_ctx(
-        &self,
-        remaining: &[AccountInfo<'info>],
-        rewards_acc_is_token_a: bool,
-    ) -> CpiContextForWhirlpool<'_, '_, '_, 'info, whirlpool::cpi::accounts::Swap<'info>> {
-        let (token_owner_account_a, token_owner_account_b) = if rewards_acc_is_token_a {
-            (
-                self.vault_rewards_token_account.to_account_info(),
-                self.vault_destination_token_account.to_account_info(),
-            )
-        } else {
-            (
-                self.vault_destination_token_account.to_account_info(),
-                self.vault_rewards_token_account.to_account_info(),
-            )
-        };
-
-        CpiContextForWhirlpool::new(
-            self.swap_program.to_account_info(),
-            whirlpool::cpi::accounts::Swap {
-                token_program: self.token_program.to_account_info(),
-                token_authority: self.vault_account.to_account_info(),
-                token_owner_account_a,
-                token_owner_account_b,
-                whirlpool: remaining[0].to_account_info(),
-                token_vault_a: remaining[1].to_account_info(),
-                token_vault_b: remaining[2].to_account_info(),
-                tick_array_0: remaining[3].to_account_info(),
-                tick_array_1: remaining[4].to_account_info(),
-                tick_array_2: remaining[5].to_account_info(),
-                oracle: remaining[6].to_account_info(),
-            },
-        )
-    }
-}
-
+/// Swap the vault rewards into one of the input tokens. The keeper bounty is paid out of the
+/// amount received, to the caller token account of the destination mint when provided as last
+/// remaining account
 pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, SwapRewards<'info>>) -> Result<()> {
-    let market_rewards: &MarketRewardsInfo = ctx
+    let index = ctx
         .accounts
         .vault_account
         .market_rewards
         .iter()
-        .find(|market| market.rewards_mint == ctx.accounts.vault_rewards_token_account.mint)
+        .position(|market| market.rewards_mint == ctx.accounts.vault_rewards_token_account.mint)
         .ok_or(ErrorCode::InvalidRewardMint)?;
 
+    let market_rewards: &MarketRewardsInfo = &ctx.accounts.vault_account.market_rewards[index];
+    let route = ctx.accounts.vault_account.rewards_routes[index];
+    let price_bound = ctx.accounts.vault_account.rewards_price_bounds[index];
+    let chunking = ctx.accounts.vault_account.rewards_swap_chunking[index];
+
+    let current_slot = Clock::get()?.slot;
+    require!(chunking.can_swap(current_slot), ErrorCode::NotEnoughSlots);
+
     require!(
         market_rewards.destination_token_account
             == ctx.accounts.vault_destination_token_account.key(),
@@ -125,22 +90,105 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, SwapRewards<'info>>) -> Re
     );
 
     let amount_out_before = ctx.accounts.vault_destination_token_account.amount;
-    let amount_to_swap = ctx.accounts.vault_rewards_token_account.amount;
-    let min_amount_out = market_rewards.min_amount_out;
+    let amount_available = ctx.accounts.vault_rewards_token_account.amount;
+    let amount_to_swap = chunking.amount_to_swap(amount_available);
 
-    match market_rewards.id {
-        MarketRewards::OrcaV2 => swap_orca_cpi(&ctx, amount_to_swap, min_amount_out),
-        MarketRewards::Whirlpool => swap_whirlpool_cpi(&ctx, amount_to_swap, min_amount_out),
-        _ => Err(ErrorCode::SwapNotSet.into()),
-    }?;
+    // The accounts needed by the price bound are placed after the swap ones,
+    // followed by the optional keeper token account
+    let swap_accounts_len = rewards_swap_accounts_len(market_rewards, &route)?;
+    let (swap_remaining, rest) = ctx
+        .remaining_accounts
+        .split_at(swap_accounts_len.min(ctx.remaining_accounts.len()));
+    let (price_bound_remaining, keeper_remaining) =
+        rest.split_at(price_bound.accounts_len().min(rest.len()));
+    let keeper_destination_account = match keeper_remaining {
+        [] => None,
+        [keeper_destination_account] => Some(keeper_token_account(
+            keeper_destination_account,
+            ctx.accounts.vault_destination_token_account.mint,
+        )?),
+        _ => return Err(ErrorCode::InvalidNumberOfAccounts.into()),
+    };
+
+    let min_amount_out = rewards_min_amount_out(
+        market_rewards,
+        &price_bound,
+        ctx.accounts.vault_destination_token_account.mint,
+        amount_to_swap,
+        swap_remaining,
+        price_bound_remaining.first(),
+    )?;
+
+    let seeds = generate_seeds!(ctx.accounts.vault_account);
+    let signer = &[&seeds[..]];
+
+    swap_rewards_cpi(
+        market_rewards,
+        &route,
+        &SwapAccounts {
+            token_program: &ctx.accounts.token_program.to_account_info(),
+            authority: &ctx.accounts.vault_account.to_account_info(),
+            source_token_account: &ctx.accounts.vault_rewards_token_account.to_account_info(),
+            source_mint: ctx.accounts.vault_rewards_token_account.mint,
+            destination_token_account: &ctx
+                .accounts
+                .vault_destination_token_account
+                .to_account_info(),
+            destination_mint: ctx.accounts.vault_destination_token_account.mint,
+            swap_program: &ctx.accounts.swap_program,
+            remaining: swap_remaining,
+        },
+        amount_to_swap,
+        min_amount_out,
+        signer,
+    )?;
 
     ctx.accounts.vault_destination_token_account.reload()?;
 
     let amount_out_after = ctx.accounts.vault_destination_token_account.amount;
     let amount_out_increase = amount_out_after.safe_sub(amount_out_before)?;
 
+    let is_destination_a = ctx.accounts.vault_destination_token_account.mint
+        == ctx.accounts.vault_account.input_token_a_mint_pubkey;
+
+    let (amount_a, amount_b) = if is_destination_a {
+        (amount_out_increase, 0)
+    } else {
+        (0, amount_out_increase)
+    };
+
+    // Only the instructions paying both input tokens pay the carried amounts
+    let keeper_bounty = match keeper_destination_account {
+        Some(keeper_destination_account) => {
+            let (keeper_bounty_a, keeper_bounty_b) = ctx
+                .accounts
+                .vault_account
+                .keeper_bounty
+                .amounts(amount_a, amount_b)?;
+            let keeper_bounty = keeper_bounty_a.max(keeper_bounty_b);
+
+            transfer_from_vault(
+                &ctx.accounts.token_program.to_account_info(),
+                &ctx.accounts.vault_account.to_account_info(),
+                &ctx.accounts
+                    .vault_destination_token_account
+                    .to_account_info(),
+                &keeper_destination_account,
+                keeper_bounty,
+                signer,
+            )?;
+            keeper_bounty
+        }
+        None => {
+            ctx.accounts
+                .vault_account
+                .keeper_bounties(amount_a, amount_b, 0, 0, false)?;
+            0
+        }
+    };
+
     let vault = &mut ctx.accounts.vault_account;
-    if ctx.accounts.vault_destination_token_account.mint == vault.input_token_a_mint_pubkey {
+    if is_destination_a {
         vault.earned_rewards_token_a =
             vault.earned_rewards_token_a.safe_add(amount_out_increase)?;
     } else {
@@ -148,6 +196,10 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, SwapRewards<'info>>) -> Re
             vault.earned_rewards_token_b.safe_add(amount_out_increase)?;
     }
 
+    let chunking = &mut vault.rewards_swap_chunking[index];
+    chunking.record_swap(current_slot)?;
+    let swap_count = chunking.swap_count;
+
     emit!(SwapEvent {
         vault_account: ctx.accounts.vault_account.key(),
         mint_in: ctx.accounts.vault_rewards_token_account.mint,
@@ -156,73 +208,219 @@ pub fn handler<'info>(ctx: Context<'_, '_, '_, 'info, SwapRewards<'info>>) -> Re
         amount_out: amount_out_increase,
     });
 
+    emit!(SwapRewardsEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        rewards_mint: ctx.accounts.vault_rewards_token_account.mint,
+        swap_count,
+        remaining_amount: amount_available.safe_sub(amount_to_swap)?,
+        keeper_bounty,
+    });
+
     Ok(())
 }
 
-fn swap_orca_cpi<'info>(
-    ctx: &Context<'_, '_, '_, 'info, SwapRewards<'info>>,
-    amount_to_swap: u64,
+/// Number of accounts preceding the venue specific accounts of each route hop:
+/// swap_program, hop output token account
+const ROUTE_HOP_BASE_LEN: usize = 2;
+
+/// Accounts needed to swap through a route
+pub(crate) struct RouteAccounts<'a, 'info> {
+    pub token_program: &'a AccountInfo<'info>,
+    /// Owner of the token accounts
+    pub authority: &'a AccountInfo<'info>,
+    pub source_token_account: &'a AccountInfo<'info>,
+    pub source_mint: Pubkey,
+    pub destination_token_account: &'a AccountInfo<'info>,
+    /// Per hop: swap_program, output token account and venue specific accounts.
+    /// The output of the intermediate hops is the authority ATA of the hop mint
+    pub remaining: &'a [AccountInfo<'info>],
+}
+
+/// Number of accounts required to swap the rewards of the market, excluding the swap_program
+/// of direct swaps
+pub(crate) fn rewards_swap_accounts_len(
+    market: &MarketRewardsInfo,
+    route: &SwapRoute,
+) -> Result<usize> {
+    if route.is_set() {
+        route_accounts_len(route)
+    } else {
+        Ok(swap_adapter(market.id)?.accounts_len())
+    }
+}
+
+/// Swap the rewards through the route when set, directly through the market otherwise.
+/// For routes, accounts.remaining holds the hops accounts and swap_program is the
+/// program of the first hop
+pub(crate) fn swap_rewards_cpi(
+    market: &MarketRewardsInfo,
+    route: &SwapRoute,
+    accounts: &SwapAccounts,
+    amount_in: u64,
     min_amount_out: u64,
+    signer: &[&[&[u8]]],
 ) -> Result<()> {
-    require!(
-        ctx.accounts.swap_program.key() == orca_swap_v2::ID,
-        ErrorCode::SwapInvalidProgramId
-    );
-    require!(ctx.remaining_accounts.len() == 6, InvalidNumberOfAccounts);
+    if route.is_set() {
+        require!(
+            accounts.remaining.first().map(|acc| acc.key()) == Some(accounts.swap_program.key()),
+            ErrorCode::SwapInvalidProgramId
+        );
 
-    let seeds = generate_seeds!(ctx.accounts.vault_account);
-    let signer = &[&seeds[..]];
-
-    orca_swap_v2::swap(
-        ctx.accounts
-            .swap_orca_ctx(ctx.remaining_accounts)
-            .with_signer(signer),
-        amount_to_swap,
-        min_amount_out,
-    )?;
+        swap_through_route(
+            route,
+            &RouteAccounts {
+                token_program: accounts.token_program,
+                authority: accounts.authority,
+                source_token_account: accounts.source_token_account,
+                source_mint: accounts.source_mint,
+                destination_token_account: accounts.destination_token_account,
+                remaining: accounts.remaining,
+            },
+            amount_in,
+            min_amount_out,
+            signer,
+        )?;
+    } else {
+        swap_adapter(market.id)?.validate_and_swap(accounts, amount_in, min_amount_out, signer)?;
+    }
 
     Ok(())
 }
 
-fn swap_whirlpool_cpi<'info>(
-    ctx: &Context<'_, '_, '_, 'info, SwapRewards<'info>>,
-    amount_to_swap: u64,
+/// Number of remaining accounts required to swap through the route
+pub(crate) fn route_accounts_len(route: &SwapRoute) -> Result<usize> {
+    route.hops().iter().try_fold(0_usize, |len, hop| {
+        len.safe_add(ROUTE_HOP_BASE_LEN.safe_add(swap_adapter(hop.market)?.accounts_len())?)
+    })
+}
+
+/// Swap amount_in through every hop of the route, checking only the final amount out.
+/// Returns the amount received in the destination account
+pub(crate) fn swap_through_route(
+    route: &SwapRoute,
+    accounts: &RouteAccounts,
+    amount_in: u64,
     min_amount_out: u64,
-) -> Result<()> {
+    signer: &[&[&[u8]]],
+) -> Result<u64> {
     require!(
-        ctx.accounts.swap_program.key() == whirlpool::ID,
-        ErrorCode::SwapInvalidProgramId
+        accounts.remaining.len() == route_accounts_len(route)?,
+        ErrorCode::InvalidNumberOfAccounts
     );
-    require!(ctx.remaining_accounts.len() == 7, InvalidNumberOfAccounts);
 
-    let rewards_acc_is_token_a = {
-        let acc_data_slice: &[u8] = &ctx.remaining_accounts[0].try_borrow_data()?;
-        let pool =
-            whirlpool::state::whirlpool::Whirlpool::try_deserialize(&mut acc_data_slice.borrow())?;
+    let hops = route.hops();
+    let mut remaining = accounts.remaining;
+    let mut source_token_account = accounts.source_token_account;
+    let mut source_mint = accounts.source_mint;
+    let mut amount = amount_in;
 
-        ctx.accounts.vault_rewards_token_account.mint == pool.token_mint_a
-    };
+    for (i, hop) in hops.iter().enumerate() {
+        let adapter = swap_adapter(hop.market)?;
+        let (group, rest) = remaining.split_at(ROUTE_HOP_BASE_LEN + adapter.accounts_len());
+        remaining = rest;
 
-    let is_swap_from_a_to_b = rewards_acc_is_token_a;
-    let sqrt_price_limit = if is_swap_from_a_to_b {
-        MIN_SQRT_PRICE_X64
-    } else {
-        MAX_SQRT_PRICE_X64
-    };
+        let is_last_hop = i == hops.len() - 1;
+        let destination_token_account = &group[1];
+        let expected_destination = if is_last_hop {
+            accounts.destination_token_account.key()
+        } else {
+            get_associated_token_address(accounts.authority.key, &hop.mint_out)
+        };
+        require!(
+            destination_token_account.key() == expected_destination,
+            ErrorCode::InvalidDestinationAccount
+        );
 
-    let seeds = generate_seeds!(ctx.accounts.vault_account);
-    let signer = &[&seeds[..]];
+        let amount_before = token::accessor::amount(destination_token_account)?;
 
-    whirlpool::cpi::swap(
-        ctx.accounts
-            .whirlpool_swap_ctx(ctx.remaining_accounts, rewards_acc_is_token_a)
-            .with_signer(signer),
-        amount_to_swap,
-        min_amount_out,
-        sqrt_price_limit,
-        true,
-        is_swap_from_a_to_b,
-    )?;
+        // The route is bounded as a whole by min_amount_out, intermediate hops
+        // must only output something
+        let hop_min_amount_out = if is_last_hop { min_amount_out } else { 1 };
 
-    Ok(())
+        adapter.validate_and_swap(
+            &SwapAccounts {
+                token_program: accounts.token_program,
+                authority: accounts.authority,
+                source_token_account,
+                source_mint,
+                destination_token_account,
+                destination_mint: hop.mint_out,
+                swap_program: &group[0],
+                remaining: &group[ROUTE_HOP_BASE_LEN..],
+            },
+            amount,
+            hop_min_amount_out,
+            signer,
+        )?;
+
+        amount = token::accessor::amount(destination_token_account)?.safe_sub(amount_before)?;
+        source_token_account = destination_token_account;
+        source_mint = hop.mint_out;
+    }
+
+    require!(amount >= min_amount_out, ErrorCode::SwapAmountOutTooLow);
+
+    Ok(amount)
+}
+
+/// Minimum amount to receive when swapping amount_in of the rewards into destination_mint.
+/// swap_accounts are the accounts of the swap venues, which the reference pool cannot be
+/// part of, as a swap through it would move the price it is bounded with
+pub(crate) fn rewards_min_amount_out(
+    market: &MarketRewardsInfo,
+    price_bound: &RewardsPriceBound,
+    destination_mint: Pubkey,
+    amount_in: u64,
+    swap_accounts: &[AccountInfo],
+    reference_pool: Option<&AccountInfo>,
+) -> Result<u64> {
+    let min_amount_out = match price_bound.kind {
+        PriceBoundKind::Fixed => return Ok(market.min_amount_out),
+        PriceBoundKind::MinPrice => amount_at_price(amount_in, price_bound.min_price_x64)?,
+        PriceBoundKind::ReferencePool => {
+            let reference_pool = reference_pool.ok_or(ErrorCode::InvalidNumberOfAccounts)?;
+            require!(
+                reference_pool.key() == price_bound.reference_pool
+                    && reference_pool.owner == &whirlpool::ID,
+                ErrorCode::InvalidReferencePool
+            );
+            require!(
+                swap_accounts
+                    .iter()
+                    .all(|account| account.key != reference_pool.key),
+                ErrorCode::InvalidReferencePool
+            );
+
+            let pool = {
+                let acc_data_slice: &[u8] = &reference_pool.try_borrow_data()?;
+                whirlpool::state::whirlpool::Whirlpool::try_deserialize(
+                    &mut acc_data_slice.borrow(),
+                )?
+            };
+
+            let expected_amount_out = if pool.token_mint_a == market.rewards_mint
+                && pool.token_mint_b == destination_mint
+            {
+                amount_a_to_b(amount_in, pool.sqrt_price)?
+            } else if pool.token_mint_a == destination_mint
+                && pool.token_mint_b == market.rewards_mint
+            {
+                amount_b_to_a(amount_in, pool.sqrt_price)?
+            } else {
+                return Err(ErrorCode::InvalidReferencePool.into());
+            };
+
+            // The spot price can still be moved within the transaction, the min price
+            // stays the floor of the bound when set
+            expected_amount_out
+                .safe_mul_div(
+                    u128::from(BPS_SCALE.safe_sub(price_bound.max_slippage_bps.into())?),
+                    u128::from(BPS_SCALE),
+                )?
+                .max(amount_at_price(amount_in, price_bound.min_price_x64)?)
+        }
+    };
+
+    // Receiving nothing is never acceptable
+    Ok(u64::try_from(min_amount_out).unwrap_or(u64::MAX).max(1))
 }
@@ -0,0 +1,91 @@
+use crate::error::ErrorCode;
+use crate::macros::generate_seeds;
+use crate::state::VaultAccount;
+use crate::{TREASURY_PUBKEY, VAULT_ACCOUNT_SEED, VAULT_LP_TOKEN_MINT_SEED, VAULT_VERSION};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+
+#[event]
+struct SweepTokenEvent {
+    vault_account: Pubkey,
+    mint: Pubkey,
+    amount: u64,
+}
+
+#[derive(Accounts)]
+pub struct SweepToken<'info> {
+    #[account()]
+    pub user_signer: Signer<'info>,
+    #[account(
+        constraint = vault_account.version == VAULT_VERSION @ ErrorCode::InvalidVaultVersion,
+        seeds = [VAULT_ACCOUNT_SEED, &[vault_account.id][..], vault_account.whirlpool_id.as_ref()],
+        bump = vault_account.bumps.vault
+    )]
+    pub vault_account: Box<Account<'info, VaultAccount>>,
+    #[account(
+        mut,
+        constraint = vault_account.is_sweepable_mint(vault_token_account.mint) @ ErrorCode::SweepMintNotAllowed,
+        token::authority = vault_account,
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(
+        mut,
+        associated_token::mint = vault_token_account.mint,
+        associated_token::authority = TREASURY_PUBKEY
+    )]
+    pub treasury_token_account: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> SweepToken<'info> {
+    fn transfer_from_vault_to_treasury_ctx(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.vault_token_account.to_account_info(),
+                to: self.treasury_token_account.to_account_info(),
+                authority: self.vault_account.to_account_info(),
+            },
+        )
+    }
+}
+
+pub fn handler(ctx: Context<SweepToken>) -> Result<()> {
+    // The LP tokens held by the vault are the ones locked by the users
+    let vault_lp_token_mint = Pubkey::create_program_address(
+        &[
+            VAULT_LP_TOKEN_MINT_SEED,
+            ctx.accounts.vault_account.key().as_ref(),
+            &[ctx.accounts.vault_account.bumps.lp_token_mint],
+        ],
+        &crate::ID,
+    )
+    .map_err(|_| error!(ErrorCode::SweepMintNotAllowed))?;
+    require!(
+        ctx.accounts.vault_token_account.mint != vault_lp_token_mint,
+        ErrorCode::SweepMintNotAllowed
+    );
+
+    let seeds = generate_seeds!(ctx.accounts.vault_account);
+    let signer = &[&seeds[..]];
+
+    let amount = ctx.accounts.vault_token_account.amount;
+    if amount > 0 {
+        token::transfer(
+            ctx.accounts
+                .transfer_from_vault_to_treasury_ctx()
+                .with_signer(signer),
+            amount,
+        )?;
+    }
+
+    emit!(SweepTokenEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        mint: ctx.accounts.vault_token_account.mint,
+        amount,
+    });
+
+    Ok(())
+}
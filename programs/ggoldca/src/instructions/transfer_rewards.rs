@@ -5,6 +5,14 @@ use crate::{VAULT_ACCOUNT_SEED, VAULT_VERSION};
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 
+#[event]
+struct TransferRewardsEvent {
+    vault_account: Pubkey,
+    rewards_mint: Pubkey,
+    destination_token_account: Pubkey,
+    amount: u64,
+}
+
 #[derive(Accounts)]
 pub struct TransferRewards<'info> {
     #[account(
@@ -63,12 +71,20 @@ pub fn handler(ctx: Context<TransferRewards>) -> Result<()> {
         ErrorCode::InvalidDestinationAccount
     );
 
+    let amount = ctx.accounts.vault_rewards_token_account.amount;
     token::transfer(
         ctx.accounts
             .transfer_from_vault_to_destination_ctx()
             .with_signer(signer),
-        ctx.accounts.vault_rewards_token_account.amount,
+        amount,
     )?;
 
+    emit!(TransferRewardsEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        rewards_mint: ctx.accounts.vault_rewards_token_account.mint,
+        destination_token_account: ctx.accounts.destination_token_account.key(),
+        amount,
+    });
+
     Ok(())
 }
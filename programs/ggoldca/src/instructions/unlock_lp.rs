@@ -0,0 +1,41 @@
+use crate::error::ErrorCode;
+use crate::instructions::{LockUnlockLp, LockUnlockLpEvent};
+use crate::macros::generate_seeds;
+use crate::math::safe_arithmetics::SafeArithmetics;
+use anchor_lang::prelude::*;
+use anchor_spl::token;
+
+pub fn handler(ctx: Context<LockUnlockLp>, lp_amount: u64) -> Result<()> {
+    require!(lp_amount > 0, ErrorCode::ZeroLpAmount);
+    require!(
+        lp_amount <= ctx.accounts.rewards_checkpoint.lp_balance,
+        ErrorCode::NotEnoughLockedLp
+    );
+
+    ctx.accounts.update_rewards()?;
+
+    let checkpoint = &mut ctx.accounts.rewards_checkpoint;
+    checkpoint.lp_balance = checkpoint.lp_balance.safe_sub(lp_amount)?;
+
+    let vault = &mut ctx.accounts.vault_account;
+    vault.rewards_locked_lp = vault.rewards_locked_lp.safe_sub(lp_amount)?;
+
+    let seeds = generate_seeds!(ctx.accounts.vault_account);
+    let signer = &[&seeds[..]];
+
+    token::transfer(
+        ctx.accounts
+            .transfer_from_vault_to_user_ctx()
+            .with_signer(signer),
+        lp_amount,
+    )?;
+
+    emit!(LockUnlockLpEvent {
+        vault_account: ctx.accounts.vault_account.key(),
+        user: ctx.accounts.user_signer.key(),
+        amount: lp_amount,
+        rewards_locked_lp: ctx.accounts.vault_account.rewards_locked_lp,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::error::ErrorCode;
+use crate::instructions::{StakeUnstakeLp, StakeUnstakeLpEvent};
+use crate::macros::generate_farm_seeds;
+use crate::math::safe_arithmetics::SafeArithmetics;
+use anchor_lang::prelude::*;
+use anchor_spl::token;
+
+pub fn handler(ctx: Context<StakeUnstakeLp>, lp_amount: u64) -> Result<()> {
+    require!(lp_amount > 0, ErrorCode::ZeroLpAmount);
+    require!(
+        lp_amount <= ctx.accounts.farm_stake.amount,
+        ErrorCode::NotEnoughStakedLp
+    );
+
+    ctx.accounts.update_rewards()?;
+
+    let farm_stake = &mut ctx.accounts.farm_stake;
+    farm_stake.amount = farm_stake.amount.safe_sub(lp_amount)?;
+
+    let farm = &mut ctx.accounts.farm;
+    farm.total_staked = farm.total_staked.safe_sub(lp_amount)?;
+
+    let seeds = generate_farm_seeds!(ctx.accounts.farm);
+    let signer = &[&seeds[..]];
+
+    token::transfer(
+        ctx.accounts
+            .transfer_from_farm_to_user_ctx()
+            .with_signer(signer),
+        lp_amount,
+    )?;
+
+    emit!(StakeUnstakeLpEvent {
+        farm: ctx.accounts.farm.key(),
+        user: ctx.accounts.user_signer.key(),
+        amount: lp_amount,
+        total_staked: ctx.accounts.farm.total_staked,
+    });
+
+    Ok(())
+}
@@ -1,5 +1,9 @@
 pub mod orca_swap_v2;
+pub mod swap_adapter;
+pub mod token_swap;
 pub mod whirlpool_position;
 
 pub use orca_swap_v2::*;
+pub use swap_adapter::*;
+pub use token_swap::*;
 pub use whirlpool_position::*;
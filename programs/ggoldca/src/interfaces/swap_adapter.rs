@@ -0,0 +1,238 @@
+use crate::error::ErrorCode;
+use crate::interfaces::{orca_swap_v2, token_swap};
+use crate::state::MarketRewards;
+use anchor_lang::prelude::*;
+use anchor_lang_for_whirlpool::{
+    context::CpiContext as CpiContextForWhirlpool, AccountDeserialize,
+};
+use std::borrow::Borrow;
+use whirlpool::math::tick_math::{MAX_SQRT_PRICE_X64, MIN_SQRT_PRICE_X64};
+
+/// Accounts shared by every swap venue
+pub struct SwapAccounts<'a, 'info> {
+    pub token_program: &'a AccountInfo<'info>,
+    /// Owner of the source and destination token accounts
+    pub authority: &'a AccountInfo<'info>,
+    pub source_token_account: &'a AccountInfo<'info>,
+    pub source_mint: Pubkey,
+    pub destination_token_account: &'a AccountInfo<'info>,
+    pub destination_mint: Pubkey,
+    pub swap_program: &'a AccountInfo<'info>,
+    /// Venue specific accounts
+    pub remaining: &'a [AccountInfo<'info>],
+}
+
+/// Swap venue, defining its own account layout and validation
+pub trait SwapAdapter {
+    /// Number of venue specific accounts
+    fn accounts_len(&self) -> usize;
+
+    /// Check the swap program and the venue specific accounts
+    fn validate(&self, accounts: &SwapAccounts) -> Result<()>;
+
+    /// Swap amount_in of the source token into the destination token
+    fn swap(
+        &self,
+        accounts: &SwapAccounts,
+        amount_in: u64,
+        min_amount_out: u64,
+        signer: &[&[&[u8]]],
+    ) -> Result<()>;
+
+    /// Validate the accounts and swap
+    fn validate_and_swap(
+        &self,
+        accounts: &SwapAccounts,
+        amount_in: u64,
+        min_amount_out: u64,
+        signer: &[&[&[u8]]],
+    ) -> Result<()> {
+        require!(
+            accounts.remaining.len() == self.accounts_len(),
+            ErrorCode::InvalidNumberOfAccounts
+        );
+        self.validate(accounts)?;
+        self.swap(accounts, amount_in, min_amount_out, signer)
+    }
+}
+
+/// Swap adapter of the given market
+pub fn swap_adapter(market: MarketRewards) -> Result<&'static dyn SwapAdapter> {
+    match market {
+        MarketRewards::OrcaV2 => Ok(&ORCA_V2_ADAPTER),
+        MarketRewards::Whirlpool => Ok(&WhirlpoolAdapter),
+        MarketRewards::SplTokenSwap => Ok(&SPL_TOKEN_SWAP_ADAPTER),
+        _ => Err(ErrorCode::SwapNotSet.into()),
+    }
+}
+
+const ORCA_V2_ADAPTER: TokenSwapAdapter = TokenSwapAdapter {
+    program_ids: &[orca_swap_v2::ID],
+};
+
+const SPL_TOKEN_SWAP_ADAPTER: TokenSwapAdapter = TokenSwapAdapter {
+    program_ids: &[spl_token_swap::ID, orca_swap_v2::ORCA_SWAP_V1_ID],
+};
+
+/// spl-token-swap based pools.
+/// Accounts: amm_id, amm_authority, pool_source, pool_destination, lp_token_mint, fees_account
+pub struct TokenSwapAdapter {
+    /// Programs allowed to be called
+    pub program_ids: &'static [Pubkey],
+}
+
+impl TokenSwapAdapter {
+    fn swap_ctx<'info>(
+        &self,
+        accounts: &SwapAccounts<'_, 'info>,
+    ) -> CpiContext<'_, '_, '_, 'info, token_swap::Swap<'info>> {
+        CpiContext::new(
+            accounts.swap_program.to_account_info(),
+            token_swap::Swap {
+                token_program: accounts.token_program.to_account_info(),
+                user_account: accounts.authority.to_account_info(),
+                user_token_a_account: accounts.source_token_account.to_account_info(),
+                user_token_b_account: accounts.destination_token_account.to_account_info(),
+                amm_id: accounts.remaining[0].to_account_info(),
+                amm_authority: accounts.remaining[1].to_account_info(),
+                pool_token_a_account: accounts.remaining[2].to_account_info(),
+                pool_token_b_account: accounts.remaining[3].to_account_info(),
+                lp_token_mint: accounts.remaining[4].to_account_info(),
+                fees_account: accounts.remaining[5].to_account_info(),
+            },
+        )
+    }
+}
+
+impl SwapAdapter for TokenSwapAdapter {
+    fn accounts_len(&self) -> usize {
+        6
+    }
+
+    fn validate(&self, accounts: &SwapAccounts) -> Result<()> {
+        require!(
+            self.program_ids.contains(accounts.swap_program.key),
+            ErrorCode::SwapInvalidProgramId
+        );
+        require!(
+            accounts.remaining[0].owner == accounts.swap_program.key,
+            ErrorCode::SwapInvalidProgramId
+        );
+        Ok(())
+    }
+
+    fn swap(
+        &self,
+        accounts: &SwapAccounts,
+        amount_in: u64,
+        min_amount_out: u64,
+        signer: &[&[&[u8]]],
+    ) -> Result<()> {
+        token_swap::swap(
+            self.swap_ctx(accounts).with_signer(signer),
+            amount_in,
+            min_amount_out,
+        )
+    }
+}
+
+/// Whirlpools.
+/// Accounts: whirlpool, token_vault_a, token_vault_b, tick_array_0, tick_array_1, tick_array_2,
+/// oracle
+pub struct WhirlpoolAdapter;
+
+impl WhirlpoolAdapter {
+    fn pool(accounts: &SwapAccounts) -> Result<whirlpool::state::whirlpool::Whirlpool> {
+        let acc_data_slice: &[u8] = &accounts.remaining[0].try_borrow_data()?;
+        Ok(whirlpool::state::whirlpool::Whirlpool::try_deserialize(
+            &mut acc_data_slice.borrow(),
+        )?)
+    }
+
+    fn swap_ctx<'info>(
+        &self,
+        accounts: &SwapAccounts<'_, 'info>,
+        source_is_token_a: bool,
+    ) -> CpiContextForWhirlpool<'_, '_, '_, 'info, whirlpool::cpi::accounts::Swap<'info>> {
+        let (token_owner_account_a, token_owner_account_b) = if source_is_token_a {
+            (
+                accounts.source_token_account.to_account_info(),
+                accounts.destination_token_account.to_account_info(),
+            )
+        } else {
+            (
+                accounts.destination_token_account.to_account_info(),
+                accounts.source_token_account.to_account_info(),
+            )
+        };
+
+        CpiContextForWhirlpool::new(
+            accounts.swap_program.to_account_info(),
+            whirlpool::cpi::accounts::Swap {
+                token_program: accounts.token_program.to_account_info(),
+                token_authority: accounts.authority.to_account_info(),
+                token_owner_account_a,
+                token_owner_account_b,
+                whirlpool: accounts.remaining[0].to_account_info(),
+                token_vault_a: accounts.remaining[1].to_account_info(),
+                token_vault_b: accounts.remaining[2].to_account_info(),
+                tick_array_0: accounts.remaining[3].to_account_info(),
+                tick_array_1: accounts.remaining[4].to_account_info(),
+                tick_array_2: accounts.remaining[5].to_account_info(),
+                oracle: accounts.remaining[6].to_account_info(),
+            },
+        )
+    }
+}
+
+impl SwapAdapter for WhirlpoolAdapter {
+    fn accounts_len(&self) -> usize {
+        7
+    }
+
+    fn validate(&self, accounts: &SwapAccounts) -> Result<()> {
+        require!(
+            accounts.swap_program.key() == whirlpool::ID,
+            ErrorCode::SwapInvalidProgramId
+        );
+
+        let pool = Self::pool(accounts)?;
+        let pool_mints = [pool.token_mint_a, pool.token_mint_b];
+        require!(
+            pool_mints.contains(&accounts.source_mint)
+                && pool_mints.contains(&accounts.destination_mint),
+            ErrorCode::SwapInvalidPool
+        );
+
+        Ok(())
+    }
+
+    fn swap(
+        &self,
+        accounts: &SwapAccounts,
+        amount_in: u64,
+        min_amount_out: u64,
+        signer: &[&[&[u8]]],
+    ) -> Result<()> {
+        let source_is_token_a = accounts.source_mint == Self::pool(accounts)?.token_mint_a;
+
+        let is_swap_from_a_to_b = source_is_token_a;
+        let sqrt_price_limit = if is_swap_from_a_to_b {
+            MIN_SQRT_PRICE_X64
+        } else {
+            MAX_SQRT_PRICE_X64
+        };
+
+        whirlpool::cpi::swap(
+            self.swap_ctx(accounts, source_is_token_a)
+                .with_signer(signer),
+            amount_in,
+            min_amount_out,
+            sqrt_price_limit,
+            true,
+            is_swap_from_a_to_b,
+        )?;
+
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@
 use anchor_lang::prelude::*;
 use error::ErrorCode;
 use instructions::*;
+use state::{KeeperBounty, RangeConfig, RewardsPriceBound, RouteHop};
 
 pub mod error;
 pub mod instructions;
@@ -33,9 +34,23 @@ pub const TREASURY_PUBKEY: Pubkey = Pubkey::new_from_array([
 
 pub const VAULT_ACCOUNT_SEED: &[u8; 5] = b"vault";
 pub const VAULT_LP_TOKEN_MINT_SEED: &[u8; 4] = b"mint";
+pub const REWARDS_CHECKPOINT_SEED: &[u8; 18] = b"rewards_checkpoint";
+pub const FARM_SEED: &[u8; 4] = b"farm";
+pub const FARM_STAKE_SEED: &[u8; 10] = b"farm_stake";
+pub const USER_POSITION_SEED: &[u8; 13] = b"user_position";
+pub const VAULT_HISTORY_SEED: &[u8; 7] = b"history";
 
 pub const FEE_SCALE: u64 = 100;
-pub const VAULT_VERSION: u8 = 1;
+pub const BPS_SCALE: u64 = 10_000;
+/// Rewards to collect at least when a fee is charged, keeping the fee rounding error below 1%
+pub const MIN_REWARDS_AMOUNT: u64 = 100;
+/// Highest fee threshold of set_min_fees, keeping the fees of small vaults collectable
+pub const MAX_MIN_FEES: u64 = 100_000_000;
+/// Longest spacing between rewards swaps, about a day, so the rewards cannot be frozen
+pub const MAX_SLOTS_BETWEEN_SWAPS: u64 = 216_000;
+/// Version of the VaultAccount layout. Any change of VaultAccount::SIZE not absorbed by its
+/// padding requires a new version and its migration in migrate_vault
+pub const VAULT_VERSION: u8 = 2;
 
 pub const IS_PAUSED: bool = false;
 
@@ -54,6 +69,11 @@ pub mod ggoldca {
         instructions::initialize_vault::handler(ctx, id, fee, min_slots_for_reinvest)
     }
 
+    #[access_control(is_admin(ctx.accounts.user_signer.key))]
+    pub fn migrate_vault(ctx: Context<MigrateVault>) -> Result<()> {
+        instructions::migrate_vault::handler(ctx)
+    }
+
     #[access_control(is_admin(ctx.accounts.user_signer.key))]
     pub fn set_vault_pause_status(
         ctx: Context<SetVaultPauseStatus>,
@@ -77,11 +97,21 @@ pub mod ggoldca {
         instructions::open_position::handler(ctx, bump, tick_lower_index, tick_upper_index)
     }
 
+    #[access_control(is_admin(ctx.accounts.user_signer.key))]
+    pub fn open_centered_position(ctx: Context<OpenPosition>, bump: u8) -> Result<()> {
+        instructions::open_centered_position::handler(ctx, bump)
+    }
+
     #[access_control(is_admin(ctx.accounts.user_signer.key))]
     pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
         instructions::close_position::handler(ctx)
     }
 
+    #[access_control(is_admin(ctx.accounts.user_signer.key))]
+    pub fn close_vault<'info>(ctx: Context<'_, '_, '_, 'info, CloseVault<'info>>) -> Result<()> {
+        instructions::close_vault::handler(ctx)
+    }
+
     #[access_control(is_admin(ctx.accounts.user_signer.key))]
     pub fn set_market_rewards(
         ctx: Context<SetMarketRewards>,
@@ -90,11 +120,70 @@ pub mod ggoldca {
         instructions::set_market_rewards::handler(ctx, market_rewards)
     }
 
+    #[access_control(is_admin(ctx.accounts.user_signer.key))]
+    pub fn set_rewards_price_bound(
+        ctx: Context<SetRewardsPriceBound>,
+        price_bound: RewardsPriceBound,
+    ) -> Result<()> {
+        instructions::set_rewards_price_bound::handler(ctx, price_bound)
+    }
+
+    #[access_control(is_admin(ctx.accounts.user_signer.key))]
+    pub fn set_rewards_route(ctx: Context<SetRewardsRoute>, hops: Vec<RouteHop>) -> Result<()> {
+        instructions::set_rewards_route::handler(ctx, hops)
+    }
+
+    #[access_control(is_admin(ctx.accounts.user_signer.key))]
+    pub fn set_rewards_swap_chunking(
+        ctx: Context<SetRewardsSwapChunking>,
+        max_amount_in: u64,
+        min_slots_between_swaps: u64,
+    ) -> Result<()> {
+        instructions::set_rewards_swap_chunking::handler(
+            ctx,
+            max_amount_in,
+            min_slots_between_swaps,
+        )
+    }
+
     #[access_control(is_admin(ctx.accounts.user_signer.key))]
     pub fn set_vault_fee(ctx: Context<SetVaultFee>, fee: u64) -> Result<()> {
         instructions::set_vault_fee::handler(ctx, fee)
     }
 
+    #[access_control(is_admin(ctx.accounts.user_signer.key))]
+    pub fn set_keeper_bounty(
+        ctx: Context<SetKeeperBounty>,
+        keeper_bounty: KeeperBounty,
+    ) -> Result<()> {
+        instructions::set_keeper_bounty::handler(ctx, keeper_bounty)
+    }
+
+    #[access_control(is_admin(ctx.accounts.user_signer.key))]
+    pub fn set_max_price_deviation(
+        ctx: Context<SetMaxPriceDeviation>,
+        max_price_deviation_bps: u16,
+    ) -> Result<()> {
+        instructions::set_max_price_deviation::handler(ctx, max_price_deviation_bps)
+    }
+
+    #[access_control(is_admin(ctx.accounts.user_signer.key))]
+    pub fn set_max_swap_slippage(
+        ctx: Context<SetMaxSwapSlippage>,
+        max_slippage_bps: u16,
+    ) -> Result<()> {
+        instructions::set_max_swap_slippage::handler(ctx, max_slippage_bps)
+    }
+
+    #[access_control(is_admin(ctx.accounts.user_signer.key))]
+    pub fn set_min_fees(
+        ctx: Context<SetMinFees>,
+        min_fees_token_a: u64,
+        min_fees_token_b: u64,
+    ) -> Result<()> {
+        instructions::set_min_fees::handler(ctx, min_fees_token_a, min_fees_token_b)
+    }
+
     #[access_control(is_admin(ctx.accounts.user_signer.key))]
     pub fn set_min_slots_for_reinvest(
         ctx: Context<SetMinSlotsForReinvest>,
@@ -103,10 +192,46 @@ pub mod ggoldca {
         instructions::set_min_slots_for_reinvest::handler(ctx, min_slots)
     }
 
+    #[access_control(is_admin(ctx.accounts.user_signer.key))]
+    pub fn set_range_config(ctx: Context<SetRangeConfig>, range_config: RangeConfig) -> Result<()> {
+        instructions::set_range_config::handler(ctx, range_config)
+    }
+
     #[access_control(is_admin(ctx.accounts.user_signer.key))]
     #[access_control(is_paused(ctx.accounts.vault_account.is_paused))]
-    pub fn rebalance(ctx: Context<Rebalance>) -> Result<()> {
-        instructions::rebalance::handler(ctx)
+    pub fn rebalance<'info>(
+        ctx: Context<'_, '_, '_, 'info, Rebalance<'info>>,
+        min_amount_a: u64,
+        min_amount_b: u64,
+        reference_sqrt_price: u128,
+        max_price_deviation_bps: u16,
+        swap_to_ratio: bool,
+    ) -> Result<()> {
+        instructions::rebalance::handler(
+            ctx,
+            min_amount_a,
+            min_amount_b,
+            reference_sqrt_price,
+            max_price_deviation_bps,
+            swap_to_ratio,
+        )
+    }
+
+    #[access_control(is_admin(ctx.accounts.user_signer.key))]
+    pub fn refresh_price_history(
+        ctx: Context<RefreshPriceHistory>,
+        reference_sqrt_price: u128,
+        max_price_deviation_bps: u16,
+    ) -> Result<()> {
+        instructions::refresh_price_history::handler(
+            ctx,
+            reference_sqrt_price,
+            max_price_deviation_bps,
+        )
+    }
+
+    pub fn init_user_position(ctx: Context<InitUserPosition>) -> Result<()> {
+        instructions::init_user_position::handler(ctx)
     }
 
     #[access_control(is_paused(ctx.accounts.vault_account.is_paused))]
@@ -130,15 +255,35 @@ pub mod ggoldca {
     }
 
     #[access_control(is_paused(ctx.accounts.vault_account.is_paused))]
-    pub fn collect_fees(ctx: Context<CollectFees>) -> Result<()> {
+    pub fn collect_fees<'info>(ctx: Context<'_, '_, '_, 'info, CollectFees<'info>>) -> Result<()> {
         instructions::collect_fees::handler(ctx)
     }
 
     #[access_control(is_paused(ctx.accounts.vault_account.is_paused))]
-    pub fn collect_rewards(ctx: Context<CollectRewards>, reward_index: u8) -> Result<()> {
+    pub fn collect_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, CollectRewards<'info>>,
+        reward_index: u8,
+    ) -> Result<()> {
         instructions::collect_rewards::handler(ctx, reward_index)
     }
 
+    pub fn init_rewards_checkpoint(ctx: Context<InitRewardsCheckpoint>) -> Result<()> {
+        instructions::init_rewards_checkpoint::handler(ctx)
+    }
+
+    #[access_control(is_paused(ctx.accounts.vault_account.is_paused))]
+    pub fn claim_rewards(ctx: Context<ClaimRewards>) -> Result<()> {
+        instructions::claim_rewards::handler(ctx)
+    }
+
+    pub fn lock_lp(ctx: Context<LockUnlockLp>, lp_amount: u64) -> Result<()> {
+        instructions::lock_lp::handler(ctx, lp_amount)
+    }
+
+    pub fn unlock_lp(ctx: Context<LockUnlockLp>, lp_amount: u64) -> Result<()> {
+        instructions::unlock_lp::handler(ctx, lp_amount)
+    }
+
     #[access_control(is_paused(ctx.accounts.vault_account.is_paused))]
     pub fn swap_rewards<'info>(ctx: Context<'_, '_, '_, 'info, SwapRewards<'info>>) -> Result<()> {
         instructions::swap_rewards::handler(ctx)
@@ -150,10 +295,59 @@ pub mod ggoldca {
     }
 
     #[access_control(is_paused(ctx.accounts.vault_account.is_paused))]
-    pub fn reinvest(ctx: Context<Reinvest>) -> Result<()> {
+    pub fn reinvest<'info>(ctx: Context<'_, '_, '_, 'info, Reinvest<'info>>) -> Result<()> {
         instructions::reinvest::handler(ctx)
     }
 
+    #[access_control(is_admin(ctx.accounts.user_signer.key))]
+    pub fn init_vault_history(
+        ctx: Context<InitVaultHistory>,
+        min_slots_between_snapshots: u64,
+    ) -> Result<()> {
+        instructions::init_vault_history::handler(ctx, min_slots_between_snapshots)
+    }
+
+    pub fn get_vault_apy(ctx: Context<GetVaultApy>, start_index: u8, end_index: u8) -> Result<()> {
+        instructions::get_vault_apy::handler(ctx, start_index, end_index)
+    }
+
+    #[access_control(is_paused(ctx.accounts.vault_account.is_paused))]
+    pub fn harvest_and_compound<'info>(
+        ctx: Context<'_, '_, '_, 'info, HarvestAndCompound<'info>>,
+    ) -> Result<()> {
+        instructions::harvest_and_compound::handler(ctx)
+    }
+
+    #[access_control(is_admin(ctx.accounts.user_signer.key))]
+    pub fn create_farm(ctx: Context<CreateFarm>, id: u8, funder: Pubkey) -> Result<()> {
+        instructions::create_farm::handler(ctx, id, funder)
+    }
+
+    pub fn fund_farm(ctx: Context<FundFarm>, amount: u64, duration_slots: u64) -> Result<()> {
+        instructions::fund_farm::handler(ctx, amount, duration_slots)
+    }
+
+    pub fn init_farm_stake(ctx: Context<InitFarmStake>) -> Result<()> {
+        instructions::init_farm_stake::handler(ctx)
+    }
+
+    pub fn stake_lp(ctx: Context<StakeUnstakeLp>, lp_amount: u64) -> Result<()> {
+        instructions::stake_lp::handler(ctx, lp_amount)
+    }
+
+    pub fn unstake_lp(ctx: Context<StakeUnstakeLp>, lp_amount: u64) -> Result<()> {
+        instructions::unstake_lp::handler(ctx, lp_amount)
+    }
+
+    pub fn claim_farm_rewards(ctx: Context<ClaimFarmRewards>) -> Result<()> {
+        instructions::claim_farm_rewards::handler(ctx)
+    }
+
+    #[access_control(is_admin(ctx.accounts.user_signer.key))]
+    pub fn sweep_token(ctx: Context<SweepToken>) -> Result<()> {
+        instructions::sweep_token::handler(ctx)
+    }
+
     #[access_control(is_admin(ctx.accounts.user_signer.key))]
     pub fn set_token_metadata(
         ctx: Context<SetTokenMetadata>,
@@ -10,3 +10,16 @@ macro_rules! generate_seeds {
     };
 }
 pub(crate) use generate_seeds;
+
+/// Generate signed seeds for a farm account
+macro_rules! generate_farm_seeds {
+    ($farm:expr) => {
+        &[
+            "farm".as_ref(),
+            $farm.vault_account.as_ref(),
+            &[$farm.id][..],
+            &[$farm.bump],
+        ]
+    };
+}
+pub(crate) use generate_farm_seeds;
@@ -1,2 +1,18 @@
+pub mod liquidity;
+pub mod performance;
+pub mod price;
+pub mod quote;
+pub mod reward_per_share;
 pub mod safe_arithmetics;
+pub mod tick_range;
+pub mod twap;
+pub mod volatility;
+pub use liquidity::*;
+pub use performance::*;
+pub use price::*;
+pub use quote::*;
+pub use reward_per_share::*;
 pub use safe_arithmetics::*;
+pub use tick_range::*;
+pub use twap::*;
+pub use volatility::*;
@@ -0,0 +1,197 @@
+use crate::error::ErrorCode;
+use crate::math::safe_arithmetics::SafeArithmetics;
+use crate::BPS_SCALE;
+use anchor_lang::prelude::*;
+use whirlpool::math::{bit_math, U256};
+
+/// Number of seconds used to annualize returns
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
+
+/// Value of a single share, as Q64.64
+pub fn value_per_share_x64(value: u128, total_shares: u64) -> Result<u128> {
+    (U256::from(value) << bit_math::Q64_RESOLUTION)
+        .safe_div(U256::from(total_shares))?
+        .try_into_u128()
+        .map_err(|_| error!(ErrorCode::MathOverflowConversion))
+}
+
+/// Value of amount_a_x64 and amount_b_x64 in token b at the given sqrt price, as Q64.64
+pub fn value_in_token_b_x64(
+    amount_a_x64: u128,
+    amount_b_x64: u128,
+    sqrt_price: u128,
+) -> Result<u128> {
+    let amount_a_in_b_x64 = (U256::from(amount_a_x64).safe_mul(U256::from(sqrt_price))?
+        >> bit_math::Q64_RESOLUTION)
+        .safe_mul(U256::from(sqrt_price))?
+        >> bit_math::Q64_RESOLUTION;
+
+    amount_a_in_b_x64
+        .safe_add(U256::from(amount_b_x64))?
+        .try_into_u128()
+        .map_err(|_| error!(ErrorCode::MathOverflowConversion))
+}
+
+/// Ratio between the end and start values, as Q64.64
+fn growth_x64(start_value_x64: u128, end_value_x64: u128) -> Result<u128> {
+    (U256::from(end_value_x64) << bit_math::Q64_RESOLUTION)
+        .safe_div(U256::from(start_value_x64))?
+        .try_into_u128()
+        .map_err(|_| error!(ErrorCode::MathOverflowConversion))
+}
+
+/// Return of a Q64.64 growth, in bps (negative for losses)
+fn return_bps(growth_x64: u128) -> Result<i128> {
+    let one_x64 = 1_i128 << bit_math::Q64_RESOLUTION;
+    let growth_x64 = i128::try_from(growth_x64).map_err(|_| ErrorCode::MathOverflowConversion)?;
+
+    Ok(growth_x64
+        .checked_sub(one_x64)
+        .ok_or(ErrorCode::MathOverflowSub)?
+        .checked_mul(i128::from(BPS_SCALE))
+        .ok_or(ErrorCode::MathOverflowMul)?
+        >> bit_math::Q64_RESOLUTION)
+}
+
+/// Simple annualized return between two values per share, in bps
+pub fn apr_bps(start_value_x64: u128, end_value_x64: u128, elapsed_seconds: u64) -> Result<i64> {
+    require!(elapsed_seconds > 0, ErrorCode::MathZeroDivision);
+
+    let delta_x64 = if end_value_x64 >= start_value_x64 {
+        end_value_x64 - start_value_x64
+    } else {
+        start_value_x64 - end_value_x64
+    };
+
+    let apr_bps = U256::from(delta_x64)
+        .safe_mul(U256::from(BPS_SCALE))?
+        .safe_mul(U256::from(SECONDS_PER_YEAR))?
+        .safe_div(U256::from(start_value_x64).safe_mul(U256::from(elapsed_seconds))?)?
+        .try_into_u128()
+        .map_err(|_| ErrorCode::MathOverflowConversion)?;
+    let apr_bps = i64::try_from(apr_bps).map_err(|_| ErrorCode::MathOverflowConversion)?;
+
+    if end_value_x64 >= start_value_x64 {
+        Ok(apr_bps)
+    } else {
+        Ok(-apr_bps)
+    }
+}
+
+/// Annualized return between two values per share, compounding the growth once per elapsed
+/// period, in bps. Periods longer than a year are not compounded
+pub fn apy_bps(start_value_x64: u128, end_value_x64: u128, elapsed_seconds: u64) -> Result<i64> {
+    require!(elapsed_seconds > 0, ErrorCode::MathZeroDivision);
+
+    if elapsed_seconds >= SECONDS_PER_YEAR {
+        return apr_bps(start_value_x64, end_value_x64, elapsed_seconds);
+    }
+
+    let periods = SECONDS_PER_YEAR / elapsed_seconds;
+    let annual_growth_x64 = pow_x64(growth_x64(start_value_x64, end_value_x64)?, periods)?;
+
+    i64::try_from(return_bps(annual_growth_x64)?)
+        .map_err(|_| error!(ErrorCode::MathOverflowConversion))
+}
+
+/// base^exponent, both as Q64.64
+fn pow_x64(base_x64: u128, mut exponent: u64) -> Result<u128> {
+    let mut base = U256::from(base_x64);
+    let mut result = U256::from(1_u128 << bit_math::Q64_RESOLUTION);
+
+    let max = U256::from(u128::MAX);
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result.safe_mul(base)? >> bit_math::Q64_RESOLUTION;
+            require!(result <= max, ErrorCode::MathOverflowMul);
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            base = base.safe_mul(base)? >> bit_math::Q64_RESOLUTION;
+            require!(base <= max, ErrorCode::MathOverflowMul);
+        }
+    }
+
+    result
+        .try_into_u128()
+        .map_err(|_| error!(ErrorCode::MathOverflowConversion))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const ONE_X64: u128 = 1 << 64;
+
+    #[test]
+    fn test_value_per_share_x64() {
+        assert_eq!(value_per_share_x64(1_000, 1_000).unwrap(), ONE_X64);
+        assert_eq!(value_per_share_x64(1_500, 1_000).unwrap(), ONE_X64 * 3 / 2);
+        assert!(value_per_share_x64(1_000, 0).is_err());
+    }
+
+    #[test]
+    fn test_value_in_token_b_x64() {
+        // price = 1
+        assert_eq!(
+            value_in_token_b_x64(ONE_X64, ONE_X64, ONE_X64).unwrap(),
+            2 * ONE_X64
+        );
+        // price = 4
+        assert_eq!(
+            value_in_token_b_x64(3 * ONE_X64, ONE_X64, 2 * ONE_X64).unwrap(),
+            13 * ONE_X64
+        );
+        // price = 0.25
+        assert_eq!(
+            value_in_token_b_x64(4 * ONE_X64, 0, ONE_X64 / 2).unwrap(),
+            ONE_X64
+        );
+        assert!(value_in_token_b_x64(u128::MAX, 0, 2 * ONE_X64).is_err());
+    }
+
+    #[test]
+    fn test_apr_bps() {
+        let start = 100 * ONE_X64;
+        let end = 101 * ONE_X64;
+
+        // 1% in a year
+        assert_eq!(apr_bps(start, end, SECONDS_PER_YEAR).unwrap(), 100);
+        // 1% in a month
+        assert_eq!(apr_bps(start, end, SECONDS_PER_YEAR / 12).unwrap(), 1_200);
+        // -1% in a year
+        assert_eq!(
+            apr_bps(start, 99 * ONE_X64, SECONDS_PER_YEAR).unwrap(),
+            -100
+        );
+
+        assert_eq!(apr_bps(start, start, 1).unwrap(), 0);
+        assert!(apr_bps(start, end, 0).is_err());
+    }
+
+    #[test]
+    fn test_apy_bps() {
+        let start = 100 * ONE_X64;
+        let end = 101 * ONE_X64;
+
+        // No compounding over a year or more
+        assert_eq!(apy_bps(start, end, SECONDS_PER_YEAR).unwrap(), 100);
+        assert_eq!(apy_bps(start, end, 2 * SECONDS_PER_YEAR).unwrap(), 50);
+
+        // 1.01^12 - 1 = 12.68%
+        assert_eq!(apy_bps(start, end, SECONDS_PER_YEAR / 12).unwrap(), 1_268);
+        // 0.99^12 - 1 = -11.36%
+        let end = 99 * ONE_X64;
+        assert_eq!(apy_bps(start, end, SECONDS_PER_YEAR / 12).unwrap(), -1_137);
+
+        assert_eq!(apy_bps(start, start, 1).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_pow_x64() {
+        assert_eq!(pow_x64(2 * ONE_X64, 0).unwrap(), ONE_X64);
+        assert_eq!(pow_x64(2 * ONE_X64, 10).unwrap(), 1_024 * ONE_X64);
+        assert_eq!(pow_x64(ONE_X64 / 2, 3).unwrap(), ONE_X64 / 8);
+        assert!(pow_x64(2 * ONE_X64, 64).is_err());
+    }
+}
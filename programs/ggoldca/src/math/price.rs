@@ -0,0 +1,167 @@
+use crate::error::ErrorCode;
+use crate::math::safe_arithmetics::SafeArithmetics;
+use crate::BPS_SCALE;
+use anchor_lang::prelude::*;
+use whirlpool::math::{
+    bit_math,
+    tick_math::{MAX_SQRT_PRICE_X64, MIN_SQRT_PRICE_X64},
+    U256,
+};
+
+/// Check the price doesn't deviate more than max_deviation_bps from the reference price
+pub fn is_price_within_deviation(
+    sqrt_price: u128,
+    reference_sqrt_price: u128,
+    max_deviation_bps: u16,
+) -> Result<bool> {
+    let price_x128 = U256::from(sqrt_price).safe_mul(U256::from(sqrt_price))?;
+    let reference_price_x128 =
+        U256::from(reference_sqrt_price).safe_mul(U256::from(reference_sqrt_price))?;
+
+    let delta_x128 = if price_x128 > reference_price_x128 {
+        price_x128.safe_sub(reference_price_x128)?
+    } else {
+        reference_price_x128.safe_sub(price_x128)?
+    };
+
+    Ok(delta_x128.safe_mul(U256::from(BPS_SCALE))?
+        <= reference_price_x128.safe_mul(U256::from(max_deviation_bps))?)
+}
+
+/// Value of amount_a in token b at the given price
+pub fn amount_a_to_b(amount_a: u64, sqrt_price: u128) -> Result<u128> {
+    let sqrt_price = U256::from(sqrt_price);
+
+    let amount_x64 = U256::from(amount_a).safe_mul(sqrt_price)? >> bit_math::Q64_RESOLUTION;
+    let amount_b = amount_x64.safe_mul(sqrt_price)? >> bit_math::Q64_RESOLUTION;
+
+    amount_b
+        .try_into_u128()
+        .map_err(|_| error!(ErrorCode::MathOverflowConversion))
+}
+
+/// Value of amount_b in token a at the given price
+pub fn amount_b_to_a(amount_b: u64, sqrt_price: u128) -> Result<u128> {
+    let sqrt_price = U256::from(sqrt_price);
+
+    let amount_x64 = (U256::from(amount_b) << bit_math::Q64_RESOLUTION).safe_div(sqrt_price)?;
+    let amount_a = (amount_x64 << bit_math::Q64_RESOLUTION).safe_div(sqrt_price)?;
+
+    amount_a
+        .try_into_u128()
+        .map_err(|_| error!(ErrorCode::MathOverflowConversion))
+}
+
+/// Value of amount at the given price (Q64.64)
+pub fn amount_at_price(amount: u64, price_x64: u128) -> Result<u128> {
+    let value = U256::from(amount).safe_mul(U256::from(price_x64))? >> bit_math::Q64_RESOLUTION;
+
+    value
+        .try_into_u128()
+        .map_err(|_| error!(ErrorCode::MathOverflowConversion))
+}
+
+/// Sqrt price of the price obtained multiplying the current one by ratio_bps / BPS_SCALE
+pub fn sqrt_price_from_price_ratio(sqrt_price: u128, ratio_bps: u64) -> Result<u128> {
+    let new_sqrt_price = U256::from(sqrt_price)
+        .safe_mul(U256::from(sqrt_price))?
+        .safe_mul(U256::from(ratio_bps))?
+        .safe_div(U256::from(BPS_SCALE))?
+        .integer_sqrt()
+        .try_into_u128()
+        .map_err(|_| error!(ErrorCode::MathOverflowConversion))?;
+
+    Ok(new_sqrt_price.clamp(MIN_SQRT_PRICE_X64, MAX_SQRT_PRICE_X64))
+}
+
+/// Sqrt price limit for a swap not moving the price more than max_deviation_bps
+pub fn sqrt_price_limit(sqrt_price: u128, max_deviation_bps: u16, a_to_b: bool) -> Result<u128> {
+    let max_deviation_bps = u64::from(max_deviation_bps);
+
+    // Swapping from a to b reduces the price
+    let ratio_bps = if a_to_b {
+        BPS_SCALE.safe_sub(max_deviation_bps)?
+    } else {
+        BPS_SCALE.safe_add(max_deviation_bps)?
+    };
+
+    sqrt_price_from_price_ratio(sqrt_price, ratio_bps)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use whirlpool::math::tick_math::sqrt_price_from_tick_index;
+
+    #[test]
+    fn test_is_price_within_deviation() {
+        let reference = 1_u128 << 64;
+
+        // price = 1.0201
+        let sqrt_price = reference * 101 / 100;
+        assert!(is_price_within_deviation(sqrt_price, reference, 202).unwrap());
+        assert!(!is_price_within_deviation(sqrt_price, reference, 200).unwrap());
+
+        // price = 0.9801
+        let sqrt_price = reference * 99 / 100;
+        assert!(is_price_within_deviation(sqrt_price, reference, 200).unwrap());
+        assert!(!is_price_within_deviation(sqrt_price, reference, 198).unwrap());
+    }
+
+    #[test]
+    fn test_is_price_within_deviation_ticks() {
+        // 100 ticks ~ 1.005% price change
+        let reference = sqrt_price_from_tick_index(-5_000);
+        let sqrt_price = sqrt_price_from_tick_index(-4_900);
+
+        assert!(is_price_within_deviation(sqrt_price, reference, 101).unwrap());
+        assert!(!is_price_within_deviation(sqrt_price, reference, 100).unwrap());
+        assert!(is_price_within_deviation(reference, reference, 0).unwrap());
+    }
+
+    #[test]
+    fn test_amount_a_to_b() {
+        assert_eq!(amount_a_to_b(1_000, 1 << 64).unwrap(), 1_000);
+        assert_eq!(amount_a_to_b(1_000, 1 << 65).unwrap(), 4_000);
+        assert_eq!(amount_a_to_b(1_000, 1 << 63).unwrap(), 250);
+        assert_eq!(amount_a_to_b(999, 1 << 63).unwrap(), 249);
+    }
+
+    #[test]
+    fn test_amount_b_to_a() {
+        assert_eq!(amount_b_to_a(1_000, 1 << 64).unwrap(), 1_000);
+        assert_eq!(amount_b_to_a(4_000, 1 << 65).unwrap(), 1_000);
+        assert_eq!(amount_b_to_a(250, 1 << 63).unwrap(), 1_000);
+        assert_eq!(amount_b_to_a(999, 1 << 65).unwrap(), 249);
+
+        let sqrt_price = sqrt_price_from_tick_index(12_345);
+        let amount_b = amount_a_to_b(1_000_000, sqrt_price).unwrap();
+        let amount_a = amount_b_to_a(amount_b as u64, sqrt_price).unwrap();
+        assert!((999_990..=1_000_000).contains(&amount_a));
+    }
+
+    #[test]
+    fn test_amount_at_price() {
+        assert_eq!(amount_at_price(1_000, 1 << 64).unwrap(), 1_000);
+        assert_eq!(amount_at_price(1_000, 3 << 63).unwrap(), 1_500);
+        assert_eq!(amount_at_price(1_000, 1 << 60).unwrap(), 62);
+        assert_eq!(
+            amount_at_price(u64::MAX, u128::MAX).unwrap(),
+            (u128::from(u64::MAX) * u128::from(u64::MAX)) + u128::from(u64::MAX) - 1
+        );
+    }
+
+    #[test]
+    fn test_sqrt_price_limit() {
+        let sqrt_price = sqrt_price_from_tick_index(1_000);
+
+        let lower_limit = sqrt_price_limit(sqrt_price, 100, true).unwrap();
+        let upper_limit = sqrt_price_limit(sqrt_price, 100, false).unwrap();
+
+        assert!(lower_limit < sqrt_price && upper_limit > sqrt_price);
+        assert!(is_price_within_deviation(lower_limit, sqrt_price, 101).unwrap());
+        assert!(is_price_within_deviation(upper_limit, sqrt_price, 100).unwrap());
+        assert!(!is_price_within_deviation(lower_limit, sqrt_price, 99).unwrap());
+        assert!(!is_price_within_deviation(upper_limit, sqrt_price, 99).unwrap());
+    }
+}
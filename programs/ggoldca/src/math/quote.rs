@@ -0,0 +1,420 @@
+//! Deposit, withdraw and reinvest math of the vault instructions, free of
+//! account state so that the same quotes can be computed off-chain
+use crate::error::ErrorCode;
+use crate::math::liquidity::token_amounts_from_liquidity;
+use crate::math::price::{amount_a_to_b, amount_b_to_a, sqrt_price_limit};
+use crate::math::safe_arithmetics::{SafeArithmetics, SafeMulDiv};
+use crate::BPS_SCALE;
+use anchor_lang::prelude::*;
+use whirlpool::math::{
+    bit_math,
+    tick_math::{MAX_SQRT_PRICE_X64, MIN_SQRT_PRICE_X64},
+    U256,
+};
+
+/// Liquidity added to the position and vault balances paid by a deposit
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DepositQuote {
+    pub liquidity: u128,
+    pub vault_amount_a: u64,
+    pub vault_amount_b: u64,
+}
+
+impl DepositQuote {
+    /// Total token amounts paid by the user, the position share rounded up as whirlpool does
+    pub fn total_amounts(
+        &self,
+        curr_sqrt_price: u128,
+        curr_tick: i32,
+        lower_tick: i32,
+        upper_tick: i32,
+    ) -> Result<(u64, u64)> {
+        let (position_amount_a, position_amount_b) = token_amounts_from_liquidity(
+            curr_sqrt_price,
+            curr_tick,
+            lower_tick,
+            upper_tick,
+            self.liquidity,
+            true,
+        )?;
+
+        Ok((
+            position_amount_a.safe_add(self.vault_amount_a)?,
+            position_amount_b.safe_add(self.vault_amount_b)?,
+        ))
+    }
+}
+
+/// Deposit of lp_amount. Rounded up in favour of the vault
+pub fn deposit_quote(
+    lp_amount: u64,
+    lp_supply: u64,
+    position_liquidity: u128,
+    vault_amount_a: u64,
+    vault_amount_b: u64,
+) -> Result<DepositQuote> {
+    if lp_supply == 0 {
+        return Ok(DepositQuote {
+            liquidity: u128::from(lp_amount),
+            vault_amount_a: 0,
+            vault_amount_b: 0,
+        });
+    }
+
+    Ok(DepositQuote {
+        liquidity: position_liquidity
+            .safe_mul_div_round_up(u128::from(lp_amount), u128::from(lp_supply))?,
+        vault_amount_a: vault_amount_a.safe_mul_div_round_up(lp_amount, lp_supply)?,
+        vault_amount_b: vault_amount_b.safe_mul_div_round_up(lp_amount, lp_supply)?,
+    })
+}
+
+/// Liquidity removed from the position and vault balances received by a withdrawal
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawQuote {
+    pub liquidity: u128,
+    pub vault_amount_a: u64,
+    pub vault_amount_b: u64,
+}
+
+impl WithdrawQuote {
+    /// Total token amounts received by the user, the position share rounded down as whirlpool does
+    pub fn total_amounts(
+        &self,
+        curr_sqrt_price: u128,
+        curr_tick: i32,
+        lower_tick: i32,
+        upper_tick: i32,
+    ) -> Result<(u64, u64)> {
+        let (position_amount_a, position_amount_b) = token_amounts_from_liquidity(
+            curr_sqrt_price,
+            curr_tick,
+            lower_tick,
+            upper_tick,
+            self.liquidity,
+            false,
+        )?;
+
+        Ok((
+            position_amount_a.safe_add(self.vault_amount_a)?,
+            position_amount_b.safe_add(self.vault_amount_b)?,
+        ))
+    }
+}
+
+/// Withdrawal of lp_amount. Rounded down in favour of the vault. The liquidity
+/// of the last reinvestment is excluded, so that it cannot be sandwiched
+pub fn withdraw_quote(
+    lp_amount: u64,
+    lp_supply: u64,
+    position_liquidity: u128,
+    last_liquidity_increase: u128,
+    vault_amount_a: u64,
+    vault_amount_b: u64,
+) -> Result<WithdrawQuote> {
+    let past_liquidity = position_liquidity.safe_sub(last_liquidity_increase)?;
+
+    Ok(WithdrawQuote {
+        liquidity: past_liquidity.safe_mul_div(u128::from(lp_amount), u128::from(lp_supply))?,
+        vault_amount_a: vault_amount_a.safe_mul_div(lp_amount, lp_supply)?,
+        vault_amount_b: vault_amount_b.safe_mul_div(lp_amount, lp_supply)?,
+    })
+}
+
+/// Swap to the position ratio of a reinvestment
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ReinvestQuote {
+    pub swap: SwapParams,
+}
+
+/// Reinvestment of the vault balances. The position amounts are the ones of its
+/// current liquidity. The liquidity finally deposited depends on the swap output,
+/// est_liquidity_from_token_amounts gives it from the post-swap balances
+pub fn reinvest_quote(
+    vault_amount_a: u64,
+    vault_amount_b: u64,
+    position_amount_a: u64,
+    position_amount_b: u64,
+    sqrt_price: u128,
+    max_swap_slippage_bps: u16,
+) -> Result<ReinvestQuote> {
+    let swap = swap_params_for_ratio(
+        vault_amount_a,
+        vault_amount_b,
+        position_amount_a,
+        position_amount_b,
+        sqrt_price,
+    )?
+    .with_max_slippage(sqrt_price, max_swap_slippage_bps)?;
+
+    Ok(ReinvestQuote { swap })
+}
+
+/// Parameters of a whirlpool swap
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SwapParams {
+    pub amount: u64,
+    pub other_amount_threshold: u64,
+    pub sqrt_price_limit: u128,
+    pub amount_specified_is_input: bool,
+    pub a_to_b: bool,
+}
+
+impl SwapParams {
+    /// Bound the swap execution to max_slippage_bps from the pre-swap price
+    pub fn with_max_slippage(mut self, sqrt_price: u128, max_slippage_bps: u16) -> Result<Self> {
+        let slippage_bps = u64::from(max_slippage_bps);
+
+        let max_slippage_factor = u128::from(BPS_SCALE.safe_add(slippage_bps)?);
+        let min_slippage_factor = u128::from(BPS_SCALE.safe_sub(slippage_bps)?);
+
+        let other_amount_threshold = match (self.a_to_b, self.amount_specified_is_input) {
+            // Input of token_a, the A to B swaps always specify it. Minimum amount of token_b
+            // to receive
+            (true, _) => amount_a_to_b(self.amount, sqrt_price)?
+                .safe_mul_div(min_slippage_factor, u128::from(BPS_SCALE))?,
+            // Input of token_b, minimum amount of token_a to receive
+            (false, true) => amount_b_to_a(self.amount, sqrt_price)?
+                .safe_mul_div(min_slippage_factor, u128::from(BPS_SCALE))?,
+            // Output of token_a, maximum amount of token_b to pay
+            (false, false) => amount_a_to_b(self.amount, sqrt_price)?
+                .safe_mul_div_round_up(max_slippage_factor, u128::from(BPS_SCALE))?,
+        };
+
+        self.other_amount_threshold = u64::try_from(other_amount_threshold).unwrap_or(u64::MAX);
+        self.sqrt_price_limit = sqrt_price_limit(sqrt_price, max_slippage_bps, self.a_to_b)?;
+
+        Ok(self)
+    }
+}
+
+/// Swap needed to match the position ratio. Formula described in docs/math.tex
+pub fn swap_params_for_ratio(
+    amount_a: u64,
+    amount_b: u64,
+    position_amount_a: u64,
+    position_amount_b: u64,
+    sqrt_price: u128,
+) -> Result<SwapParams> {
+    if position_amount_a == 0 {
+        return Ok(swap_params_from_a_to_b(amount_a));
+    } else if position_amount_b == 0 {
+        return Ok(swap_params_from_exact_b_to_a(amount_b));
+    }
+
+    let price_x128 = U256::from(sqrt_price).pow(2.into());
+
+    let ratio_x64 = (1_u128 << bit_math::Q64_RESOLUTION)
+        .safe_mul_div(position_amount_a.into(), position_amount_b.into())?;
+
+    let ratio_times_price_x192 = U256::from(ratio_x64)
+        .checked_mul(price_x128)
+        .ok_or(ErrorCode::MathOverflowMul)?;
+
+    let ratio_amount_b_x64 = ratio_x64.safe_mul(amount_b.into())?;
+    let amount_a_x64 = u128::from(amount_a) << bit_math::Q64_RESOLUTION;
+
+    let is_delta_a_positive = amount_a_x64 > ratio_amount_b_x64;
+
+    let numerator_x64 = if is_delta_a_positive {
+        amount_a_x64.safe_sub(ratio_amount_b_x64)?
+    } else {
+        ratio_amount_b_x64.safe_sub(amount_a_x64)?
+    };
+
+    let numerator_x192 =
+        U256::from(numerator_x64) << bit_math::Q64_RESOLUTION << bit_math::Q64_RESOLUTION;
+
+    let denominator_x192 = (U256::from(1)
+        << bit_math::Q64_RESOLUTION
+        << bit_math::Q64_RESOLUTION
+        << bit_math::Q64_RESOLUTION)
+        .checked_add(ratio_times_price_x192)
+        .ok_or(ErrorCode::MathOverflowAdd)?;
+
+    let amount_to_swap: u64 = numerator_x192
+        .checked_div(denominator_x192)
+        .ok_or(ErrorCode::MathZeroDivision)?
+        .try_into()
+        .map_err(|_| error!(ErrorCode::MathOverflowConversion))?;
+
+    if is_delta_a_positive {
+        Ok(swap_params_from_a_to_b(amount_to_swap))
+    } else {
+        Ok(swap_params_from_b_to_a(amount_to_swap))
+    }
+}
+
+fn swap_params_from_a_to_b(amount_a: u64) -> SwapParams {
+    SwapParams {
+        amount: amount_a,
+        other_amount_threshold: 1,
+        sqrt_price_limit: MIN_SQRT_PRICE_X64,
+        amount_specified_is_input: true,
+        a_to_b: true,
+    }
+}
+
+fn swap_params_from_b_to_a(amount_a: u64) -> SwapParams {
+    SwapParams {
+        amount: amount_a,
+        other_amount_threshold: u64::MAX,
+        sqrt_price_limit: MAX_SQRT_PRICE_X64,
+        amount_specified_is_input: false,
+        a_to_b: false,
+    }
+}
+
+fn swap_params_from_exact_b_to_a(amount_b: u64) -> SwapParams {
+    SwapParams {
+        amount: amount_b,
+        other_amount_threshold: 1,
+        sqrt_price_limit: MAX_SQRT_PRICE_X64,
+        amount_specified_is_input: true,
+        a_to_b: false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::math::liquidity::est_liquidity_from_token_amounts;
+    use whirlpool::math::tick_math::sqrt_price_from_tick_index;
+
+    #[test]
+    fn test_deposit_quote_first_deposit() {
+        let quote = deposit_quote(1_000, 0, 0, 0, 0).unwrap();
+        assert_eq!(
+            quote,
+            DepositQuote {
+                liquidity: 1_000,
+                vault_amount_a: 0,
+                vault_amount_b: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_deposit_quote_rounds_up() {
+        let quote = deposit_quote(1, 3, 10, 10, 0).unwrap();
+        assert_eq!(
+            quote,
+            DepositQuote {
+                liquidity: 4,
+                vault_amount_a: 4,
+                vault_amount_b: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_withdraw_quote_rounds_down() {
+        let quote = withdraw_quote(1, 3, 10, 0, 10, 0).unwrap();
+        assert_eq!(
+            quote,
+            WithdrawQuote {
+                liquidity: 3,
+                vault_amount_a: 3,
+                vault_amount_b: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_withdraw_quote_excludes_last_liquidity_increase() {
+        let quote = withdraw_quote(500, 1_000, 1_200, 200, 0, 0).unwrap();
+        assert_eq!(quote.liquidity, 500);
+
+        assert!(withdraw_quote(500, 1_000, 100, 200, 0, 0).is_err());
+    }
+
+    #[test]
+    fn test_deposit_withdraw_round_trip() {
+        let sqrt_price = sqrt_price_from_tick_index(0);
+
+        let deposit = deposit_quote(333, 1_000, 2_693_896, 1_001, 7).unwrap();
+        let withdraw = withdraw_quote(
+            333,
+            1_333,
+            2_693_896 + deposit.liquidity,
+            0,
+            1_001 + deposit.vault_amount_a,
+            7 + deposit.vault_amount_b,
+        )
+        .unwrap();
+
+        let (deposit_a, deposit_b) = deposit.total_amounts(sqrt_price, 0, -1280, 1280).unwrap();
+        let (withdraw_a, withdraw_b) = withdraw.total_amounts(sqrt_price, 0, -1280, 1280).unwrap();
+
+        // The user never receives more than deposited
+        assert!(withdraw_a <= deposit_a);
+        assert!(withdraw_b <= deposit_b);
+    }
+
+    #[test]
+    fn test_total_amounts_match_liquidity_amounts() {
+        let sqrt_price = sqrt_price_from_tick_index(0);
+        let liquidity =
+            est_liquidity_from_token_amounts(sqrt_price, 0, -1280, 1280, 167_000, 167_000).unwrap();
+
+        let deposit = DepositQuote {
+            liquidity,
+            vault_amount_a: 10,
+            vault_amount_b: 20,
+        };
+        let (position_a, position_b) =
+            token_amounts_from_liquidity(sqrt_price, 0, -1280, 1280, liquidity, true).unwrap();
+        assert_eq!(
+            deposit.total_amounts(sqrt_price, 0, -1280, 1280).unwrap(),
+            (position_a + 10, position_b + 20)
+        );
+
+        let withdraw = WithdrawQuote {
+            liquidity,
+            vault_amount_a: 10,
+            vault_amount_b: 20,
+        };
+        let (position_a, position_b) =
+            token_amounts_from_liquidity(sqrt_price, 0, -1280, 1280, liquidity, false).unwrap();
+        assert_eq!(
+            withdraw.total_amounts(sqrt_price, 0, -1280, 1280).unwrap(),
+            (position_a + 10, position_b + 20)
+        );
+    }
+
+    #[test]
+    fn test_reinvest_quote() {
+        let sqrt_price = 1_u128 << 64;
+
+        let quote = reinvest_quote(995, 1_980, 100, 100, sqrt_price, 50).unwrap();
+
+        let expected_swap = swap_params_for_ratio(995, 1_980, 100, 100, sqrt_price)
+            .unwrap()
+            .with_max_slippage(sqrt_price, 50)
+            .unwrap();
+        assert_eq!(quote.swap, expected_swap);
+
+        // Half of the excess of token_b is swapped at price 1
+        assert!(!quote.swap.a_to_b);
+        assert_eq!(quote.swap.amount, 492);
+    }
+
+    #[test]
+    fn test_reinvest_quote_single_sided() {
+        let sqrt_price = 1_u128 << 64;
+
+        // Position of token_a only, all token_b is swapped as input
+        let quote = reinvest_quote(10, 1_000, 100, 0, sqrt_price, 50).unwrap();
+        assert!(!quote.swap.a_to_b);
+        assert!(quote.swap.amount_specified_is_input);
+        assert_eq!(quote.swap.amount, 1_000);
+        assert_eq!(quote.swap.other_amount_threshold, 995);
+
+        // Position of token_b only, all token_a is swapped as input
+        let quote = reinvest_quote(1_000, 10, 0, 100, sqrt_price, 50).unwrap();
+        assert!(quote.swap.a_to_b);
+        assert!(quote.swap.amount_specified_is_input);
+        assert_eq!(quote.swap.amount, 1_000);
+        assert_eq!(quote.swap.other_amount_threshold, 995);
+    }
+}
@@ -0,0 +1,53 @@
+use crate::error::ErrorCode;
+use crate::math::safe_arithmetics::{SafeArithmetics, SafeMulDiv};
+use anchor_lang::prelude::*;
+use whirlpool::math::bit_math;
+
+/// Increase of the reward per share (Q64.64) when distributing amount among total_shares.
+/// Nothing can be distributed without shares
+pub fn reward_per_share_increase(amount: u64, total_shares: u64) -> Result<u128> {
+    if total_shares == 0 {
+        return Ok(0);
+    }
+
+    (u128::from(amount) << bit_math::Q64_RESOLUTION).safe_div(u128::from(total_shares))
+}
+
+/// Rewards accrued by shares since the reward per share increased by reward_per_share_delta_x64
+pub fn accrued_rewards(shares: u64, reward_per_share_delta_x64: u128) -> Result<u64> {
+    u128::from(shares)
+        .safe_mul_div(reward_per_share_delta_x64, 1 << bit_math::Q64_RESOLUTION)?
+        .try_into()
+        .map_err(|_| error!(ErrorCode::MathOverflowConversion))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reward_per_share_increase() {
+        assert_eq!(reward_per_share_increase(1_000, 0).unwrap(), 0);
+        assert_eq!(reward_per_share_increase(1_000, 1_000).unwrap(), 1 << 64);
+        assert_eq!(reward_per_share_increase(1_000, 4_000).unwrap(), 1 << 62);
+        assert_eq!(
+            reward_per_share_increase(u64::MAX, 1).unwrap(),
+            u128::from(u64::MAX) << 64
+        );
+    }
+
+    #[test]
+    fn test_accrued_rewards() {
+        let delta = reward_per_share_increase(1_000, 3_000).unwrap();
+
+        // Distributed amounts are rounded down
+        assert_eq!(accrued_rewards(1_000, delta).unwrap(), 333);
+        assert_eq!(accrued_rewards(2_000, delta).unwrap(), 666);
+        assert_eq!(accrued_rewards(3_000, delta).unwrap(), 999);
+        assert_eq!(accrued_rewards(0, delta).unwrap(), 0);
+
+        let delta = reward_per_share_increase(u64::MAX, 1).unwrap();
+        assert_eq!(accrued_rewards(1, delta).unwrap(), u64::MAX);
+        assert!(accrued_rewards(2, delta).is_err());
+    }
+}
@@ -34,6 +34,7 @@ macro_rules! impl_safe_arithmetics {
     };
 }
 
+impl_safe_arithmetics!(i64);
 impl_safe_arithmetics!(u64);
 impl_safe_arithmetics!(u128);
 impl_safe_arithmetics!(U256);
@@ -0,0 +1,190 @@
+use crate::error::ErrorCode;
+use crate::math::price::sqrt_price_from_price_ratio;
+use crate::math::safe_arithmetics::{SafeArithmetics, SafeMulDiv};
+use crate::state::{RangeConfig, RangeWidthKind};
+use crate::BPS_SCALE;
+use anchor_lang::prelude::*;
+use whirlpool::math::tick_math;
+use whirlpool::state::tick::{MAX_TICK_INDEX, MIN_TICK_INDEX};
+
+/// Check the ticks define a valid position range for the given tick spacing
+pub fn validate_tick_range(tick_lower: i32, tick_upper: i32, tick_spacing: u16) -> Result<()> {
+    require!(tick_lower < tick_upper, ErrorCode::InvalidTickRange);
+    require!(
+        tick_lower >= MIN_TICK_INDEX && tick_upper <= MAX_TICK_INDEX,
+        ErrorCode::TicksOutOfBounds
+    );
+
+    let tick_spacing = i32::from(tick_spacing);
+    require!(
+        tick_lower % tick_spacing == 0 && tick_upper % tick_spacing == 0,
+        ErrorCode::TicksNotAligned
+    );
+
+    Ok(())
+}
+
+/// Compute the aligned range around the current price following the vault range config.
+/// The volatility (in ticks) is only required when the width is derived from it.
+pub fn centered_tick_range(
+    curr_sqrt_price: u128,
+    curr_tick: i32,
+    tick_spacing: u16,
+    config: &RangeConfig,
+    volatility: Option<u64>,
+) -> Result<(i32, i32)> {
+    let (tick_lower, tick_upper) = match config.width_kind {
+        RangeWidthKind::NotSet => return Err(ErrorCode::RangeConfigNotSet.into()),
+        RangeWidthKind::Ticks => ticks_around(curr_tick, config.split_width(config.width.into())),
+        RangeWidthKind::Bps => {
+            let (lower_width, upper_width) = config.split_width(config.width.into());
+            (
+                tick_from_price_ratio(curr_sqrt_price, BPS_SCALE.safe_sub(lower_width)?)?,
+                tick_from_price_ratio(curr_sqrt_price, BPS_SCALE.safe_add(upper_width)?)?,
+            )
+        }
+        RangeWidthKind::Volatility => {
+            let volatility = volatility.ok_or(ErrorCode::NotEnoughPriceObservations)?;
+            let width = volatility
+                .safe_mul_div(config.width.into(), BPS_SCALE)?
+                .clamp(config.min_width_ticks.into(), config.max_width_ticks.into());
+
+            ticks_around(curr_tick, config.split_width(width))
+        }
+    };
+
+    let tick_lower = align_tick_down(tick_lower, tick_spacing);
+    let tick_upper = align_tick_up(tick_upper, tick_spacing);
+
+    validate_tick_range(tick_lower, tick_upper, tick_spacing)?;
+
+    Ok((tick_lower, tick_upper))
+}
+
+fn ticks_around(curr_tick: i32, (lower_width, upper_width): (u64, u64)) -> (i32, i32) {
+    (
+        curr_tick.saturating_sub(lower_width as i32),
+        curr_tick.saturating_add(upper_width as i32),
+    )
+}
+
+/// Tick of the price obtained multiplying the current price by ratio_bps / BPS_SCALE
+fn tick_from_price_ratio(curr_sqrt_price: u128, ratio_bps: u64) -> Result<i32> {
+    let sqrt_price = sqrt_price_from_price_ratio(curr_sqrt_price, ratio_bps)?;
+    Ok(tick_math::tick_index_from_sqrt_price(&sqrt_price))
+}
+
+fn align_tick_down(tick: i32, tick_spacing: u16) -> i32 {
+    let tick_spacing = i32::from(tick_spacing);
+    tick.div_euclid(tick_spacing) * tick_spacing
+}
+
+fn align_tick_up(tick: i32, tick_spacing: u16) -> i32 {
+    -align_tick_down(-tick, tick_spacing)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_align_tick() {
+        assert_eq!(align_tick_down(130, 64), 128);
+        assert_eq!(align_tick_up(130, 64), 192);
+        assert_eq!(align_tick_down(-130, 64), -192);
+        assert_eq!(align_tick_up(-130, 64), -128);
+        assert_eq!(align_tick_down(128, 64), 128);
+        assert_eq!(align_tick_up(128, 64), 128);
+    }
+
+    #[test]
+    fn test_validate_tick_range() {
+        assert!(validate_tick_range(-128, 128, 64).is_ok());
+        assert!(validate_tick_range(128, -128, 64).is_err());
+        assert!(validate_tick_range(128, 128, 64).is_err());
+        assert!(validate_tick_range(-100, 128, 64).is_err());
+        assert!(validate_tick_range(MIN_TICK_INDEX - 64, 128, 64).is_err());
+    }
+
+    #[test]
+    fn test_centered_tick_range_in_ticks() {
+        let config = RangeConfig {
+            width_kind: RangeWidthKind::Ticks,
+            width: 1000,
+            lower_share_bps: 5_000,
+            ..RangeConfig::default()
+        };
+
+        let curr_tick = 10;
+        let sqrt_price = tick_math::sqrt_price_from_tick_index(curr_tick);
+        let range = centered_tick_range(sqrt_price, curr_tick, 64, &config, None).unwrap();
+
+        assert_eq!(range, (-512, 512));
+    }
+
+    #[test]
+    fn test_centered_tick_range_asymmetric() {
+        let config = RangeConfig {
+            width_kind: RangeWidthKind::Ticks,
+            width: 1000,
+            lower_share_bps: 2_500,
+            ..RangeConfig::default()
+        };
+
+        let curr_tick = 0;
+        let sqrt_price = tick_math::sqrt_price_from_tick_index(curr_tick);
+        let range = centered_tick_range(sqrt_price, curr_tick, 8, &config, None).unwrap();
+
+        assert_eq!(range, (-256, 752));
+    }
+
+    #[test]
+    fn test_centered_tick_range_in_bps() {
+        // +-10% of the current price: ln(1.1) / ln(1.0001) ~ 953, ln(0.9) / ln(1.0001) ~ -1054
+        let config = RangeConfig {
+            width_kind: RangeWidthKind::Bps,
+            width: 2_000,
+            lower_share_bps: 5_000,
+            ..RangeConfig::default()
+        };
+
+        let curr_tick = 0;
+        let sqrt_price = tick_math::sqrt_price_from_tick_index(curr_tick);
+        let range = centered_tick_range(sqrt_price, curr_tick, 1, &config, None).unwrap();
+
+        assert!((-1055..=-1053).contains(&range.0));
+        assert!((952..=954).contains(&range.1));
+    }
+
+    #[test]
+    fn test_centered_tick_range_from_volatility() {
+        // 2 sigma in each direction
+        let config = RangeConfig {
+            width_kind: RangeWidthKind::Volatility,
+            width: 40_000,
+            lower_share_bps: 5_000,
+            min_width_ticks: 128,
+            max_width_ticks: 4096,
+        };
+
+        let curr_tick = 0;
+        let sqrt_price = tick_math::sqrt_price_from_tick_index(curr_tick);
+
+        let range = centered_tick_range(sqrt_price, curr_tick, 64, &config, Some(250)).unwrap();
+        assert_eq!(range, (-512, 512));
+
+        let range = centered_tick_range(sqrt_price, curr_tick, 64, &config, Some(10)).unwrap();
+        assert_eq!(range, (-64, 64));
+
+        let range = centered_tick_range(sqrt_price, curr_tick, 64, &config, Some(10_000)).unwrap();
+        assert_eq!(range, (-2048, 2048));
+
+        assert!(centered_tick_range(sqrt_price, curr_tick, 64, &config, None).is_err());
+    }
+
+    #[test]
+    fn test_centered_tick_range_not_set() {
+        let config = RangeConfig::default();
+        assert!(centered_tick_range(1 << 64, 0, 64, &config, None).is_err());
+    }
+}
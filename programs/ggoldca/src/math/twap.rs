@@ -0,0 +1,96 @@
+use crate::state::PriceObservation;
+
+/// Window used to compute the vault TWAP (~6 min)
+pub const TWAP_WINDOW_SLOTS: u64 = 900;
+
+/// Time-weighted average tick over the last window_slots. Each observation is considered valid
+/// until the next one, and the latest one until the current slot.
+/// Returns None when the observations don't cover any slot of the window, or when the latest
+/// one is older than the window, as it may no longer reflect the market price.
+pub fn time_weighted_average_tick(
+    observations: &[PriceObservation],
+    current_slot: u64,
+    window_slots: u64,
+) -> Option<i32> {
+    let window_start = current_slot.saturating_sub(window_slots);
+
+    if observations.last()?.slot < window_start {
+        return None;
+    }
+
+    let mut weighted_ticks: i128 = 0;
+    let mut total_slots: u64 = 0;
+
+    for (index, observation) in observations.iter().enumerate() {
+        let start = observation.slot.max(window_start);
+        let end = observations
+            .get(index + 1)
+            .map_or(current_slot, |next| next.slot);
+
+        if end > start {
+            let elapsed_slots = end - start;
+            weighted_ticks += i128::from(observation.tick) * i128::from(elapsed_slots);
+            total_slots += elapsed_slots;
+        }
+    }
+
+    if total_slots == 0 {
+        return None;
+    }
+
+    Some(weighted_ticks.div_euclid(i128::from(total_slots)) as i32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn observations(data: &[(u64, i32)]) -> Vec<PriceObservation> {
+        data.iter()
+            .map(|&(slot, tick)| PriceObservation { slot, tick })
+            .collect()
+    }
+
+    #[test]
+    fn test_twap_no_data() {
+        assert_eq!(time_weighted_average_tick(&[], 100, 50), None);
+
+        // The observation of the current slot has no weight yet
+        let obs = observations(&[(100, 10)]);
+        assert_eq!(time_weighted_average_tick(&obs, 100, 50), None);
+    }
+
+    #[test]
+    fn test_twap_single_observation() {
+        let obs = observations(&[(10, -300)]);
+        assert_eq!(time_weighted_average_tick(&obs, 50, 50), Some(-300));
+    }
+
+    #[test]
+    fn test_twap_stale_observations() {
+        let obs = observations(&[(0, 100), (10, -300)]);
+
+        // The latest observation is at the window start
+        assert_eq!(time_weighted_average_tick(&obs, 60, 50), Some(-300));
+        // No observation within the window
+        assert_eq!(time_weighted_average_tick(&obs, 61, 50), None);
+        assert_eq!(time_weighted_average_tick(&obs, 1_000, 50), None);
+    }
+
+    #[test]
+    fn test_twap() {
+        let obs = observations(&[(0, 100), (60, 200), (80, 400)]);
+
+        // Window [50, 100]: 100 * 10 + 200 * 20 + 400 * 20 = 13_000 / 50
+        assert_eq!(time_weighted_average_tick(&obs, 100, 50), Some(260));
+
+        // Whole history: 100 * 60 + 200 * 20 + 400 * 20 = 18_000 / 100
+        assert_eq!(time_weighted_average_tick(&obs, 100, 1_000), Some(180));
+    }
+
+    #[test]
+    fn test_twap_ignores_current_slot_observation() {
+        let obs = observations(&[(0, -100), (100, 5_000)]);
+        assert_eq!(time_weighted_average_tick(&obs, 100, 50), Some(-100));
+    }
+}
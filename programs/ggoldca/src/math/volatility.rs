@@ -0,0 +1,70 @@
+use crate::math::safe_arithmetics::{SafeArithmetics, SafeMulDiv};
+use crate::state::PriceObservation;
+use anchor_lang::prelude::*;
+use whirlpool::math::U256;
+
+/// Horizon used to express the realized volatility (~1 day)
+pub const VOLATILITY_HORIZON_SLOTS: u64 = 216_000;
+
+/// Realized volatility of the log-price, in ticks, over VOLATILITY_HORIZON_SLOTS.
+/// Returns None when the observations don't span any slot.
+pub fn realized_volatility(observations: &[PriceObservation]) -> Result<Option<u64>> {
+    let (first, last) = match (observations.first(), observations.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Ok(None),
+    };
+
+    let elapsed_slots = last.slot.safe_sub(first.slot)?;
+    if elapsed_slots == 0 {
+        return Ok(None);
+    }
+
+    // A tick is a constant log-price increment, so tick differences are log returns
+    let sum_squared_returns = observations.windows(2).try_fold(0_u128, |acc, pair| {
+        let tick_delta = u128::from(pair[1].tick.abs_diff(pair[0].tick));
+        acc.safe_add(tick_delta.safe_mul(tick_delta)?)
+    })?;
+
+    let variance = sum_squared_returns.safe_mul_div(
+        u128::from(VOLATILITY_HORIZON_SLOTS),
+        u128::from(elapsed_slots),
+    )?;
+
+    Ok(Some(U256::from(variance).integer_sqrt().as_u64()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn observations(data: &[(u64, i32)]) -> Vec<PriceObservation> {
+        data.iter()
+            .map(|&(slot, tick)| PriceObservation { slot, tick })
+            .collect()
+    }
+
+    #[test]
+    fn test_realized_volatility_not_enough_data() {
+        assert_eq!(realized_volatility(&[]).unwrap(), None);
+        assert_eq!(
+            realized_volatility(&observations(&[(10, 5), (10, 7)])).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_realized_volatility_constant_price() {
+        let obs = observations(&[(0, 100), (1_000, 100), (2_000, 100)]);
+        assert_eq!(realized_volatility(&obs).unwrap(), Some(0));
+    }
+
+    #[test]
+    fn test_realized_volatility() {
+        // Squared returns: 30^2 + 40^2 = 2_500 over half of the horizon
+        let half_horizon = VOLATILITY_HORIZON_SLOTS / 2;
+        let obs = observations(&[(0, 0), (half_horizon / 2, 30), (half_horizon, -10)]);
+
+        // sqrt(2 * 2_500) = 70.7
+        assert_eq!(realized_volatility(&obs).unwrap(), Some(70));
+    }
+}
@@ -1,6 +1,15 @@
 use crate::error::ErrorCode;
-use crate::VAULT_VERSION;
+use crate::math::performance::{value_in_token_b_x64, value_per_share_x64};
+use crate::math::price::is_price_within_deviation;
+use crate::math::reward_per_share::{accrued_rewards, reward_per_share_increase};
+use crate::math::safe_arithmetics::{SafeArithmetics, SafeMulDiv};
+use crate::math::twap::{time_weighted_average_tick, TWAP_WINDOW_SLOTS};
+use crate::{
+    BPS_SCALE, FEE_SCALE, MAX_MIN_FEES, MAX_SLOTS_BETWEEN_SWAPS, MIN_REWARDS_AMOUNT, VAULT_VERSION,
+};
 use anchor_lang::prelude::*;
+use whirlpool::math::tick_math;
+use whirlpool::state::tick::{MAX_TICK_INDEX, MIN_TICK_INDEX};
 
 /// Number of simultaneous positions allowed
 pub const MAX_POSITIONS: usize = 3;
@@ -8,8 +17,23 @@ pub const MAX_POSITIONS: usize = 3;
 /// Number of whirlpool rewards (from whirlpool::state::whirlpool::NUM_REWARDS)
 pub const WHIRLPOOL_NUM_REWARDS: usize = 3;
 
+/// Maximum number of hops of a rewards swap route
+pub const MAX_ROUTE_HOPS: usize = 3;
+
+/// Number of price observations stored by the vault
+pub const NUM_PRICE_OBSERVATIONS: usize = 16;
+
+/// Number of performance snapshots stored in the vault history
+pub const NUM_HISTORY_SNAPSHOTS: usize = 32;
+
+/// Default maximum slippage allowed when swapping, in bps
+pub const DEFAULT_MAX_SWAP_SLIPPAGE_BPS: u16 = 100;
+
+/// Default maximum deviation allowed between the pool price and the vault TWAP, in bps
+pub const DEFAULT_MAX_PRICE_DEVIATION_BPS: u16 = 200;
+
 /// Additional padding (8 * bytes)
-const PADDING_AS_U64: usize = 10;
+const PADDING_AS_U64: usize = 2;
 
 /// Strategy vault account
 #[account]
@@ -58,6 +82,50 @@ pub struct VaultAccount {
     /// Information about the opened positions (max = MAX_POSITIONS)
     pub positions: Vec<PositionInfo>,
 
+    /// Strategy used to compute the range of new positions
+    pub range_config: RangeConfig,
+
+    /// Whirlpool price observations
+    pub price_history: PriceHistory,
+
+    /// Maximum slippage (pool fees included) allowed when swapping during reinvest, in bps
+    pub max_swap_slippage_bps: u16,
+
+    /// Maximum deviation allowed between the pool price and the vault TWAP, in bps (0 = disabled)
+    pub max_price_deviation_bps: u16,
+
+    /// Bounty paid to the callers of collect_fees and reinvest
+    pub keeper_bounty: KeeperBounty,
+    /// Harvested amounts which did not pay the keeper bounty yet, paid by the next call
+    /// providing the keeper token accounts
+    pub unpaid_bounty_token_a: u64,
+    pub unpaid_bounty_token_b: u64,
+
+    /// Minimum amount of collected fees required to charge the treasury fee
+    pub min_fees_token_a: u64,
+    pub min_fees_token_b: u64,
+
+    /// Collected fees below the minimum, carried forward until they are charged
+    pub uncharged_fees_token_a: u64,
+    pub uncharged_fees_token_b: u64,
+
+    /// Multi-hop swap routes of the rewards (same order as market_rewards)
+    pub rewards_routes: [SwapRoute; WHIRLPOOL_NUM_REWARDS],
+
+    /// Bounds of the rewards swaps min_amount_out (same order as market_rewards)
+    pub rewards_price_bounds: [RewardsPriceBound; WHIRLPOOL_NUM_REWARDS],
+
+    /// Split of the rewards swaps in tranches (same order as market_rewards)
+    pub rewards_swap_chunking: [SwapChunking; WHIRLPOOL_NUM_REWARDS],
+
+    /// Distributed rewards per locked LP token, as Q64.64 (same order as market_rewards)
+    pub rewards_per_share_x64: [u128; WHIRLPOOL_NUM_REWARDS],
+    /// LP tokens locked in the rewards checkpoints, the only ones accruing distributed rewards
+    pub rewards_locked_lp: u64,
+    /// Distributed rewards accrued by the checkpoints and not claimed yet (same order as
+    /// market_rewards)
+    pub rewards_owed: [u64; WHIRLPOOL_NUM_REWARDS],
+
     /// Additional padding
     pub _padding: [u64; PADDING_AS_U64],
 }
@@ -80,6 +148,23 @@ impl VaultAccount {
         + WHIRLPOOL_NUM_REWARDS * MarketRewardsInfo::SIZE
         + 4
         + MAX_POSITIONS * PositionInfo::SIZE
+        + RangeConfig::SIZE
+        + PriceHistory::SIZE
+        + 2
+        + 2
+        + KeeperBounty::SIZE
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + 8
+        + WHIRLPOOL_NUM_REWARDS * SwapRoute::SIZE
+        + WHIRLPOOL_NUM_REWARDS * RewardsPriceBound::SIZE
+        + WHIRLPOOL_NUM_REWARDS * SwapChunking::SIZE
+        + WHIRLPOOL_NUM_REWARDS * 16
+        + 8
+        + WHIRLPOOL_NUM_REWARDS * 8
         + 8 * PADDING_AS_U64;
 
     /// Create a new vault
@@ -93,6 +178,10 @@ impl VaultAccount {
             input_token_b_mint_pubkey: params.input_token_b_mint_pubkey,
             fee: params.fee,
             min_slots_for_reinvest: params.min_slots_for_reinvest,
+            max_swap_slippage_bps: DEFAULT_MAX_SWAP_SLIPPAGE_BPS,
+            max_price_deviation_bps: DEFAULT_MAX_PRICE_DEVIATION_BPS,
+            min_fees_token_a: FEE_SCALE,
+            min_fees_token_b: FEE_SCALE,
             ..Self::default()
         }
     }
@@ -114,6 +203,188 @@ impl VaultAccount {
         self.positions[0].pubkey
     }
 
+    /// TWAP tick of the price history, None when it holds no observation within the window
+    pub fn twap_tick(&self, current_slot: u64) -> Option<i32> {
+        time_weighted_average_tick(
+            &self.price_history.sorted_observations(),
+            current_slot,
+            TWAP_WINDOW_SLOTS,
+        )
+    }
+
+    /// Check the pool price doesn't deviate from the vault TWAP more than allowed.
+    /// A stale price history is rejected, as any price could then be used as reference: it is
+    /// refreshed by refresh_price_history against an admin reference price. Only checked
+    /// prices are recorded, and the observation of the current slot has no weight yet, so
+    /// that the TWAP cannot be moved within a single transaction
+    pub fn check_price_deviation(&self, sqrt_price: u128, current_slot: u64) -> Result<()> {
+        if self.max_price_deviation_bps == 0 {
+            return Ok(());
+        }
+
+        let twap_tick = self
+            .twap_tick(current_slot)
+            .ok_or_else(|| error!(ErrorCode::StalePriceHistory))?;
+
+        require!(
+            is_price_within_deviation(
+                sqrt_price,
+                tick_math::sqrt_price_from_tick_index(twap_tick),
+                self.max_price_deviation_bps
+            )?,
+            ErrorCode::PriceDeviationExceeded
+        );
+
+        Ok(())
+    }
+
+    /// Check the collected fees allow charging the treasury fee for at least one token
+    pub fn fees_meet_threshold(&self, amount_a: u64, amount_b: u64) -> bool {
+        self.fee == 0
+            || amount_a.saturating_add(self.uncharged_fees_token_a) > self.min_fees_token_a
+            || amount_b.saturating_add(self.uncharged_fees_token_b) > self.min_fees_token_b
+    }
+
+    /// Check the fee thresholds keep the fee rounding below 1% without blocking the collection
+    pub fn validate_min_fees(min_fees_token_a: u64, min_fees_token_b: u64) -> Result<()> {
+        for min_fees in [min_fees_token_a, min_fees_token_b] {
+            require!(
+                (FEE_SCALE..=MAX_MIN_FEES).contains(&min_fees),
+                ErrorCode::InvalidMinFees
+            );
+        }
+        Ok(())
+    }
+
+    /// Treasury fees for the collected fees. Amounts not exceeding the minimum are carried
+    /// forward, unless charge_all is set (e.g. to empty a position before closing it).
+    /// The fees are capped by the available vault balances, the carried amounts may have
+    /// been reinvested since they were collected
+    pub fn charge_fees(
+        &mut self,
+        amount_a: u64,
+        amount_b: u64,
+        available_a: u64,
+        available_b: u64,
+        charge_all: bool,
+    ) -> Result<(u64, u64)> {
+        if self.fee == 0 {
+            return Ok((0, 0));
+        }
+
+        let (fee, min_fees_a, min_fees_b) =
+            (self.fee, self.min_fees_token_a, self.min_fees_token_b);
+
+        let treasury_fee_a = treasury_fee(
+            fee,
+            amount_a,
+            min_fees_a,
+            available_a,
+            &mut self.uncharged_fees_token_a,
+            charge_all,
+        )?;
+        let treasury_fee_b = treasury_fee(
+            fee,
+            amount_b,
+            min_fees_b,
+            available_b,
+            &mut self.uncharged_fees_token_b,
+            charge_all,
+        )?;
+
+        Ok((treasury_fee_a, treasury_fee_b))
+    }
+
+    /// Keeper bounty of the harvested amounts, capped by the available balances. Without
+    /// keeper accounts the amounts are carried forward, so reinvest pays them later
+    pub fn keeper_bounties(
+        &mut self,
+        amount_a: u64,
+        amount_b: u64,
+        available_a: u64,
+        available_b: u64,
+        has_keeper_accounts: bool,
+    ) -> Result<(u64, u64)> {
+        if self.keeper_bounty.bps == 0 {
+            return Ok((0, 0));
+        }
+
+        // Saturating, a carried amount must not block harvesting
+        let total_a = amount_a.saturating_add(self.unpaid_bounty_token_a);
+        let total_b = amount_b.saturating_add(self.unpaid_bounty_token_b);
+
+        if !has_keeper_accounts {
+            self.unpaid_bounty_token_a = total_a;
+            self.unpaid_bounty_token_b = total_b;
+            return Ok((0, 0));
+        }
+
+        self.unpaid_bounty_token_a = 0;
+        self.unpaid_bounty_token_b = 0;
+
+        let (bounty_a, bounty_b) = self.keeper_bounty.amounts(total_a, total_b)?;
+        Ok((bounty_a.min(available_a), bounty_b.min(available_b)))
+    }
+
+    /// Check the collected rewards allow charging the treasury fee
+    pub fn rewards_meet_threshold(&self, amount: u64) -> bool {
+        self.fee == 0 || amount > MIN_REWARDS_AMOUNT
+    }
+
+    /// Treasury fee for the collected rewards
+    pub fn rewards_treasury_fee(&self, amount: u64) -> Result<u64> {
+        amount.safe_mul_div_round_up(self.fee, FEE_SCALE)
+    }
+
+    /// Check the mint is neither an input token nor a configured rewards mint
+    pub fn is_sweepable_mint(&self, mint: Pubkey) -> bool {
+        mint != self.input_token_a_mint_pubkey
+            && mint != self.input_token_b_mint_pubkey
+            && !self
+                .market_rewards
+                .iter()
+                .any(|market| market.id != MarketRewards::NotSet && market.rewards_mint == mint)
+    }
+
+    /// Distribute the collected rewards among the locked LP tokens
+    pub fn distribute_rewards(&mut self, reward_index: usize, amount: u64) -> Result<()> {
+        require!(self.rewards_locked_lp > 0, ErrorCode::NoLockedLp);
+
+        let increase_x64 = reward_per_share_increase(amount, self.rewards_locked_lp)?;
+        self.rewards_per_share_x64[reward_index] =
+            self.rewards_per_share_x64[reward_index].safe_add(increase_x64)?;
+        Ok(())
+    }
+
+    /// Accrue the distributed rewards of a checkpoint. It must be called before its locked
+    /// amount changes
+    pub fn checkpoint_rewards(&mut self, checkpoint: &mut UserRewardsCheckpoint) -> Result<()> {
+        let accrued = checkpoint.update(self.rewards_per_share_x64)?;
+        for (owed, accrued) in self.rewards_owed.iter_mut().zip(accrued) {
+            *owed = owed.safe_add(accrued)?;
+        }
+        Ok(())
+    }
+
+    /// Take the rewards of reward_index owed to a checkpoint
+    pub fn claim_rewards(
+        &mut self,
+        checkpoint: &mut UserRewardsCheckpoint,
+        reward_index: usize,
+    ) -> Result<u64> {
+        let amount = checkpoint.take_owed_rewards(reward_index);
+        self.rewards_owed[reward_index] = self.rewards_owed[reward_index].safe_sub(amount)?;
+        Ok(amount)
+    }
+
+    /// Check some distributed rewards of the mint are still to be claimed
+    pub fn has_owed_rewards(&self, mint: Pubkey) -> bool {
+        self.market_rewards
+            .iter()
+            .zip(self.rewards_owed)
+            .any(|(market, owed)| market.rewards_mint == mint && owed > 0)
+    }
+
     /// Update the current active position
     pub fn update_active_position(&mut self, key: Pubkey) {
         let new_position_indx = self
@@ -126,6 +397,414 @@ impl VaultAccount {
     }
 }
 
+fn treasury_fee(
+    fee: u64,
+    amount: u64,
+    min_amount: u64,
+    available: u64,
+    uncharged_amount: &mut u64,
+    charge_all: bool,
+) -> Result<u64> {
+    let total_amount = amount.safe_add(*uncharged_amount)?;
+
+    if !charge_all && total_amount <= min_amount {
+        *uncharged_amount = total_amount;
+        return Ok(0);
+    }
+
+    let treasury_fee = total_amount.safe_mul_div_round_up(fee, FEE_SCALE)?;
+    if treasury_fee <= available {
+        *uncharged_amount = 0;
+        return Ok(treasury_fee);
+    }
+
+    // Charge what is available, the amount not covered stays carried forward
+    let charged_amount = available.safe_mul_div(FEE_SCALE, fee)?;
+    *uncharged_amount = total_amount.safe_sub(charged_amount)?;
+    Ok(available)
+}
+
+/// Harvested amount left once the treasury fee is charged. The fee also covers the carried
+/// uncharged fees, so it can exceed the harvested amount
+pub fn amount_after_fee(amount: u64, treasury_fee: u64) -> u64 {
+    amount.saturating_sub(treasury_fee)
+}
+
+/// Rewards distributed to a user, checkpointed whenever the locked LP tokens change and on
+/// each claim. Only locked LP tokens accrue rewards, so that LP tokens cannot claim twice by
+/// moving between users and LP tokens held by other programs do not dilute the rewards
+#[account]
+#[derive(Default, Debug)]
+pub struct UserRewardsCheckpoint {
+    /// Vault of the distributed rewards
+    pub vault_account: Pubkey,
+    /// Owner of the checkpoint
+    pub user: Pubkey,
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// LP tokens locked by the user, kept by the vault until unlocked
+    pub lp_balance: u64,
+    /// Vault rewards per share at the last checkpoint (same order as market_rewards)
+    pub rewards_per_share_x64: [u128; WHIRLPOOL_NUM_REWARDS],
+    /// Rewards accrued and not claimed yet (same order as market_rewards)
+    pub owed_rewards: [u64; WHIRLPOOL_NUM_REWARDS],
+}
+
+impl UserRewardsCheckpoint {
+    pub const SIZE: usize =
+        32 + 32 + 1 + 8 + WHIRLPOOL_NUM_REWARDS * 16 + WHIRLPOOL_NUM_REWARDS * 8;
+
+    /// Accrue the rewards distributed on the locked LP tokens since the last checkpoint.
+    /// It must be called before the locked amount changes. Returns the accrued rewards
+    pub fn update(
+        &mut self,
+        rewards_per_share_x64: [u128; WHIRLPOOL_NUM_REWARDS],
+    ) -> Result<[u64; WHIRLPOOL_NUM_REWARDS]> {
+        let mut accrued = [0; WHIRLPOOL_NUM_REWARDS];
+        for (index, reward_per_share_x64) in rewards_per_share_x64.iter().enumerate() {
+            let delta_x64 = reward_per_share_x64.safe_sub(self.rewards_per_share_x64[index])?;
+            accrued[index] = accrued_rewards(self.lp_balance, delta_x64)?;
+
+            self.owed_rewards[index] = self.owed_rewards[index].safe_add(accrued[index])?;
+            self.rewards_per_share_x64[index] = *reward_per_share_x64;
+        }
+
+        Ok(accrued)
+    }
+
+    /// Take the owed rewards of the given index
+    pub fn take_owed_rewards(&mut self, reward_index: usize) -> u64 {
+        std::mem::take(&mut self.owed_rewards[reward_index])
+    }
+}
+
+/// Ring buffer of vault performance snapshots
+#[account]
+#[derive(Default, Debug)]
+pub struct VaultHistory {
+    /// Vault of the snapshots
+    pub vault_account: Pubkey,
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Minimum number of slots between two snapshots
+    pub min_slots_between_snapshots: u64,
+
+    /// Index of the most recent snapshot
+    pub last_index: u8,
+    /// Number of stored snapshots
+    pub len: u8,
+    /// Stored snapshots
+    pub snapshots: [HistorySnapshot; NUM_HISTORY_SNAPSHOTS],
+}
+
+impl VaultHistory {
+    pub const SIZE: usize = 32 + 1 + 8 + 1 + 1 + NUM_HISTORY_SNAPSHOTS * HistorySnapshot::SIZE;
+
+    /// Store a new snapshot if enough slots elapsed since the last one.
+    /// Returns whether the snapshot was stored
+    pub fn record(&mut self, snapshot: HistorySnapshot) -> bool {
+        if self.len > 0 {
+            let last_slot = self.snapshots[usize::from(self.last_index)].slot;
+            // Only the first snapshot of each slot is kept
+            let min_slots = self.min_slots_between_snapshots.max(1);
+            if snapshot.slot.saturating_sub(last_slot) < min_slots {
+                return false;
+            }
+        }
+
+        let index = if self.len == 0 {
+            0
+        } else {
+            (usize::from(self.last_index) + 1) % NUM_HISTORY_SNAPSHOTS
+        };
+
+        self.snapshots[index] = snapshot;
+        self.last_index = index as u8;
+
+        if usize::from(self.len) < NUM_HISTORY_SNAPSHOTS {
+            self.len += 1;
+        }
+
+        true
+    }
+
+    /// Snapshot by chronological index (0 = oldest)
+    pub fn get(&self, index: usize) -> Option<&HistorySnapshot> {
+        let len = usize::from(self.len);
+        if index >= len {
+            return None;
+        }
+
+        let first = (usize::from(self.last_index) + NUM_HISTORY_SNAPSHOTS + 1 - len)
+            % NUM_HISTORY_SNAPSHOTS;
+        self.snapshots.get((first + index) % NUM_HISTORY_SNAPSHOTS)
+    }
+}
+
+/// Vault value per LP token at a given time
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default, Debug)]
+pub struct HistorySnapshot {
+    pub slot: u64,
+    pub unix_timestamp: i64,
+    /// Position liquidity per LP token, as Q64.64
+    pub liquidity_per_lp_x64: u128,
+    /// Token a (position and idle) per LP token, as Q64.64
+    pub token_a_per_lp_x64: u128,
+    /// Token b (position and idle) per LP token, as Q64.64
+    pub token_b_per_lp_x64: u128,
+    /// Whirlpool sqrt price, as Q64.64
+    pub sqrt_price: u128,
+}
+
+impl HistorySnapshot {
+    pub const SIZE: usize = 8 + 8 + 16 + 16 + 16 + 16;
+
+    pub fn new(
+        clock: &Clock,
+        sqrt_price: u128,
+        liquidity: u128,
+        amount_a: u64,
+        amount_b: u64,
+        lp_supply: u64,
+    ) -> Result<Self> {
+        Ok(Self {
+            slot: clock.slot,
+            unix_timestamp: clock.unix_timestamp,
+            liquidity_per_lp_x64: value_per_share_x64(liquidity, lp_supply)?,
+            token_a_per_lp_x64: value_per_share_x64(amount_a.into(), lp_supply)?,
+            token_b_per_lp_x64: value_per_share_x64(amount_b.into(), lp_supply)?,
+            sqrt_price,
+        })
+    }
+
+    /// Value of an LP token in token b at the given sqrt price, as Q64.64
+    pub fn value_per_lp_x64(&self, sqrt_price: u128) -> Result<u128> {
+        value_in_token_b_x64(self.token_a_per_lp_x64, self.token_b_per_lp_x64, sqrt_price)
+    }
+}
+
+/// Deposits and withdrawals of a user through the vault
+#[account]
+#[derive(Default, Debug)]
+pub struct UserPosition {
+    /// Vault of the position
+    pub vault_account: Pubkey,
+    /// Owner of the position
+    pub user: Pubkey,
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Slot of the first deposit (0 = no deposit yet)
+    pub first_deposit_slot: u64,
+
+    /// Cumulative amounts deposited
+    pub deposited_token_a: u64,
+    pub deposited_token_b: u64,
+
+    /// Cumulative amounts withdrawn
+    pub withdrawn_token_a: u64,
+    pub withdrawn_token_b: u64,
+
+    /// LP tokens minted minus LP tokens burned through the vault
+    pub lp_balance: u64,
+
+    /// Amounts deposited for the LP tokens of lp_balance, reduced pro rata on withdrawals
+    pub cost_basis_token_a: u64,
+    pub cost_basis_token_b: u64,
+
+    /// Cumulative amounts withdrawn minus their cost basis
+    pub realized_gain_token_a: i64,
+    pub realized_gain_token_b: i64,
+}
+
+impl UserPosition {
+    pub const SIZE: usize = 32 + 32 + 1 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8;
+
+    pub fn record_deposit(
+        &mut self,
+        current_slot: u64,
+        amount_a: u64,
+        amount_b: u64,
+        lp_amount: u64,
+    ) -> Result<()> {
+        if self.first_deposit_slot == 0 {
+            self.first_deposit_slot = current_slot;
+        }
+
+        self.deposited_token_a = self.deposited_token_a.safe_add(amount_a)?;
+        self.deposited_token_b = self.deposited_token_b.safe_add(amount_b)?;
+        self.cost_basis_token_a = self.cost_basis_token_a.safe_add(amount_a)?;
+        self.cost_basis_token_b = self.cost_basis_token_b.safe_add(amount_b)?;
+        self.lp_balance = self.lp_balance.safe_add(lp_amount)?;
+
+        Ok(())
+    }
+
+    /// Realize the gains of the withdrawn LP tokens against their share of the cost basis.
+    /// LP tokens may have been received outside the vault, without a known cost basis, so
+    /// only the share of lp_amount within lp_balance realizes gains
+    pub fn record_withdraw(&mut self, amount_a: u64, amount_b: u64, lp_amount: u64) -> Result<()> {
+        self.withdrawn_token_a = self.withdrawn_token_a.safe_add(amount_a)?;
+        self.withdrawn_token_b = self.withdrawn_token_b.safe_add(amount_b)?;
+
+        let tracked_lp_amount = lp_amount.min(self.lp_balance);
+        if tracked_lp_amount > 0 {
+            let cost_a = self
+                .cost_basis_token_a
+                .safe_mul_div(tracked_lp_amount, self.lp_balance)?;
+            let cost_b = self
+                .cost_basis_token_b
+                .safe_mul_div(tracked_lp_amount, self.lp_balance)?;
+            let proceeds_a = amount_a.safe_mul_div(tracked_lp_amount, lp_amount)?;
+            let proceeds_b = amount_b.safe_mul_div(tracked_lp_amount, lp_amount)?;
+
+            self.realized_gain_token_a = self
+                .realized_gain_token_a
+                .safe_add(signed_difference(proceeds_a, cost_a)?)?;
+            self.realized_gain_token_b = self
+                .realized_gain_token_b
+                .safe_add(signed_difference(proceeds_b, cost_b)?)?;
+            self.cost_basis_token_a = self.cost_basis_token_a.safe_sub(cost_a)?;
+            self.cost_basis_token_b = self.cost_basis_token_b.safe_sub(cost_b)?;
+        }
+
+        self.lp_balance = self.lp_balance.safe_sub(tracked_lp_amount)?;
+
+        Ok(())
+    }
+}
+
+fn signed_difference(a: u64, b: u64) -> Result<i64> {
+    i64::try_from(i128::from(a) - i128::from(b))
+        .map_err(|_| error!(ErrorCode::MathOverflowConversion))
+}
+
+/// Staking farm emitting rewards to the staked vault LP tokens
+#[account]
+#[derive(Default, Debug)]
+pub struct Farm {
+    /// Vault of the staked LP tokens
+    pub vault_account: Pubkey,
+    /// Farm number for a given vault
+    pub id: u8,
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// Staked mint (vault_lp_token_mint_pubkey)
+    pub lp_token_mint: Pubkey,
+    /// Emitted mint
+    pub rewards_mint: Pubkey,
+    /// Account allowed to fund the farm
+    pub funder: Pubkey,
+
+    /// Rewards emitted per slot until end_slot
+    pub reward_rate_per_slot: u64,
+    /// Last slot of the emissions
+    pub end_slot: u64,
+    /// Last slot the accumulator was updated
+    pub last_update_slot: u64,
+    /// Emitted rewards per staked LP token, as Q64.64
+    pub reward_per_share_x64: u128,
+
+    /// Total LP tokens staked
+    pub total_staked: u64,
+}
+
+impl Farm {
+    pub const SIZE: usize = 32 + 1 + 1 + 32 + 32 + 32 + 8 + 8 + 8 + 16 + 8;
+
+    /// Accumulate the rewards emitted up to the given slot.
+    /// Without any LP staked nobody can receive the emissions, so the ones left are
+    /// postponed to start from the given slot, and are rolled over by the next fund
+    pub fn update(&mut self, current_slot: u64) -> Result<()> {
+        if self.total_staked == 0 {
+            if current_slot > self.last_update_slot {
+                if self.end_slot > self.last_update_slot {
+                    let remaining_slots = self.end_slot.safe_sub(self.last_update_slot)?;
+                    self.end_slot = current_slot.safe_add(remaining_slots)?;
+                }
+                self.last_update_slot = current_slot;
+            }
+            return Ok(());
+        }
+
+        let last_emission_slot = current_slot.min(self.end_slot);
+
+        if last_emission_slot > self.last_update_slot {
+            let elapsed_slots = last_emission_slot.safe_sub(self.last_update_slot)?;
+            let emitted = self.reward_rate_per_slot.safe_mul(elapsed_slots)?;
+
+            self.reward_per_share_x64 = self
+                .reward_per_share_x64
+                .safe_add(reward_per_share_increase(emitted, self.total_staked)?)?;
+            self.last_update_slot = last_emission_slot;
+        }
+
+        Ok(())
+    }
+
+    /// Emit amount plus the rewards not emitted yet during the next duration_slots
+    pub fn fund(&mut self, amount: u64, duration_slots: u64, current_slot: u64) -> Result<()> {
+        self.update(current_slot)?;
+
+        let remaining_rewards = if current_slot < self.end_slot {
+            self.end_slot
+                .safe_sub(current_slot)?
+                .safe_mul(self.reward_rate_per_slot)?
+        } else {
+            0
+        };
+
+        let reward_rate_per_slot = amount
+            .safe_add(remaining_rewards)?
+            .safe_div(duration_slots)?;
+        require!(reward_rate_per_slot > 0, ErrorCode::InvalidFarmFunding);
+
+        self.reward_rate_per_slot = reward_rate_per_slot;
+        self.end_slot = current_slot.safe_add(duration_slots)?;
+        self.last_update_slot = current_slot;
+
+        Ok(())
+    }
+}
+
+/// LP tokens staked by a user in a farm
+#[account]
+#[derive(Default, Debug)]
+pub struct FarmStake {
+    /// Farm of the stake
+    pub farm: Pubkey,
+    /// Owner of the stake
+    pub user: Pubkey,
+    /// PDA bump seed
+    pub bump: u8,
+
+    /// LP tokens staked
+    pub amount: u64,
+    /// Farm reward per share at the last update
+    pub reward_per_share_x64: u128,
+    /// Rewards accrued and not claimed yet
+    pub owed_rewards: u64,
+}
+
+impl FarmStake {
+    pub const SIZE: usize = 32 + 32 + 1 + 8 + 16 + 8;
+
+    /// Accrue the rewards emitted since the last update
+    pub fn update(&mut self, reward_per_share_x64: u128) -> Result<()> {
+        let delta_x64 = reward_per_share_x64.safe_sub(self.reward_per_share_x64)?;
+
+        self.owed_rewards = self
+            .owed_rewards
+            .safe_add(accrued_rewards(self.amount, delta_x64)?)?;
+        self.reward_per_share_x64 = reward_per_share_x64;
+
+        Ok(())
+    }
+}
+
 /// Create a new vault
 pub struct VaultAccountParams {
     /// Vault id
@@ -202,7 +881,7 @@ impl MarketRewardsInfo {
 
         match self.id {
             MarketRewards::NotSet => {}
-            MarketRewards::Transfer => {
+            MarketRewards::Transfer | MarketRewards::Distribute => {
                 require!(
                     self.rewards_mint != token_a_mint && self.rewards_mint != token_b_mint,
                     ErrorCode::MarketInvalidMint,
@@ -238,6 +917,8 @@ pub enum MarketRewards {
     Transfer,
     OrcaV2,
     Whirlpool,
+    SplTokenSwap,
+    Distribute,
 }
 
 impl MarketRewards {
@@ -249,3 +930,1027 @@ impl Default for MarketRewards {
         MarketRewards::NotSet
     }
 }
+
+/// Swap step of a rewards route
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default, Debug)]
+pub struct RouteHop {
+    /// Market where the hop is swapped
+    pub market: MarketRewards,
+    /// Mint received from the hop
+    pub mint_out: Pubkey,
+}
+
+impl RouteHop {
+    pub const SIZE: usize = MarketRewards::SIZE + 32;
+}
+
+/// Multi-hop swap route from the rewards mint to the destination mint
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default, Debug)]
+pub struct SwapRoute {
+    /// Number of hops used (0 = swap directly in the market rewards)
+    pub len: u8,
+    pub hops: [RouteHop; MAX_ROUTE_HOPS],
+}
+
+impl SwapRoute {
+    pub const SIZE: usize = 1 + MAX_ROUTE_HOPS * RouteHop::SIZE;
+
+    /// Build a route checking the hops chain rewards_mint into destination_mint.
+    /// Intermediate mints cannot repeat nor be any of the input_mints, so the
+    /// intermediate accounts never hold other balances of the vault
+    pub fn new(
+        hops: &[RouteHop],
+        rewards_mint: Pubkey,
+        destination_mint: Pubkey,
+        input_mints: &[Pubkey],
+    ) -> Result<Self> {
+        require!(hops.len() <= MAX_ROUTE_HOPS, ErrorCode::InvalidSwapRoute);
+
+        let mut visited_mints = vec![rewards_mint];
+        for (i, hop) in hops.iter().enumerate() {
+            require!(
+                !matches!(
+                    hop.market,
+                    MarketRewards::NotSet | MarketRewards::Transfer | MarketRewards::Distribute
+                ),
+                ErrorCode::InvalidSwapRoute
+            );
+            require!(
+                !visited_mints.contains(&hop.mint_out),
+                ErrorCode::InvalidSwapRoute
+            );
+
+            if i == hops.len() - 1 {
+                require!(
+                    hop.mint_out == destination_mint,
+                    ErrorCode::InvalidSwapRoute
+                );
+            } else {
+                require!(
+                    hop.mint_out != destination_mint && !input_mints.contains(&hop.mint_out),
+                    ErrorCode::InvalidSwapRoute
+                );
+            }
+
+            visited_mints.push(hop.mint_out);
+        }
+
+        let mut route = Self {
+            len: hops.len() as u8,
+            ..Self::default()
+        };
+        route.hops[..hops.len()].copy_from_slice(hops);
+
+        Ok(route)
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.len > 0
+    }
+
+    pub fn hops(&self) -> &[RouteHop] {
+        &self.hops[..usize::from(self.len)]
+    }
+}
+
+/// Minimum amount out of the rewards swaps
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default, Debug)]
+pub struct RewardsPriceBound {
+    /// How the minimum amount out is computed
+    pub kind: PriceBoundKind,
+    /// Minimum price of the rewards in destination tokens, as Q64.64 (MinPrice, and floor
+    /// of ReferencePool when not 0)
+    pub min_price_x64: u128,
+    /// Whirlpool of the rewards and destination mints used as price reference (ReferencePool)
+    pub reference_pool: Pubkey,
+    /// Maximum slippage against the reference pool price, in bps (ReferencePool)
+    pub max_slippage_bps: u16,
+}
+
+impl RewardsPriceBound {
+    pub const SIZE: usize = PriceBoundKind::SIZE + 16 + 32 + 2;
+
+    pub fn validate(&self) -> Result<()> {
+        match self.kind {
+            PriceBoundKind::Fixed => {}
+            PriceBoundKind::MinPrice => {
+                require!(self.min_price_x64 > 0, ErrorCode::InvalidPriceBound)
+            }
+            PriceBoundKind::ReferencePool => {
+                require!(
+                    self.reference_pool != Pubkey::default(),
+                    ErrorCode::InvalidPriceBound
+                );
+                require!(
+                    u64::from(self.max_slippage_bps) <= BPS_SCALE,
+                    ErrorCode::InvalidSlippage
+                );
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Number of remaining accounts required to compute the bound
+    pub fn accounts_len(&self) -> usize {
+        match self.kind {
+            PriceBoundKind::ReferencePool => 1,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Eq, Copy, Clone, Debug)]
+#[repr(u8)]
+pub enum PriceBoundKind {
+    /// MarketRewardsInfo::min_amount_out, whatever the amount swapped
+    Fixed,
+    /// Amount swapped times min_price_x64
+    MinPrice,
+    /// Amount swapped at the reference pool price minus max_slippage_bps, at least
+    /// amount swapped times min_price_x64. The reference pool cannot be a swap venue
+    ReferencePool,
+}
+
+impl PriceBoundKind {
+    pub const SIZE: usize = 1;
+}
+
+impl Default for PriceBoundKind {
+    fn default() -> Self {
+        PriceBoundKind::Fixed
+    }
+}
+
+/// Split of the rewards swaps in tranches to limit the price impact
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default, Debug)]
+pub struct SwapChunking {
+    /// Maximum amount of rewards swapped per call (0 = unlimited)
+    pub max_amount_in: u64,
+    /// Minimum number of slots between two swaps (0 = disabled)
+    pub min_slots_between_swaps: u64,
+    /// Slot of the last swap
+    pub last_swap_slot: u64,
+    /// Number of swaps done
+    pub swap_count: u64,
+}
+
+impl SwapChunking {
+    pub const SIZE: usize = 8 + 8 + 8 + 8;
+
+    /// Check enough slots elapsed since the last swap
+    pub fn can_swap(&self, current_slot: u64) -> bool {
+        self.swap_count == 0
+            || current_slot.saturating_sub(self.last_swap_slot) >= self.min_slots_between_swaps
+    }
+
+    /// Amount of the available rewards to swap in the current tranche
+    pub fn amount_to_swap(&self, available_amount: u64) -> u64 {
+        if self.max_amount_in == 0 {
+            available_amount
+        } else {
+            available_amount.min(self.max_amount_in)
+        }
+    }
+
+    /// Check tranches are above dust and swaps are spaced at most MAX_SLOTS_BETWEEN_SWAPS.
+    /// A fixed price bound does not follow the tranche size, so it cannot be chunked
+    pub fn validate(&self, price_bound_kind: PriceBoundKind) -> Result<()> {
+        require!(
+            self.max_amount_in == 0 || self.max_amount_in > MIN_REWARDS_AMOUNT,
+            ErrorCode::InvalidSwapChunking
+        );
+        require!(
+            self.max_amount_in == 0 || price_bound_kind != PriceBoundKind::Fixed,
+            ErrorCode::InvalidSwapChunking
+        );
+        require!(
+            self.min_slots_between_swaps <= MAX_SLOTS_BETWEEN_SWAPS,
+            ErrorCode::InvalidSwapChunking
+        );
+        Ok(())
+    }
+
+    pub fn record_swap(&mut self, current_slot: u64) -> Result<()> {
+        self.last_swap_slot = current_slot;
+        self.swap_count = self.swap_count.safe_add(1)?;
+        Ok(())
+    }
+}
+
+/// Price range strategy
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default, Debug)]
+pub struct RangeConfig {
+    /// Units in which the range width is expressed
+    pub width_kind: RangeWidthKind,
+    /// Range width, either in ticks, in bps of the current price or in bps of the volatility
+    pub width: u32,
+    /// Share of the width placed below the current price, in bps (5_000 = centered)
+    pub lower_share_bps: u16,
+    /// Minimum width in ticks when derived from the volatility
+    pub min_width_ticks: u32,
+    /// Maximum width in ticks when derived from the volatility
+    pub max_width_ticks: u32,
+}
+
+impl RangeConfig {
+    pub const SIZE: usize = RangeWidthKind::SIZE + 4 + 2 + 4 + 4;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            u64::from(self.lower_share_bps) <= BPS_SCALE,
+            ErrorCode::InvalidRangeConfig
+        );
+
+        let max_width_ticks = (MAX_TICK_INDEX - MIN_TICK_INDEX) as u32;
+
+        match self.width_kind {
+            RangeWidthKind::NotSet => {}
+            RangeWidthKind::Ticks => {
+                require!(
+                    self.width > 0 && self.width <= max_width_ticks,
+                    ErrorCode::InvalidRangeConfig
+                );
+            }
+            RangeWidthKind::Bps => {
+                require!(self.width > 0, ErrorCode::InvalidRangeConfig);
+                // The lower bound price has to remain positive
+                let (lower_width, _) = self.split_width(self.width.into());
+                require!(lower_width < BPS_SCALE, ErrorCode::InvalidRangeConfig);
+            }
+            RangeWidthKind::Volatility => {
+                require!(self.width > 0, ErrorCode::InvalidRangeConfig);
+                require!(
+                    self.min_width_ticks > 0
+                        && self.min_width_ticks <= self.max_width_ticks
+                        && self.max_width_ticks <= max_width_ticks,
+                    ErrorCode::InvalidRangeConfig
+                );
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Split the width into the parts below and above the current price
+    pub fn split_width(&self, width: u64) -> (u64, u64) {
+        let lower_width = width * u64::from(self.lower_share_bps) / BPS_SCALE;
+        (lower_width, width - lower_width)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, PartialEq, Eq, Copy, Clone, Debug)]
+#[repr(u8)]
+pub enum RangeWidthKind {
+    NotSet,
+    Ticks,
+    Bps,
+    Volatility,
+}
+
+impl RangeWidthKind {
+    pub const SIZE: usize = 1;
+}
+
+impl Default for RangeWidthKind {
+    fn default() -> Self {
+        RangeWidthKind::NotSet
+    }
+}
+
+/// Bounty paid to the callers of the permissionless harvesting instructions. Each harvested
+/// amount pays it once, in the instruction bringing it to the vault: the fees in collect_fees,
+/// the swapped rewards in swap_rewards and the other rewards in collect_rewards. Input token
+/// amounts harvested without keeper accounts pay it in the next call, reinvest included
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default, Debug)]
+pub struct KeeperBounty {
+    /// Cut of the harvested amounts, in bps (0 = disabled)
+    pub bps: u16,
+    /// Maximum amount of token_a paid per call
+    pub max_amount_a: u64,
+    /// Maximum amount of token_b paid per call
+    pub max_amount_b: u64,
+    /// Maximum amount of each reward paid per call (same order as market_rewards)
+    pub max_rewards_amounts: [u64; WHIRLPOOL_NUM_REWARDS],
+}
+
+impl KeeperBounty {
+    pub const SIZE: usize = 2 + 8 + 8 + WHIRLPOOL_NUM_REWARDS * 8;
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            u64::from(self.bps) <= BPS_SCALE,
+            ErrorCode::InvalidKeeperBounty
+        );
+        Ok(())
+    }
+
+    /// Bounty corresponding to the given harvested amounts
+    pub fn amounts(&self, amount_a: u64, amount_b: u64) -> Result<(u64, u64)> {
+        let bps = u64::from(self.bps);
+
+        let bounty_a = amount_a
+            .safe_mul_div(bps, BPS_SCALE)?
+            .min(self.max_amount_a);
+        let bounty_b = amount_b
+            .safe_mul_div(bps, BPS_SCALE)?
+            .min(self.max_amount_b);
+
+        Ok((bounty_a, bounty_b))
+    }
+
+    /// Bounty corresponding to the given harvested rewards of reward_index
+    pub fn rewards_amount(&self, reward_index: usize, amount: u64) -> Result<u64> {
+        Ok(amount
+            .safe_mul_div(u64::from(self.bps), BPS_SCALE)?
+            .min(self.max_rewards_amounts[reward_index]))
+    }
+}
+
+/// Whirlpool price observation
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default, Debug)]
+pub struct PriceObservation {
+    /// Slot of the observation
+    pub slot: u64,
+    /// Whirlpool current tick
+    pub tick: i32,
+}
+
+impl PriceObservation {
+    pub const SIZE: usize = 8 + 4;
+}
+
+/// Ring buffer of whirlpool price observations
+#[derive(AnchorSerialize, AnchorDeserialize, Copy, Clone, Default, Debug)]
+pub struct PriceHistory {
+    /// Index of the most recent observation
+    pub last_index: u8,
+    /// Number of stored observations
+    pub len: u8,
+    /// Stored observations
+    pub observations: [PriceObservation; NUM_PRICE_OBSERVATIONS],
+}
+
+impl PriceHistory {
+    pub const SIZE: usize = 1 + 1 + NUM_PRICE_OBSERVATIONS * PriceObservation::SIZE;
+
+    /// Store a new observation. Only the first observation of each slot is kept
+    pub fn record(&mut self, slot: u64, tick: i32) {
+        if self.len > 0 && self.observations[usize::from(self.last_index)].slot >= slot {
+            return;
+        }
+
+        let index = if self.len == 0 {
+            0
+        } else {
+            (usize::from(self.last_index) + 1) % NUM_PRICE_OBSERVATIONS
+        };
+
+        self.observations[index] = PriceObservation { slot, tick };
+        self.last_index = index as u8;
+
+        if usize::from(self.len) < NUM_PRICE_OBSERVATIONS {
+            self.len += 1;
+        }
+    }
+
+    /// Stored observations sorted from the oldest to the newest
+    pub fn sorted_observations(&self) -> Vec<PriceObservation> {
+        let len = usize::from(self.len);
+        let first = (usize::from(self.last_index) + NUM_PRICE_OBSERVATIONS + 1 - len)
+            % NUM_PRICE_OBSERVATIONS;
+
+        (0..len)
+            .map(|i| self.observations[(first + i) % NUM_PRICE_OBSERVATIONS])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn vault_with_fee(fee: u64) -> VaultAccount {
+        VaultAccount::new(VaultAccountParams {
+            id: 0,
+            bumps: Bumps::default(),
+            whirlpool_id: Pubkey::default(),
+            input_token_a_mint_pubkey: Pubkey::default(),
+            input_token_b_mint_pubkey: Pubkey::default(),
+            fee,
+            min_slots_for_reinvest: 0,
+        })
+    }
+
+    #[test]
+    fn test_treasury_fee() {
+        let mut uncharged = 0;
+
+        // Below the minimum the amount is carried forward
+        assert_eq!(
+            treasury_fee(10, 50, 100, 1_000, &mut uncharged, false).unwrap(),
+            0
+        );
+        assert_eq!(uncharged, 50);
+
+        // Charged with the carried amount once the minimum is exceeded
+        assert_eq!(
+            treasury_fee(10, 60, 100, 1_000, &mut uncharged, false).unwrap(),
+            11
+        );
+        assert_eq!(uncharged, 0);
+
+        // charge_all skips the minimum
+        assert_eq!(
+            treasury_fee(10, 5, 100, 1_000, &mut uncharged, true).unwrap(),
+            1
+        );
+        assert_eq!(uncharged, 0);
+    }
+
+    #[test]
+    fn test_treasury_fee_capped_by_available() {
+        let mut uncharged = 900;
+
+        // The carried 900 were reinvested, only 20 are available for a fee of 100
+        assert_eq!(
+            treasury_fee(10, 100, 100, 20, &mut uncharged, false).unwrap(),
+            20
+        );
+        // 200 were charged, the rest is carried forward
+        assert_eq!(uncharged, 800);
+
+        assert_eq!(
+            treasury_fee(10, 0, 100, 1_000, &mut uncharged, false).unwrap(),
+            80
+        );
+        assert_eq!(uncharged, 0);
+
+        // Nothing available, nothing charged
+        let mut uncharged = 500;
+        assert_eq!(
+            treasury_fee(10, 0, 100, 0, &mut uncharged, false).unwrap(),
+            0
+        );
+        assert_eq!(uncharged, 500);
+    }
+
+    #[test]
+    fn test_charge_fees() {
+        let mut vault = vault_with_fee(10);
+        vault.min_fees_token_a = 100;
+        vault.min_fees_token_b = 1_000;
+
+        assert!(vault.fees_meet_threshold(101, 0));
+        assert!(!vault.fees_meet_threshold(100, 1_000));
+
+        let fees = vault
+            .charge_fees(500, 400, u64::MAX, u64::MAX, false)
+            .unwrap();
+        assert_eq!(fees, (50, 0));
+        assert_eq!(vault.uncharged_fees_token_a, 0);
+        assert_eq!(vault.uncharged_fees_token_b, 400);
+
+        // The carried amount counts for the next threshold check
+        assert!(vault.fees_meet_threshold(0, 601));
+        let fees = vault
+            .charge_fees(0, 601, u64::MAX, u64::MAX, false)
+            .unwrap();
+        assert_eq!(fees, (0, 101));
+        assert_eq!(vault.uncharged_fees_token_b, 0);
+
+        // Without fee nothing is charged nor carried
+        let mut vault = vault_with_fee(0);
+        assert!(vault.fees_meet_threshold(0, 0));
+        assert_eq!(vault.charge_fees(500, 400, 0, 0, false).unwrap(), (0, 0));
+        assert_eq!(vault.uncharged_fees_token_a, 0);
+    }
+
+    #[test]
+    fn test_amount_after_fee() {
+        let mut vault = vault_with_fee(10);
+        vault.min_fees_token_a = 1_000;
+        vault.uncharged_fees_token_a = 999;
+
+        // The fee charges the carried amount too, exceeding the harvested amount
+        let (fee_a, _) = vault.charge_fees(2, 0, u64::MAX, u64::MAX, false).unwrap();
+        assert_eq!(fee_a, 101);
+        assert_eq!(amount_after_fee(2, fee_a), 0);
+        assert_eq!(
+            vault
+                .keeper_bounty
+                .amounts(amount_after_fee(2, fee_a), 0)
+                .unwrap(),
+            (0, 0)
+        );
+
+        assert_eq!(amount_after_fee(500, 50), 450);
+    }
+
+    #[test]
+    fn test_validate_min_fees() {
+        assert!(VaultAccount::validate_min_fees(FEE_SCALE, MAX_MIN_FEES).is_ok());
+        assert!(VaultAccount::validate_min_fees(FEE_SCALE - 1, FEE_SCALE).is_err());
+        assert!(VaultAccount::validate_min_fees(FEE_SCALE, MAX_MIN_FEES + 1).is_err());
+    }
+
+    #[test]
+    fn test_rewards_meet_threshold() {
+        let vault = vault_with_fee(10);
+        assert!(!vault.rewards_meet_threshold(0));
+        assert!(!vault.rewards_meet_threshold(MIN_REWARDS_AMOUNT));
+        assert!(vault.rewards_meet_threshold(MIN_REWARDS_AMOUNT + 1));
+
+        // Without fee there is no rounding to protect
+        let vault = vault_with_fee(0);
+        assert!(vault.rewards_meet_threshold(1));
+        assert_eq!(vault.rewards_treasury_fee(1_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_rewards_treasury_fee() {
+        let vault = vault_with_fee(10);
+        assert_eq!(vault.rewards_treasury_fee(1_000).unwrap(), 100);
+        // Rounded up in favour of the treasury
+        assert_eq!(vault.rewards_treasury_fee(101).unwrap(), 11);
+    }
+
+    #[test]
+    fn test_keeper_bounty_amounts() {
+        let keeper_bounty = KeeperBounty {
+            bps: 100,
+            max_amount_a: 5,
+            max_amount_b: u64::MAX,
+            max_rewards_amounts: [u64::MAX, 7, 0],
+        };
+
+        assert_eq!(keeper_bounty.amounts(1_000, 2_000).unwrap(), (5, 20));
+        assert_eq!(keeper_bounty.amounts(0, 99).unwrap(), (0, 0));
+        assert_eq!(keeper_bounty.rewards_amount(0, 2_000).unwrap(), 20);
+        assert_eq!(keeper_bounty.rewards_amount(1, 2_000).unwrap(), 7);
+        assert_eq!(keeper_bounty.rewards_amount(2, 2_000).unwrap(), 0);
+
+        let disabled = KeeperBounty::default();
+        assert_eq!(disabled.amounts(1_000, 2_000).unwrap(), (0, 0));
+        assert_eq!(disabled.rewards_amount(0, 1_000).unwrap(), 0);
+
+        let full = KeeperBounty {
+            bps: BPS_SCALE as u16,
+            max_amount_a: u64::MAX,
+            max_amount_b: u64::MAX,
+            max_rewards_amounts: [u64::MAX; WHIRLPOOL_NUM_REWARDS],
+        };
+        assert_eq!(
+            full.amounts(u64::MAX, u64::MAX).unwrap(),
+            (u64::MAX, u64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_keeper_bounties() {
+        let mut vault = vault_with_fee(10);
+        vault.keeper_bounty = KeeperBounty {
+            bps: 100,
+            max_amount_a: u64::MAX,
+            max_amount_b: u64::MAX,
+            ..KeeperBounty::default()
+        };
+
+        // Without keeper accounts the harvested amounts are carried forward
+        let bounties = vault
+            .keeper_bounties(1_000, 2_000, u64::MAX, u64::MAX, false)
+            .unwrap();
+        assert_eq!(bounties, (0, 0));
+        assert_eq!(vault.unpaid_bounty_token_a, 1_000);
+        assert_eq!(vault.unpaid_bounty_token_b, 2_000);
+
+        // The next call pays them once, capped by the available balances
+        let bounties = vault.keeper_bounties(0, 0, u64::MAX, 5, true).unwrap();
+        assert_eq!(bounties, (10, 5));
+        assert_eq!(vault.unpaid_bounty_token_a, 0);
+        assert_eq!(vault.unpaid_bounty_token_b, 0);
+        assert_eq!(
+            vault
+                .keeper_bounties(0, 0, u64::MAX, u64::MAX, true)
+                .unwrap(),
+            (0, 0)
+        );
+
+        // Nothing is carried while the bounty is disabled
+        vault.keeper_bounty = KeeperBounty::default();
+        vault.keeper_bounties(1_000, 0, 0, 0, false).unwrap();
+        assert_eq!(vault.unpaid_bounty_token_a, 0);
+    }
+
+    #[test]
+    fn test_keeper_bounty_validate() {
+        let mut keeper_bounty = KeeperBounty {
+            bps: BPS_SCALE as u16,
+            ..KeeperBounty::default()
+        };
+        assert!(keeper_bounty.validate().is_ok());
+
+        keeper_bounty.bps += 1;
+        assert!(keeper_bounty.validate().is_err());
+    }
+
+    fn hop(market: MarketRewards, mint_out: Pubkey) -> RouteHop {
+        RouteHop { market, mint_out }
+    }
+
+    #[test]
+    fn test_swap_route_new() {
+        let rewards_mint = Pubkey::new_unique();
+        let mint_mid = Pubkey::new_unique();
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+        let input_mints = [mint_a, mint_b];
+
+        let route = SwapRoute::new(
+            &[
+                hop(MarketRewards::OrcaV2, mint_mid),
+                hop(MarketRewards::Whirlpool, mint_a),
+            ],
+            rewards_mint,
+            mint_a,
+            &input_mints,
+        )
+        .unwrap();
+        assert!(route.is_set());
+        assert_eq!(route.hops().len(), 2);
+        assert_eq!(route.hops()[1].mint_out, mint_a);
+
+        // No hops resets the route
+        let route = SwapRoute::new(&[], rewards_mint, mint_a, &input_mints).unwrap();
+        assert!(!route.is_set());
+        assert!(route.hops().is_empty());
+    }
+
+    #[test]
+    fn test_swap_route_new_invalid() {
+        let rewards_mint = Pubkey::new_unique();
+        let mint_mid = Pubkey::new_unique();
+        let mint_a = Pubkey::new_unique();
+        let mint_b = Pubkey::new_unique();
+        let input_mints = [mint_a, mint_b];
+
+        let invalid_routes = [
+            // Too many hops
+            vec![hop(MarketRewards::Whirlpool, mint_a); MAX_ROUTE_HOPS + 1],
+            // Hops must be swapped in a market
+            vec![hop(MarketRewards::NotSet, mint_a)],
+            vec![hop(MarketRewards::Transfer, mint_a)],
+            vec![hop(MarketRewards::Distribute, mint_a)],
+            // The last hop must output the destination mint
+            vec![hop(MarketRewards::Whirlpool, mint_mid)],
+            // Mints cannot repeat
+            vec![
+                hop(MarketRewards::Whirlpool, rewards_mint),
+                hop(MarketRewards::Whirlpool, mint_a),
+            ],
+            vec![
+                hop(MarketRewards::OrcaV2, mint_mid),
+                hop(MarketRewards::Whirlpool, mint_mid),
+                hop(MarketRewards::Whirlpool, mint_a),
+            ],
+            // Intermediate mints cannot be the destination nor an input mint
+            vec![
+                hop(MarketRewards::Whirlpool, mint_a),
+                hop(MarketRewards::OrcaV2, mint_mid),
+                hop(MarketRewards::Whirlpool, mint_a),
+            ],
+            vec![
+                hop(MarketRewards::Whirlpool, mint_b),
+                hop(MarketRewards::Whirlpool, mint_a),
+            ],
+        ];
+
+        for hops in invalid_routes.iter() {
+            assert!(SwapRoute::new(hops, rewards_mint, mint_a, &input_mints).is_err());
+        }
+    }
+
+    #[test]
+    fn test_swap_chunking_amount_to_swap() {
+        let mut chunking = SwapChunking::default();
+
+        // Unlimited
+        assert_eq!(chunking.amount_to_swap(0), 0);
+        assert_eq!(chunking.amount_to_swap(1_000), 1_000);
+
+        chunking.max_amount_in = 400;
+        assert_eq!(chunking.amount_to_swap(300), 300);
+        assert_eq!(chunking.amount_to_swap(400), 400);
+        assert_eq!(chunking.amount_to_swap(1_000), 400);
+    }
+
+    #[test]
+    fn test_swap_chunking_can_swap() {
+        let mut chunking = SwapChunking {
+            min_slots_between_swaps: 10,
+            ..SwapChunking::default()
+        };
+
+        // The first swap is never delayed
+        assert!(chunking.can_swap(0));
+
+        chunking.record_swap(100).unwrap();
+        assert_eq!(chunking.last_swap_slot, 100);
+        assert_eq!(chunking.swap_count, 1);
+
+        assert!(!chunking.can_swap(100));
+        assert!(!chunking.can_swap(109));
+        assert!(chunking.can_swap(110));
+
+        chunking.record_swap(110).unwrap();
+        assert_eq!(chunking.last_swap_slot, 110);
+        assert_eq!(chunking.swap_count, 2);
+        assert!(!chunking.can_swap(115));
+
+        // Disabled spacing
+        chunking.min_slots_between_swaps = 0;
+        assert!(chunking.can_swap(110));
+    }
+
+    #[test]
+    fn test_swap_chunking_validate() {
+        let min_price = PriceBoundKind::MinPrice;
+        let mut chunking = SwapChunking::default();
+        assert!(chunking.validate(PriceBoundKind::Fixed).is_ok());
+
+        chunking.max_amount_in = MIN_REWARDS_AMOUNT;
+        assert!(chunking.validate(min_price).is_err());
+        chunking.max_amount_in = MIN_REWARDS_AMOUNT + 1;
+        assert!(chunking.validate(min_price).is_ok());
+        assert!(chunking.validate(PriceBoundKind::ReferencePool).is_ok());
+
+        // The fixed minimum amount out would apply to each tranche
+        assert!(chunking.validate(PriceBoundKind::Fixed).is_err());
+
+        chunking.min_slots_between_swaps = MAX_SLOTS_BETWEEN_SWAPS;
+        assert!(chunking.validate(min_price).is_ok());
+        chunking.min_slots_between_swaps += 1;
+        assert!(chunking.validate(min_price).is_err());
+    }
+
+    #[test]
+    fn test_check_price_deviation() {
+        let mut vault = vault_with_fee(0);
+        let sqrt_price = tick_math::sqrt_price_from_tick_index(0);
+
+        // No reference price at all
+        assert!(vault.check_price_deviation(sqrt_price, 100).is_err());
+
+        // The observation of the current slot has no weight yet
+        vault.price_history.record(100, 0);
+        assert!(vault.check_price_deviation(sqrt_price, 100).is_err());
+
+        vault.check_price_deviation(sqrt_price, 101).unwrap();
+        vault
+            .check_price_deviation(tick_math::sqrt_price_from_tick_index(100), 101)
+            .unwrap();
+        assert!(vault
+            .check_price_deviation(tick_math::sqrt_price_from_tick_index(5_000), 101)
+            .is_err());
+
+        // A stale history is not a reference anymore
+        vault
+            .check_price_deviation(sqrt_price, 100 + TWAP_WINDOW_SLOTS)
+            .unwrap();
+        assert!(vault
+            .check_price_deviation(sqrt_price, 101 + TWAP_WINDOW_SLOTS)
+            .is_err());
+
+        // Unless the check is disabled
+        vault.max_price_deviation_bps = 0;
+        vault
+            .check_price_deviation(sqrt_price, 101 + TWAP_WINDOW_SLOTS)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_distribute_rewards_requires_locked_lp() {
+        let mut vault = vault_with_fee(0);
+        assert!(vault.distribute_rewards(0, 1_000).is_err());
+
+        vault.rewards_locked_lp = 4_000;
+        vault.distribute_rewards(0, 1_000).unwrap();
+        assert_eq!(vault.rewards_per_share_x64, [1 << 62, 0, 0]);
+    }
+
+    #[test]
+    fn test_rewards_checkpoint_claim_once() {
+        let mut vault = vault_with_fee(0);
+        let mut checkpoint_a = UserRewardsCheckpoint::default();
+        let mut checkpoint_b = UserRewardsCheckpoint::default();
+
+        // Only locked LP tokens accrue, whoever holds the unlocked ones
+        checkpoint_a.lp_balance = 1_000;
+        vault.rewards_locked_lp = 1_000;
+        vault.distribute_rewards(0, 500).unwrap();
+
+        checkpoint_a.update(vault.rewards_per_share_x64).unwrap();
+        assert_eq!(checkpoint_a.take_owed_rewards(0), 500);
+        checkpoint_a.update(vault.rewards_per_share_x64).unwrap();
+        assert_eq!(checkpoint_a.take_owed_rewards(0), 0);
+
+        checkpoint_b.update(vault.rewards_per_share_x64).unwrap();
+        assert_eq!(checkpoint_b.take_owed_rewards(0), 0);
+
+        // A unlocks and B locks the same LP tokens, which keep accruing once
+        checkpoint_a.update(vault.rewards_per_share_x64).unwrap();
+        checkpoint_a.lp_balance = 0;
+        checkpoint_b.update(vault.rewards_per_share_x64).unwrap();
+        checkpoint_b.lp_balance = 1_000;
+        vault.distribute_rewards(0, 250).unwrap();
+
+        checkpoint_a.update(vault.rewards_per_share_x64).unwrap();
+        checkpoint_b.update(vault.rewards_per_share_x64).unwrap();
+        assert_eq!(checkpoint_a.take_owed_rewards(0), 0);
+        assert_eq!(checkpoint_b.take_owed_rewards(0), 250);
+    }
+
+    #[test]
+    fn test_rewards_checkpoint_unlock_then_claim() {
+        let mut vault = vault_with_fee(0);
+        let mut checkpoint = UserRewardsCheckpoint {
+            lp_balance: 1_000,
+            ..UserRewardsCheckpoint::default()
+        };
+        vault.rewards_locked_lp = 2_000;
+        vault.distribute_rewards(1, 1_000).unwrap();
+
+        // Unlocking to withdraw settles the rewards accrued so far
+        checkpoint.update(vault.rewards_per_share_x64).unwrap();
+        checkpoint.lp_balance = 0;
+        vault.rewards_locked_lp = 1_000;
+        vault.distribute_rewards(1, 1_000).unwrap();
+
+        checkpoint.update(vault.rewards_per_share_x64).unwrap();
+        assert_eq!(checkpoint.take_owed_rewards(0), 0);
+        assert_eq!(checkpoint.take_owed_rewards(1), 500);
+        assert_eq!(checkpoint.take_owed_rewards(1), 0);
+    }
+
+    #[test]
+    fn test_vault_owed_rewards() {
+        let mut vault = vault_with_fee(0);
+        let rewards_mint = Pubkey::new_unique();
+        vault.market_rewards[2].rewards_mint = rewards_mint;
+
+        let mut checkpoint = UserRewardsCheckpoint {
+            lp_balance: 1_000,
+            ..UserRewardsCheckpoint::default()
+        };
+        vault.rewards_locked_lp = 1_000;
+        vault.distribute_rewards(2, 300).unwrap();
+        assert!(!vault.has_owed_rewards(rewards_mint));
+
+        // Accrued rewards are owed until claimed
+        vault.checkpoint_rewards(&mut checkpoint).unwrap();
+        assert_eq!(vault.rewards_owed, [0, 0, 300]);
+        assert!(vault.has_owed_rewards(rewards_mint));
+        assert!(!vault.has_owed_rewards(Pubkey::new_unique()));
+
+        assert_eq!(vault.claim_rewards(&mut checkpoint, 2).unwrap(), 300);
+        assert_eq!(vault.rewards_owed, [0, 0, 0]);
+        assert!(!vault.has_owed_rewards(rewards_mint));
+    }
+
+    fn farm(reward_rate_per_slot: u64, end_slot: u64, total_staked: u64) -> Farm {
+        Farm {
+            reward_rate_per_slot,
+            end_slot,
+            total_staked,
+            ..Farm::default()
+        }
+    }
+
+    #[test]
+    fn test_farm_update() {
+        let mut farm = farm(10, 100, 1_000);
+
+        farm.update(40).unwrap();
+        assert_eq!(farm.last_update_slot, 40);
+        assert_eq!(farm.reward_per_share_x64, (400 << 64) / 1_000);
+
+        // Nothing emitted twice in the same slot
+        farm.update(40).unwrap();
+        assert_eq!(farm.reward_per_share_x64, (400 << 64) / 1_000);
+
+        // Emissions stop at end_slot
+        farm.update(250).unwrap();
+        assert_eq!(farm.last_update_slot, 100);
+        assert_eq!(
+            farm.reward_per_share_x64,
+            (400 << 64) / 1_000 + (600 << 64) / 1_000
+        );
+
+        farm.update(300).unwrap();
+        assert_eq!(farm.last_update_slot, 100);
+        assert_eq!(
+            farm.reward_per_share_x64,
+            (400 << 64) / 1_000 + (600 << 64) / 1_000
+        );
+    }
+
+    #[test]
+    fn test_farm_update_without_stake() {
+        let mut farm = farm(10, 100, 0);
+
+        // The emissions left are postponed while nothing is staked
+        farm.update(30).unwrap();
+        assert_eq!(farm.reward_per_share_x64, 0);
+        assert_eq!(farm.last_update_slot, 30);
+        assert_eq!(farm.end_slot, 130);
+
+        // Also past end_slot
+        farm.update(200).unwrap();
+        assert_eq!(farm.last_update_slot, 200);
+        assert_eq!(farm.end_slot, 300);
+
+        // All the rewards are emitted once staked
+        farm.total_staked = 500;
+        farm.update(400).unwrap();
+        assert_eq!(farm.last_update_slot, 300);
+        assert_eq!(farm.reward_per_share_x64, (1_000 << 64) / 500);
+
+        // A finished farm stays finished
+        farm.total_staked = 0;
+        farm.update(500).unwrap();
+        assert_eq!(farm.end_slot, 300);
+        assert_eq!(farm.last_update_slot, 500);
+    }
+
+    #[test]
+    fn test_farm_fund() {
+        let mut farm = farm(0, 0, 1_000);
+
+        assert!(farm.fund(99, 100, 10).is_err());
+
+        farm.fund(1_000, 100, 10).unwrap();
+        assert_eq!(farm.reward_rate_per_slot, 10);
+        assert_eq!(farm.end_slot, 110);
+        assert_eq!(farm.last_update_slot, 10);
+
+        // The rewards not emitted yet are rolled over
+        farm.fund(500, 100, 60).unwrap();
+        assert_eq!(farm.reward_rate_per_slot, 10);
+        assert_eq!(farm.end_slot, 160);
+        assert_eq!(farm.last_update_slot, 60);
+        assert_eq!(farm.reward_per_share_x64, (500 << 64) / 1_000);
+
+        // Including the ones postponed while nothing was staked
+        farm.total_staked = 0;
+        farm.update(100).unwrap();
+        farm.fund(0, 100, 160).unwrap();
+        assert_eq!(farm.reward_rate_per_slot, 10);
+        assert_eq!(farm.end_slot, 260);
+        assert_eq!(farm.reward_per_share_x64, (500 << 64) / 1_000);
+    }
+
+    #[test]
+    fn test_user_position_record_deposit() {
+        let mut user_position = UserPosition::default();
+
+        user_position.record_deposit(10, 1_000, 2_000, 100).unwrap();
+        user_position.record_deposit(20, 500, 1_000, 50).unwrap();
+
+        assert_eq!(user_position.first_deposit_slot, 10);
+        assert_eq!(user_position.deposited_token_a, 1_500);
+        assert_eq!(user_position.deposited_token_b, 3_000);
+        assert_eq!(user_position.cost_basis_token_a, 1_500);
+        assert_eq!(user_position.cost_basis_token_b, 3_000);
+        assert_eq!(user_position.lp_balance, 150);
+    }
+
+    #[test]
+    fn test_user_position_record_withdraw() {
+        let mut user_position = UserPosition::default();
+        user_position.record_deposit(10, 1_000, 2_000, 100).unwrap();
+
+        // A gain in token A and a loss in token B
+        user_position.record_withdraw(700, 900, 50).unwrap();
+        assert_eq!(user_position.withdrawn_token_a, 700);
+        assert_eq!(user_position.withdrawn_token_b, 900);
+        assert_eq!(user_position.cost_basis_token_a, 500);
+        assert_eq!(user_position.cost_basis_token_b, 1_000);
+        assert_eq!(user_position.realized_gain_token_a, 200);
+        assert_eq!(user_position.realized_gain_token_b, -100);
+        assert_eq!(user_position.lp_balance, 50);
+
+        // LP tokens received outside the vault realize no gains
+        user_position.record_withdraw(1_200, 4_000, 100).unwrap();
+        assert_eq!(user_position.withdrawn_token_a, 1_900);
+        assert_eq!(user_position.withdrawn_token_b, 4_900);
+        assert_eq!(user_position.cost_basis_token_a, 0);
+        assert_eq!(user_position.cost_basis_token_b, 0);
+        assert_eq!(user_position.realized_gain_token_a, 200 + 100);
+        assert_eq!(user_position.realized_gain_token_b, -100 + 1_000);
+        assert_eq!(user_position.lp_balance, 0);
+
+        user_position.record_withdraw(100, 100, 10).unwrap();
+        assert_eq!(user_position.realized_gain_token_a, 300);
+        assert_eq!(user_position.realized_gain_token_b, 900);
+    }
+}
@@ -45,6 +45,56 @@ describe("ggoldca", () => {
     id: VAULT_ID,
   };
 
+  // The published sdk passes the vault account of deposit and withdraw as read-only
+  async function depositWithdrawIx(
+    method: "deposit" | "withdraw",
+    lpAmount: anchor.BN,
+    amountA: anchor.BN,
+    amountB: anchor.BN
+  ): Promise<anchor.web3.Transaction> {
+    const [
+      {
+        vaultAccount,
+        vaultLpTokenMintPubkey,
+        vaultInputTokenAAccount,
+        vaultInputTokenBAccount,
+      },
+      poolData,
+    ] = await Promise.all([
+      ggClient.pdaAccounts.getVaultKeys(vaultId),
+      ggClient.fetcher.getWhirlpoolData(POOL_ID),
+    ]);
+
+    const vaultData = await program.account.vaultAccount.fetch(vaultAccount);
+    const [position, userLpTokenAccount, userTokenAAccount, userTokenBAccount] =
+      await Promise.all([
+        ggClient.pdaAccounts.getPositionAccounts(
+          vaultData.positions[0].pubkey,
+          vaultId
+        ),
+        getAssociatedTokenAddress(vaultLpTokenMintPubkey, userSigner),
+        getAssociatedTokenAddress(poolData.tokenMintA, userSigner),
+        getAssociatedTokenAddress(poolData.tokenMintB, userSigner),
+      ]);
+
+    return program.methods[method](lpAmount, amountA, amountB)
+      .accounts({
+        userSigner,
+        vaultAccount,
+        vaultLpTokenMintPubkey,
+        vaultInputTokenAAccount,
+        vaultInputTokenBAccount,
+        userLpTokenAccount,
+        userTokenAAccount,
+        userTokenBAccount,
+        whirlpoolProgramId: wh.ORCA_WHIRLPOOL_PROGRAM_ID,
+        position,
+        whTokenVaultA: poolData.tokenVaultA,
+        whTokenVaultB: poolData.tokenVaultB,
+      })
+      .transaction();
+  }
+
   it("Initialize vault", async () => {
     const ixs = await ggClient.initializeVaultIxs({
       userSigner,
@@ -129,6 +179,27 @@ describe("ggoldca", () => {
     console.log("open_position_2", txSig);
   });
 
+  it("Refresh price history", async () => {
+    const [{ vaultAccount }, poolData] = await Promise.all([
+      ggClient.pdaAccounts.getVaultKeys(vaultId),
+      ggClient.fetcher.getWhirlpoolData(POOL_ID),
+    ]);
+
+    const tx = await program.methods
+      .refreshPriceHistory(poolData.sqrtPrice, 100)
+      .accounts({ userSigner, vaultAccount, whirlpool: POOL_ID })
+      .transaction();
+
+    const txSig = await program.provider.sendAndConfirm(tx, [], CONFIRM_OPTS);
+    console.log("refresh_price_history", txSig);
+
+    // The observation of the current slot has no weight in the TWAP yet
+    const slot = await program.provider.connection.getSlot();
+    while ((await program.provider.connection.getSlot()) <= slot) {
+      await new Promise((resolve) => setTimeout(resolve, 100));
+    }
+  });
+
   it("Deposit", async () => {
     const lpAmount = new anchor.BN(1_000_000_000_000);
     const maxAmountA = new anchor.BN(1_000_000_000_000);
@@ -155,13 +226,7 @@ describe("ggoldca", () => {
         )
       )
       .add(
-        await ggClient.depositIx({
-          lpAmount,
-          maxAmountA,
-          maxAmountB,
-          userSigner,
-          vaultId,
-        })
+        await depositWithdrawIx("deposit", lpAmount, maxAmountA, maxAmountB)
       );
 
     const txSig = await program.provider.sendAndConfirm(tx, [], CONFIRM_OPTS);
@@ -192,15 +257,11 @@ describe("ggoldca", () => {
       []
     );
 
-    const tx = new anchor.web3.Transaction().add(transferIx).add(
-      await ggClient.depositIx({
-        lpAmount,
-        maxAmountA,
-        maxAmountB,
-        userSigner,
-        vaultId,
-      })
-    );
+    const tx = new anchor.web3.Transaction()
+      .add(transferIx)
+      .add(
+        await depositWithdrawIx("deposit", lpAmount, maxAmountA, maxAmountB)
+      );
 
     const txSig = await program.provider.sendAndConfirm(tx, [], CONFIRM_OPTS);
     console.log("deposit_with_tokens_in_vault", txSig);
@@ -315,14 +376,30 @@ describe("ggoldca", () => {
       newPosition.whirlpool
     );
 
+    // The published sdk builds the rebalance without its price arguments
     const tx = new anchor.web3.Transaction()
       .add(COMPUTE_BUDGET_IX)
       .add(
-        await ggClient.rebalanceIx({
-          userSigner,
-          vaultId,
-          newPosition: position2,
-        })
+        await program.methods
+          .rebalance(
+            new anchor.BN(0),
+            new anchor.BN(0),
+            poolData.sqrtPrice,
+            100,
+            false
+          )
+          .accounts({
+            userSigner,
+            vaultAccount,
+            vaultInputTokenAAccount,
+            vaultInputTokenBAccount,
+            whirlpoolProgramId: wh.ORCA_WHIRLPOOL_PROGRAM_ID,
+            tokenVaultA: poolData.tokenVaultA,
+            tokenVaultB: poolData.tokenVaultB,
+            currentPosition,
+            newPosition,
+          })
+          .transaction()
       )
       .add(
         await program.methods
@@ -666,13 +743,7 @@ describe("ggoldca", () => {
     const minAmountB = new anchor.BN(0);
 
     const tx = new anchor.web3.Transaction().add(
-      await ggClient.withdrawIx({
-        lpAmount,
-        minAmountA,
-        minAmountB,
-        userSigner,
-        vaultId,
-      })
+      await depositWithdrawIx("withdraw", lpAmount, minAmountA, minAmountB)
     );
 
     const txSig = await program.provider.sendAndConfirm(tx, [], CONFIRM_OPTS);
@@ -717,13 +788,7 @@ describe("ggoldca", () => {
     const maxAmountB = new anchor.BN(1_000_000_000_000);
 
     const tx = new anchor.web3.Transaction().add(
-      await ggClient.depositIx({
-        lpAmount,
-        maxAmountA,
-        maxAmountB,
-        userSigner,
-        vaultId,
-      })
+      await depositWithdrawIx("deposit", lpAmount, maxAmountA, maxAmountB)
     );
 
     try {